@@ -0,0 +1,12 @@
+-- Drops the constraints again. References cleared on the way up stay
+-- cleared; foreign_key_repairs, which lists them, is dropped too.
+DROP INDEX idx_tasks_assigned_to;
+DROP INDEX idx_employees_manager_id;
+
+ALTER TABLE tasks DROP CONSTRAINT tasks_assigned_to_fkey;
+
+ALTER TABLE employees
+    DROP CONSTRAINT employees_manager_id_fkey,
+    ALTER COLUMN manager_id TYPE TEXT USING CAST(manager_id AS TEXT);
+
+DROP TABLE foreign_key_repairs;
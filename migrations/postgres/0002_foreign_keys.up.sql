@@ -0,0 +1,37 @@
+-- References to employees that do not exist are recorded here, then
+-- cleared, so the constraints below can hold.
+CREATE TABLE foreign_key_repairs (
+    id INTEGER GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    table_name TEXT NOT NULL,
+    row_id INTEGER NOT NULL,
+    column_name TEXT NOT NULL,
+    missing_value TEXT NOT NULL,
+    repaired_at TIMESTAMPTZ NOT NULL
+);
+
+INSERT INTO foreign_key_repairs (table_name, row_id, column_name, missing_value, repaired_at)
+SELECT 'tasks', id, 'assigned_to', CAST(assigned_to AS TEXT), now()
+FROM tasks
+WHERE assigned_to IS NOT NULL AND assigned_to NOT IN (SELECT id FROM employees)
+ORDER BY id;
+
+INSERT INTO foreign_key_repairs (table_name, row_id, column_name, missing_value, repaired_at)
+SELECT 'employees', id, 'manager_id', manager_id, now()
+FROM employees
+WHERE manager_id IS NOT NULL AND manager_id NOT IN (SELECT CAST(id AS TEXT) FROM employees)
+ORDER BY id;
+
+UPDATE tasks SET assigned_to = NULL
+WHERE assigned_to IS NOT NULL AND assigned_to NOT IN (SELECT id FROM employees);
+
+UPDATE employees SET manager_id = NULL
+WHERE manager_id IS NOT NULL AND manager_id NOT IN (SELECT CAST(id AS TEXT) FROM employees);
+
+ALTER TABLE employees
+    ALTER COLUMN manager_id TYPE INTEGER USING CAST(manager_id AS INTEGER),
+    ADD FOREIGN KEY (manager_id) REFERENCES employees (id);
+
+ALTER TABLE tasks ADD FOREIGN KEY (assigned_to) REFERENCES employees (id);
+
+CREATE INDEX idx_employees_manager_id ON employees(manager_id);
+CREATE INDEX idx_tasks_assigned_to ON tasks(assigned_to);
@@ -0,0 +1,13 @@
+DROP TABLE task_attachments;
+DROP TABLE task_tags;
+DROP TABLE store_assignment_settings;
+DROP TABLE task_dependencies;
+DROP TABLE task_template_occurrences;
+DROP TABLE task_templates;
+DROP TABLE task_comment_mentions;
+DROP TABLE task_comment_revisions;
+DROP TABLE task_comments;
+DROP TABLE task_events;
+DROP TABLE employees;
+DROP TABLE tasks;
+DROP TABLE objects;
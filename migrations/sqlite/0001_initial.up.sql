@@ -0,0 +1,157 @@
+CREATE TABLE objects (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    email TEXT NOT NULL,
+    age INTEGER,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_objects_email ON objects(email);
+CREATE INDEX idx_objects_created_at ON objects(created_at);
+
+CREATE TABLE tasks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    title TEXT NOT NULL,
+    description TEXT,
+    priority_level TEXT CHECK (priority_level IN ('Low', 'Medium', 'High')),
+    status TEXT NOT NULL DEFAULT 'ToDo'
+        CHECK (status IN ('ToDo', 'InProgress', 'Blocked', 'InReview', 'Done', 'Cancelled')),
+    assigned_to INTEGER,
+    store_id INTEGER,
+    parent_id INTEGER,
+    status_reason TEXT,
+    start_at DATETIME,
+    due_at DATETIME,
+    completed_at DATETIME,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL,
+    deleted_at DATETIME,
+    position REAL
+);
+
+CREATE INDEX idx_tasks_due_at ON tasks(due_at);
+CREATE INDEX idx_tasks_store_id ON tasks(store_id);
+CREATE INDEX idx_tasks_parent_id ON tasks(parent_id);
+CREATE INDEX idx_tasks_status_position ON tasks(status, position);
+
+CREATE TABLE employees (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    external_id TEXT,
+    first_name TEXT NOT NULL,
+    last_name TEXT NOT NULL,
+    store_id INTEGER NOT NULL,
+    email TEXT,
+    manager_id TEXT,
+    deactivated_at DATETIME
+);
+
+CREATE INDEX idx_employees_store_id ON employees(store_id);
+CREATE INDEX idx_employees_external_id ON employees(external_id);
+CREATE INDEX idx_employees_name ON employees(last_name, first_name);
+
+CREATE TABLE task_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER NOT NULL,
+    event_type TEXT NOT NULL,
+    actor TEXT NOT NULL,
+    changes TEXT NOT NULL,
+    created_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_task_events_task_id ON task_events(task_id, created_at);
+
+CREATE TABLE task_comments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER NOT NULL,
+    author TEXT NOT NULL,
+    body TEXT NOT NULL,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL,
+    edited_at DATETIME,
+    deleted_at DATETIME
+);
+
+CREATE INDEX idx_task_comments_task_id ON task_comments(task_id, created_at);
+
+CREATE TABLE task_comment_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    comment_id INTEGER NOT NULL,
+    body TEXT NOT NULL,
+    edited_by TEXT NOT NULL,
+    edited_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_task_comment_revisions_comment_id ON task_comment_revisions(comment_id);
+
+CREATE TABLE task_comment_mentions (
+    comment_id INTEGER NOT NULL,
+    employee_id INTEGER NOT NULL,
+    PRIMARY KEY (comment_id, employee_id)
+);
+
+CREATE INDEX idx_task_comment_mentions_employee_id ON task_comment_mentions(employee_id);
+
+CREATE TABLE task_templates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    title TEXT NOT NULL,
+    description TEXT,
+    priority_level TEXT,
+    assigned_to INTEGER,
+    store_ids TEXT NOT NULL DEFAULT '[]',
+    rrule TEXT NOT NULL,
+    starts_on DATE NOT NULL,
+    due_in_hours INTEGER,
+    active BOOLEAN NOT NULL DEFAULT 1,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL
+);
+
+-- One row per generated task; store_key is the store id, or 0 for
+-- templates without stores, so the key stays unique without NULLs.
+CREATE TABLE task_template_occurrences (
+    template_id INTEGER NOT NULL,
+    store_key INTEGER NOT NULL,
+    occurs_on DATE NOT NULL,
+    task_id INTEGER,
+    created_at DATETIME NOT NULL,
+    PRIMARY KEY (template_id, store_key, occurs_on)
+);
+
+CREATE TABLE task_dependencies (
+    task_id INTEGER NOT NULL,
+    depends_on_id INTEGER NOT NULL,
+    created_at DATETIME NOT NULL,
+    PRIMARY KEY (task_id, depends_on_id)
+);
+
+CREATE INDEX idx_task_dependencies_depends_on_id ON task_dependencies(depends_on_id);
+
+CREATE TABLE store_assignment_settings (
+    store_id INTEGER PRIMARY KEY,
+    strategy TEXT NOT NULL,
+    last_assigned_employee_id INTEGER,
+    updated_at DATETIME NOT NULL
+);
+
+CREATE TABLE task_tags (
+    task_id INTEGER NOT NULL,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (task_id, tag)
+);
+
+CREATE INDEX idx_task_tags_tag ON task_tags(tag);
+
+CREATE TABLE task_attachments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER NOT NULL,
+    file_name TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    sha256 TEXT NOT NULL,
+    uploaded_by TEXT NOT NULL,
+    created_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_task_attachments_task_id ON task_attachments(task_id);
+CREATE INDEX idx_task_attachments_sha256 ON task_attachments(sha256);
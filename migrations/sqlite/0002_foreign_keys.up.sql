@@ -0,0 +1,94 @@
+-- References to employees that do not exist are recorded here, then
+-- cleared, so the constraints below can hold.
+CREATE TABLE foreign_key_repairs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    table_name TEXT NOT NULL,
+    row_id INTEGER NOT NULL,
+    column_name TEXT NOT NULL,
+    missing_value TEXT NOT NULL,
+    repaired_at DATETIME NOT NULL
+);
+
+INSERT INTO foreign_key_repairs (table_name, row_id, column_name, missing_value, repaired_at)
+SELECT 'tasks', id, 'assigned_to', CAST(assigned_to AS TEXT), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+FROM tasks
+WHERE assigned_to IS NOT NULL AND assigned_to NOT IN (SELECT id FROM employees)
+ORDER BY id;
+
+INSERT INTO foreign_key_repairs (table_name, row_id, column_name, missing_value, repaired_at)
+SELECT 'employees', id, 'manager_id', manager_id, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+FROM employees
+WHERE manager_id IS NOT NULL AND manager_id NOT IN (SELECT CAST(id AS TEXT) FROM employees)
+ORDER BY id;
+
+-- SQLite cannot add a constraint to an existing table, so both tables are
+-- rebuilt. Checks wait for the commit while the old tables are swapped out.
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TABLE employees_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    external_id TEXT,
+    first_name TEXT NOT NULL,
+    last_name TEXT NOT NULL,
+    store_id INTEGER NOT NULL,
+    email TEXT,
+    -- Renaming the table below renames this reference with it.
+    manager_id INTEGER REFERENCES employees_new (id),
+    deactivated_at DATETIME
+);
+
+INSERT INTO employees_new (id, external_id, first_name, last_name, store_id, email, manager_id, deactivated_at)
+SELECT id, external_id, first_name, last_name, store_id, email,
+       CASE WHEN manager_id IN (SELECT CAST(id AS TEXT) FROM employees) THEN CAST(manager_id AS INTEGER) END,
+       deactivated_at
+FROM employees;
+
+-- Keep the id sequence, so ids of deleted rows are not handed out again.
+DELETE FROM sqlite_sequence WHERE name = 'employees_new';
+INSERT INTO sqlite_sequence (name, seq) SELECT 'employees_new', seq FROM sqlite_sequence WHERE name = 'employees';
+
+DROP TABLE employees;
+ALTER TABLE employees_new RENAME TO employees;
+
+CREATE INDEX idx_employees_store_id ON employees(store_id);
+CREATE INDEX idx_employees_external_id ON employees(external_id);
+CREATE INDEX idx_employees_name ON employees(last_name, first_name);
+CREATE INDEX idx_employees_manager_id ON employees(manager_id);
+
+CREATE TABLE tasks_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    title TEXT NOT NULL,
+    description TEXT,
+    priority_level TEXT CHECK (priority_level IN ('Low', 'Medium', 'High')),
+    status TEXT NOT NULL DEFAULT 'ToDo'
+        CHECK (status IN ('ToDo', 'InProgress', 'Blocked', 'InReview', 'Done', 'Cancelled')),
+    assigned_to INTEGER REFERENCES employees (id),
+    store_id INTEGER,
+    parent_id INTEGER,
+    status_reason TEXT,
+    start_at DATETIME,
+    due_at DATETIME,
+    completed_at DATETIME,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL,
+    deleted_at DATETIME,
+    position REAL
+);
+
+INSERT INTO tasks_new (id, title, description, priority_level, status, assigned_to, store_id, parent_id, status_reason, start_at, due_at, completed_at, created_at, updated_at, deleted_at, position)
+SELECT id, title, description, priority_level, status,
+       CASE WHEN assigned_to IN (SELECT id FROM employees) THEN assigned_to END,
+       store_id, parent_id, status_reason, start_at, due_at, completed_at, created_at, updated_at, deleted_at, position
+FROM tasks;
+
+DELETE FROM sqlite_sequence WHERE name = 'tasks_new';
+INSERT INTO sqlite_sequence (name, seq) SELECT 'tasks_new', seq FROM sqlite_sequence WHERE name = 'tasks';
+
+DROP TABLE tasks;
+ALTER TABLE tasks_new RENAME TO tasks;
+
+CREATE INDEX idx_tasks_due_at ON tasks(due_at);
+CREATE INDEX idx_tasks_store_id ON tasks(store_id);
+CREATE INDEX idx_tasks_parent_id ON tasks(parent_id);
+CREATE INDEX idx_tasks_status_position ON tasks(status, position);
+CREATE INDEX idx_tasks_assigned_to ON tasks(assigned_to);
@@ -1,12 +1,31 @@
+use crate::models::attachment::AttachmentLimits;
+use crate::models::task::SlaPolicy;
+use sqlx::sqlite::{SqliteJournalMode, SqliteSynchronous};
 use std::env;
+use std::time::Duration;
 
 #[derive(Clone)]
 #[allow(dead_code)]
 pub struct AppConfig {
     pub database_url: String,
+    pub database_pool: PoolConfig,
     pub server_host: String,
     pub server_port: u16,
     pub log_level: String,
+    pub max_import_bytes: usize,
+    pub task_sla: SlaPolicy,
+    pub template_lookahead_days: u64,
+    pub template_scheduler_interval_secs: u64,
+    pub task_retention_days: i64,
+    pub task_purge_interval_secs: u64,
+    pub attachment_dir: String,
+    pub attachment_limits: AttachmentLimits,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AppConfig {
@@ -14,12 +33,55 @@ impl AppConfig {
         Self {
             database_url: env::var("DATABASE_URL")
                 .unwrap_or_else(|_| "sqlite:data/app.db".to_string()),
+            database_pool: PoolConfig::from_env(),
             server_host: env::var("SERVER_HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
             server_port: env::var("SERVER_PORT")
                 .unwrap_or_else(|_| "8080".to_string())
                 .parse()
                 .expect("SERVER_PORT must be a valid number"),
             log_level: env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string()),
+            max_import_bytes: env::var("MAX_IMPORT_BYTES")
+                .unwrap_or_else(|_| "10485760".to_string())
+                .parse()
+                .expect("MAX_IMPORT_BYTES must be a valid number"),
+            task_sla: SlaPolicy {
+                low_hours: sla_hours("TASK_SLA_LOW_HOURS", SlaPolicy::default().low_hours),
+                medium_hours: sla_hours("TASK_SLA_MEDIUM_HOURS", SlaPolicy::default().medium_hours),
+                high_hours: sla_hours("TASK_SLA_HIGH_HOURS", SlaPolicy::default().high_hours),
+            },
+            template_lookahead_days: env::var("TEMPLATE_LOOKAHEAD_DAYS")
+                .unwrap_or_else(|_| "14".to_string())
+                .parse()
+                .expect("TEMPLATE_LOOKAHEAD_DAYS must be a valid number"),
+            template_scheduler_interval_secs: env::var("TEMPLATE_SCHEDULER_INTERVAL_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .expect("TEMPLATE_SCHEDULER_INTERVAL_SECS must be a valid number"),
+            task_retention_days: env::var("TASK_RETENTION_DAYS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .expect("TASK_RETENTION_DAYS must be a valid number"),
+            task_purge_interval_secs: env::var("TASK_PURGE_INTERVAL_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .expect("TASK_PURGE_INTERVAL_SECS must be a valid number"),
+            attachment_dir: env::var("ATTACHMENT_DIR")
+                .unwrap_or_else(|_| "data/attachments".to_string()),
+            attachment_limits: AttachmentLimits {
+                max_bytes: env::var("MAX_ATTACHMENT_BYTES")
+                    .unwrap_or_else(|_| AttachmentLimits::default().max_bytes.to_string())
+                    .parse()
+                    .expect("MAX_ATTACHMENT_BYTES must be a valid number"),
+                allowed_types: env::var("ATTACHMENT_TYPES")
+                    .map(|types| {
+                        types
+                            .split(',')
+                            .map(|t| t.trim().to_ascii_lowercase())
+                            .filter(|t| !t.is_empty())
+                            .collect()
+                    })
+                    .unwrap_or_else(|_| AttachmentLimits::default().allowed_types),
+            },
         }
     }
 
@@ -27,3 +89,108 @@ impl AppConfig {
         format!("{}:{}", self.server_host, self.server_port)
     }
 }
+
+/// Connection pool limits for either backend, plus the pragmas every SQLite
+/// connection is opened with.
+#[derive(Clone, Debug)]
+pub struct PoolConfig {
+    pub max_connections: u32,
+    pub acquire_timeout: Duration,
+    /// `None` keeps idle connections open indefinitely.
+    pub idle_timeout: Option<Duration>,
+    pub sqlite_journal_mode: SqliteJournalMode,
+    /// How long a connection waits on a locked database before failing.
+    pub sqlite_busy_timeout: Duration,
+    pub sqlite_synchronous: SqliteSynchronous,
+    pub sqlite_foreign_keys: bool,
+}
+
+impl Default for PoolConfig {
+    /// WAL lets readers work alongside the single writer, and NORMAL
+    /// synchronous is durable under WAL except across power loss.
+    fn default() -> Self {
+        Self {
+            max_connections: 5,
+            acquire_timeout: Duration::from_secs(30),
+            idle_timeout: Some(Duration::from_secs(600)),
+            sqlite_journal_mode: SqliteJournalMode::Wal,
+            sqlite_busy_timeout: Duration::from_secs(5),
+            sqlite_synchronous: SqliteSynchronous::Normal,
+            sqlite_foreign_keys: true,
+        }
+    }
+}
+
+impl PoolConfig {
+    fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            max_connections: env::var("DATABASE_MAX_CONNECTIONS")
+                .map(|value| {
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|&max| max > 0)
+                        .expect("DATABASE_MAX_CONNECTIONS must be a positive number")
+                })
+                .unwrap_or(default.max_connections),
+            acquire_timeout: env::var("DATABASE_ACQUIRE_TIMEOUT_SECS")
+                .map(|value| {
+                    Duration::from_secs(
+                        value
+                            .parse()
+                            .expect("DATABASE_ACQUIRE_TIMEOUT_SECS must be a valid number"),
+                    )
+                })
+                .unwrap_or(default.acquire_timeout),
+            idle_timeout: env::var("DATABASE_IDLE_TIMEOUT_SECS")
+                .map(|value| {
+                    let secs: u64 = value
+                        .parse()
+                        .expect("DATABASE_IDLE_TIMEOUT_SECS must be a valid number");
+                    (secs > 0).then(|| Duration::from_secs(secs))
+                })
+                .unwrap_or(default.idle_timeout),
+            sqlite_journal_mode: env::var("SQLITE_JOURNAL_MODE")
+                .map(|value| {
+                    value.parse().expect(
+                        "SQLITE_JOURNAL_MODE must be one of DELETE, TRUNCATE, PERSIST, MEMORY, WAL or OFF",
+                    )
+                })
+                .unwrap_or(default.sqlite_journal_mode),
+            sqlite_busy_timeout: env::var("SQLITE_BUSY_TIMEOUT_MS")
+                .map(|value| {
+                    Duration::from_millis(
+                        value
+                            .parse()
+                            .expect("SQLITE_BUSY_TIMEOUT_MS must be a valid number"),
+                    )
+                })
+                .unwrap_or(default.sqlite_busy_timeout),
+            sqlite_synchronous: env::var("SQLITE_SYNCHRONOUS")
+                .map(|value| {
+                    value
+                        .parse()
+                        .expect("SQLITE_SYNCHRONOUS must be one of OFF, NORMAL, FULL or EXTRA")
+                })
+                .unwrap_or(default.sqlite_synchronous),
+            sqlite_foreign_keys: env::var("SQLITE_FOREIGN_KEYS")
+                .map(|value| {
+                    value
+                        .parse()
+                        .expect("SQLITE_FOREIGN_KEYS must be true or false")
+                })
+                .unwrap_or(default.sqlite_foreign_keys),
+        }
+    }
+}
+
+fn sla_hours(name: &str, default: i64) -> i64 {
+    env::var(name)
+        .map(|value| {
+            value
+                .parse()
+                .unwrap_or_else(|_| panic!("{} must be a valid number", name))
+        })
+        .unwrap_or(default)
+}
@@ -0,0 +1,600 @@
+use futures_util::future;
+use futures_util::stream::{self, BoxStream};
+use futures_util::{StreamExt, TryStreamExt};
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgArguments, PgPool, PgRow};
+use sqlx::sqlite::{SqliteArguments, SqlitePool, SqliteRow};
+use sqlx::{Arguments, ColumnIndex, Decode, Encode, FromRow, Postgres, Row, Sqlite, Type};
+use std::borrow::Cow;
+
+/// The database engines the repositories run on, chosen by the scheme of
+/// `DATABASE_URL`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    Sqlite,
+    Postgres,
+}
+
+impl Backend {
+    pub fn from_url(url: &str) -> Result<Self, sqlx::Error> {
+        if url.starts_with("sqlite:") {
+            Ok(Backend::Sqlite)
+        } else if url.starts_with("postgres:") || url.starts_with("postgresql:") {
+            Ok(Backend::Postgres)
+        } else {
+            Err(sqlx::Error::Configuration(
+                "DATABASE_URL must start with sqlite: or postgres:".into(),
+            ))
+        }
+    }
+}
+
+/// A connection pool for either backend. Repositories hold one of these and
+/// write their SQL once, with `?` placeholders, in the dialect both accept.
+#[derive(Clone, Debug)]
+pub enum DbPool {
+    Sqlite(SqlitePool),
+    Postgres(PgPool),
+}
+
+impl DbPool {
+    pub fn backend(&self) -> Backend {
+        match self {
+            DbPool::Sqlite(_) => Backend::Sqlite,
+            DbPool::Postgres(_) => Backend::Postgres,
+        }
+    }
+
+    /// Every transaction here writes, so SQLite ones take the write lock up
+    /// front. A deferred transaction that has already read cannot wait for
+    /// another writer to finish; it fails with `database is locked` at once
+    /// rather than honouring the busy timeout.
+    pub async fn begin(&self) -> Result<DbTransaction, sqlx::Error> {
+        Ok(match self {
+            DbPool::Sqlite(pool) => {
+                DbTransaction::Sqlite(pool.begin_with("BEGIN IMMEDIATE").await?)
+            }
+            DbPool::Postgres(pool) => DbTransaction::Postgres(pool.begin().await?),
+        })
+    }
+
+    pub async fn close(&self) {
+        match self {
+            DbPool::Sqlite(pool) => pool.close().await,
+            DbPool::Postgres(pool) => pool.close().await,
+        }
+    }
+}
+
+impl From<SqlitePool> for DbPool {
+    fn from(pool: SqlitePool) -> Self {
+        DbPool::Sqlite(pool)
+    }
+}
+
+impl From<PgPool> for DbPool {
+    fn from(pool: PgPool) -> Self {
+        DbPool::Postgres(pool)
+    }
+}
+
+/// A transaction on a `DbPool`, rolled back if dropped without `commit`.
+pub enum DbTransaction {
+    Sqlite(sqlx::Transaction<'static, Sqlite>),
+    Postgres(sqlx::Transaction<'static, Postgres>),
+}
+
+impl DbTransaction {
+    pub fn backend(&self) -> Backend {
+        match self {
+            DbTransaction::Sqlite(_) => Backend::Sqlite,
+            DbTransaction::Postgres(_) => Backend::Postgres,
+        }
+    }
+
+    pub async fn commit(self) -> Result<(), sqlx::Error> {
+        match self {
+            DbTransaction::Sqlite(tx) => tx.commit().await,
+            DbTransaction::Postgres(tx) => tx.commit().await,
+        }
+    }
+
+    pub async fn rollback(self) -> Result<(), sqlx::Error> {
+        match self {
+            DbTransaction::Sqlite(tx) => tx.rollback().await,
+            DbTransaction::Postgres(tx) => tx.rollback().await,
+        }
+    }
+}
+
+/// Where a query runs: straight on the pool, or inside a transaction.
+pub enum DbExecutor<'c> {
+    Pool(&'c DbPool),
+    Transaction(&'c mut DbTransaction),
+}
+
+impl DbExecutor<'_> {
+    pub fn backend(&self) -> Backend {
+        match self {
+            DbExecutor::Pool(pool) => pool.backend(),
+            DbExecutor::Transaction(tx) => tx.backend(),
+        }
+    }
+}
+
+impl<'c> From<&'c DbPool> for DbExecutor<'c> {
+    fn from(pool: &'c DbPool) -> Self {
+        DbExecutor::Pool(pool)
+    }
+}
+
+impl<'c> From<&'c mut DbTransaction> for DbExecutor<'c> {
+    fn from(tx: &'c mut DbTransaction) -> Self {
+        DbExecutor::Transaction(tx)
+    }
+}
+
+/// A row from either backend.
+pub enum DbRow {
+    Sqlite(SqliteRow),
+    Postgres(PgRow),
+}
+
+impl DbRow {
+    pub fn try_get<'r, T, I>(&'r self, index: I) -> Result<T, sqlx::Error>
+    where
+        T: Decodable<'r>,
+        I: ColumnIndex<SqliteRow> + ColumnIndex<PgRow>,
+    {
+        match self {
+            DbRow::Sqlite(row) => row.try_get(index),
+            DbRow::Postgres(row) => row.try_get(index),
+        }
+    }
+
+    /// Like `try_get`, but panics if the column is missing or of another type.
+    pub fn get<'r, T, I>(&'r self, index: I) -> T
+    where
+        T: Decodable<'r>,
+        I: ColumnIndex<SqliteRow> + ColumnIndex<PgRow>,
+    {
+        match self {
+            DbRow::Sqlite(row) => row.get(index),
+            DbRow::Postgres(row) => row.get(index),
+        }
+    }
+
+    /// Maps the whole row onto `T`, as `query_as` would.
+    pub fn decode<T: FromDbRow>(&self) -> Result<T, sqlx::Error> {
+        match self {
+            DbRow::Sqlite(row) => T::from_row(row),
+            DbRow::Postgres(row) => T::from_row(row),
+        }
+    }
+}
+
+/// The outcome of `execute`.
+#[derive(Clone, Copy, Debug)]
+pub struct DbQueryResult {
+    rows_affected: u64,
+}
+
+impl DbQueryResult {
+    pub fn rows_affected(&self) -> u64 {
+        self.rows_affected
+    }
+}
+
+/// A value that can be bound on either backend.
+pub trait Bindable<'q>:
+    Encode<'q, Sqlite> + Type<Sqlite> + Encode<'q, Postgres> + Type<Postgres> + Send + 'q
+{
+}
+
+impl<'q, T> Bindable<'q> for T where
+    T: Encode<'q, Sqlite> + Type<Sqlite> + Encode<'q, Postgres> + Type<Postgres> + Send + 'q
+{
+}
+
+/// A column value that can be read from either backend.
+pub trait Decodable<'r>:
+    Decode<'r, Sqlite> + Type<Sqlite> + Decode<'r, Postgres> + Type<Postgres>
+{
+}
+
+impl<'r, T> Decodable<'r> for T where
+    T: Decode<'r, Sqlite> + Type<Sqlite> + Decode<'r, Postgres> + Type<Postgres>
+{
+}
+
+/// A struct that can be mapped from a row of either backend.
+pub trait FromDbRow:
+    for<'r> FromRow<'r, SqliteRow> + for<'r> FromRow<'r, PgRow> + Send + Unpin
+{
+}
+
+impl<T> FromDbRow for T where
+    T: for<'r> FromRow<'r, SqliteRow> + for<'r> FromRow<'r, PgRow> + Send + Unpin
+{
+}
+
+/// A bound value waiting for the backend to be known, so it is encoded only
+/// for the one the query actually runs on.
+trait Bind<'q>: Send + 'q {
+    fn add_sqlite(self: Box<Self>, arguments: &mut SqliteArguments<'q>) -> Result<(), BoxDynError>;
+    fn add_postgres(self: Box<Self>, arguments: &mut PgArguments) -> Result<(), BoxDynError>;
+}
+
+impl<'q, T: Bindable<'q>> Bind<'q> for T {
+    fn add_sqlite(self: Box<Self>, arguments: &mut SqliteArguments<'q>) -> Result<(), BoxDynError> {
+        arguments.add(*self)
+    }
+
+    fn add_postgres(self: Box<Self>, arguments: &mut PgArguments) -> Result<(), BoxDynError> {
+        Arguments::<'q>::add(arguments, *self)
+    }
+}
+
+/// SQL and its bind values, shared by the three query flavours.
+struct Statement<'q> {
+    sql: Cow<'q, str>,
+    binds: Vec<Box<dyn Bind<'q> + 'q>>,
+}
+
+impl<'q> Statement<'q> {
+    fn new(sql: &'q str) -> Self {
+        Self {
+            sql: Cow::Borrowed(sql),
+            binds: Vec::new(),
+        }
+    }
+}
+
+fn sqlite_arguments<'q>(
+    binds: Vec<Box<dyn Bind<'q> + 'q>>,
+) -> Result<SqliteArguments<'q>, sqlx::Error> {
+    let mut arguments = SqliteArguments::default();
+    for bind in binds {
+        bind.add_sqlite(&mut arguments)
+            .map_err(sqlx::Error::Encode)?;
+    }
+    Ok(arguments)
+}
+
+fn postgres_arguments<'q>(binds: Vec<Box<dyn Bind<'q> + 'q>>) -> Result<PgArguments, sqlx::Error> {
+    let mut arguments = PgArguments::default();
+    for bind in binds {
+        bind.add_postgres(&mut arguments)
+            .map_err(sqlx::Error::Encode)?;
+    }
+    Ok(arguments)
+}
+
+/// Rewrites `?` placeholders to PostgreSQL's `$1`, `$2`, ... leaving quoted
+/// strings and identifiers alone.
+pub fn postgres_sql(sql: &str) -> Cow<'_, str> {
+    if !sql.contains('?') {
+        return Cow::Borrowed(sql);
+    }
+
+    let mut rewritten = String::with_capacity(sql.len() + 16);
+    let mut quote = None;
+    let mut index = 0;
+    for c in sql.chars() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(open), _) if c == open => quote = None,
+            (None, '?') => {
+                index += 1;
+                rewritten.push('$');
+                rewritten.push_str(&index.to_string());
+                continue;
+            }
+            _ => {}
+        }
+        rewritten.push(c);
+    }
+    Cow::Owned(rewritten)
+}
+
+/// Runs `$method` on the sqlx query built by `$build` against whichever
+/// backend `$executor` is, mapping the outcome with the matching `$wrap`.
+macro_rules! run {
+    ($statement:expr, $executor:expr, $build:path, $method:ident, $wrap_sqlite:expr, $wrap_postgres:expr) => {{
+        let Statement { sql, binds } = $statement;
+        match $executor.into() {
+            DbExecutor::Pool(DbPool::Sqlite(pool)) => $build(&sql, sqlite_arguments(binds)?)
+                .$method(pool)
+                .await
+                .map($wrap_sqlite),
+            DbExecutor::Transaction(DbTransaction::Sqlite(tx)) => {
+                $build(&sql, sqlite_arguments(binds)?)
+                    .$method(&mut **tx)
+                    .await
+                    .map($wrap_sqlite)
+            }
+            DbExecutor::Pool(DbPool::Postgres(pool)) => {
+                let sql = postgres_sql(&sql);
+                $build(&sql, postgres_arguments(binds)?)
+                    .$method(pool)
+                    .await
+                    .map($wrap_postgres)
+            }
+            DbExecutor::Transaction(DbTransaction::Postgres(tx)) => {
+                let sql = postgres_sql(&sql);
+                $build(&sql, postgres_arguments(binds)?)
+                    .$method(&mut **tx)
+                    .await
+                    .map($wrap_postgres)
+            }
+        }
+    }};
+}
+
+/// Counterpart of `sqlx::query` that runs on either backend.
+pub fn query(sql: &str) -> Query<'_> {
+    Query(Statement::new(sql))
+}
+
+/// Counterpart of `sqlx::query_as` that runs on either backend.
+pub fn query_as<O: FromDbRow>(sql: &str) -> QueryAs<'_, O> {
+    QueryAs(Statement::new(sql), std::marker::PhantomData)
+}
+
+/// Counterpart of `sqlx::query_scalar` that runs on either backend.
+pub fn query_scalar<O>(sql: &str) -> QueryScalar<'_, O>
+where
+    O: for<'r> Decodable<'r> + Send + Unpin,
+{
+    QueryScalar(Statement::new(sql), std::marker::PhantomData)
+}
+
+/// Counterpart of `sqlx::raw_sql`: runs one or more statements without
+/// binds, as migration scripts do.
+pub async fn execute_script<'c>(
+    executor: impl Into<DbExecutor<'c>>,
+    sql: &str,
+) -> Result<(), sqlx::Error> {
+    let raw = sqlx::raw_sql(sql);
+    match executor.into() {
+        DbExecutor::Pool(DbPool::Sqlite(pool)) => raw.execute(pool).await.map(|_| ()),
+        DbExecutor::Transaction(DbTransaction::Sqlite(tx)) => {
+            raw.execute(&mut **tx).await.map(|_| ())
+        }
+        DbExecutor::Pool(DbPool::Postgres(pool)) => raw.execute(pool).await.map(|_| ()),
+        DbExecutor::Transaction(DbTransaction::Postgres(tx)) => {
+            raw.execute(&mut **tx).await.map(|_| ())
+        }
+    }
+}
+
+pub struct Query<'q>(Statement<'q>);
+
+impl<'q> Query<'q> {
+    pub fn bind<T: Bindable<'q>>(mut self, value: T) -> Self {
+        self.0.binds.push(Box::new(value));
+        self
+    }
+
+    pub async fn execute<'c>(
+        self,
+        executor: impl Into<DbExecutor<'c>>,
+    ) -> Result<DbQueryResult, sqlx::Error> {
+        run!(
+            self.0,
+            executor,
+            sqlx::query_with,
+            execute,
+            |result| DbQueryResult {
+                rows_affected: result.rows_affected(),
+            },
+            |result| DbQueryResult {
+                rows_affected: result.rows_affected(),
+            }
+        )
+    }
+
+    pub async fn fetch_one<'c>(
+        self,
+        executor: impl Into<DbExecutor<'c>>,
+    ) -> Result<DbRow, sqlx::Error> {
+        run!(
+            self.0,
+            executor,
+            sqlx::query_with,
+            fetch_one,
+            DbRow::Sqlite,
+            DbRow::Postgres
+        )
+    }
+
+    pub async fn fetch_optional<'c>(
+        self,
+        executor: impl Into<DbExecutor<'c>>,
+    ) -> Result<Option<DbRow>, sqlx::Error> {
+        run!(
+            self.0,
+            executor,
+            sqlx::query_with,
+            fetch_optional,
+            |row| row.map(DbRow::Sqlite),
+            |row| row.map(DbRow::Postgres)
+        )
+    }
+
+    pub async fn fetch_all<'c>(
+        self,
+        executor: impl Into<DbExecutor<'c>>,
+    ) -> Result<Vec<DbRow>, sqlx::Error> {
+        run!(
+            self.0,
+            executor,
+            sqlx::query_with,
+            fetch_all,
+            |rows| rows.into_iter().map(DbRow::Sqlite).collect(),
+            |rows| rows.into_iter().map(DbRow::Postgres).collect()
+        )
+    }
+
+    /// Streams rows as the database produces them. The stream borrows the
+    /// query, which keeps the SQL rewritten for PostgreSQL alive.
+    pub fn fetch<'e>(&'e mut self, pool: &'e DbPool) -> BoxStream<'e, Result<DbRow, sqlx::Error>>
+    where
+        'q: 'e,
+    {
+        let binds = std::mem::take(&mut self.0.binds);
+        if pool.backend() == Backend::Postgres {
+            self.0.sql = Cow::Owned(postgres_sql(&self.0.sql).into_owned());
+        }
+        let sql: &'e str = &self.0.sql;
+
+        match pool {
+            DbPool::Sqlite(pool) => match sqlite_arguments(binds) {
+                Ok(arguments) => sqlx::query_with(sql, arguments)
+                    .fetch(pool)
+                    .map_ok(DbRow::Sqlite)
+                    .boxed(),
+                Err(e) => stream::once(future::ready(Err(e))).boxed(),
+            },
+            DbPool::Postgres(pool) => match postgres_arguments(binds) {
+                Ok(arguments) => sqlx::query_with(sql, arguments)
+                    .fetch(pool)
+                    .map_ok(DbRow::Postgres)
+                    .boxed(),
+                Err(e) => stream::once(future::ready(Err(e))).boxed(),
+            },
+        }
+    }
+}
+
+pub struct QueryAs<'q, O>(Statement<'q>, std::marker::PhantomData<O>);
+
+impl<'q, O: FromDbRow> QueryAs<'q, O> {
+    pub fn bind<T: Bindable<'q>>(mut self, value: T) -> Self {
+        self.0.binds.push(Box::new(value));
+        self
+    }
+
+    pub async fn fetch_one<'c>(
+        self,
+        executor: impl Into<DbExecutor<'c>>,
+    ) -> Result<O, sqlx::Error> {
+        run!(
+            self.0,
+            executor,
+            sqlx::query_as_with,
+            fetch_one,
+            |o| o,
+            |o| o
+        )
+    }
+
+    pub async fn fetch_optional<'c>(
+        self,
+        executor: impl Into<DbExecutor<'c>>,
+    ) -> Result<Option<O>, sqlx::Error> {
+        run!(
+            self.0,
+            executor,
+            sqlx::query_as_with,
+            fetch_optional,
+            |o| o,
+            |o| o
+        )
+    }
+
+    pub async fn fetch_all<'c>(
+        self,
+        executor: impl Into<DbExecutor<'c>>,
+    ) -> Result<Vec<O>, sqlx::Error> {
+        run!(
+            self.0,
+            executor,
+            sqlx::query_as_with,
+            fetch_all,
+            |o| o,
+            |o| o
+        )
+    }
+}
+
+pub struct QueryScalar<'q, O>(Statement<'q>, std::marker::PhantomData<O>);
+
+impl<'q, O> QueryScalar<'q, O>
+where
+    O: for<'r> Decodable<'r> + Send + Unpin,
+{
+    pub fn bind<T: Bindable<'q>>(mut self, value: T) -> Self {
+        self.0.binds.push(Box::new(value));
+        self
+    }
+
+    pub async fn fetch_one<'c>(
+        self,
+        executor: impl Into<DbExecutor<'c>>,
+    ) -> Result<O, sqlx::Error> {
+        run!(
+            self.0,
+            executor,
+            sqlx::query_scalar_with,
+            fetch_one,
+            |o| o,
+            |o| o
+        )
+    }
+
+    pub async fn fetch_optional<'c>(
+        self,
+        executor: impl Into<DbExecutor<'c>>,
+    ) -> Result<Option<O>, sqlx::Error> {
+        run!(
+            self.0,
+            executor,
+            sqlx::query_scalar_with,
+            fetch_optional,
+            |o| o,
+            |o| o
+        )
+    }
+
+    pub async fn fetch_all<'c>(
+        self,
+        executor: impl Into<DbExecutor<'c>>,
+    ) -> Result<Vec<O>, sqlx::Error> {
+        run!(
+            self.0,
+            executor,
+            sqlx::query_scalar_with,
+            fetch_all,
+            |o| o,
+            |o| o
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_postgres_placeholders_skip_quoted_text() {
+        assert_eq!(
+            postgres_sql("SELECT * FROM t WHERE a = ? AND b LIKE '?%' AND \"c?\" IN (?, ?)"),
+            "SELECT * FROM t WHERE a = $1 AND b LIKE '?%' AND \"c?\" IN ($2, $3)"
+        );
+        assert!(matches!(postgres_sql("SELECT 1"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_backend_from_url() {
+        assert_eq!(
+            Backend::from_url("sqlite::memory:").unwrap(),
+            Backend::Sqlite
+        );
+        assert_eq!(
+            Backend::from_url("postgres://localhost/app").unwrap(),
+            Backend::Postgres
+        );
+        assert!(Backend::from_url("mysql://localhost/app").is_err());
+    }
+}
@@ -0,0 +1,629 @@
+use sqlx::SqliteConnection;
+
+/// Whether the database was created before versioned migrations, by the
+/// `CREATE TABLE IF NOT EXISTS` list that `upgrade` preserves.
+pub(super) async fn is_legacy(conn: &mut SqliteConnection) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name IN ('objects', 'tasks', 'employees')",
+    )
+    .fetch_one(&mut *conn)
+    .await
+}
+
+/// Brings a database created before versioned migrations up to the schema
+/// of the initial migration. Kept as it was when versioning began; new schema
+/// changes go in `migrations/` instead.
+pub(super) async fn upgrade(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS objects (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            email TEXT NOT NULL,
+            age INTEGER,
+            created_at DATETIME NOT NULL,
+            updated_at DATETIME NOT NULL
+        )
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_objects_email ON objects(email)
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_objects_created_at ON objects(created_at)
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS tasks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            title TEXT NOT NULL,
+            description TEXT,
+            priority_level TEXT CHECK (priority_level IN ('Low', 'Medium', 'High')),
+            status TEXT NOT NULL DEFAULT 'ToDo'
+                CHECK (status IN ('ToDo', 'InProgress', 'Blocked', 'InReview', 'Done', 'Cancelled')),
+            assigned_to INTEGER,
+            completed_at DATETIME,
+            created_at DATETIME NOT NULL,
+            updated_at DATETIME NOT NULL,
+            deleted_at DATETIME
+        )
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS employees (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            external_id TEXT,
+            first_name TEXT NOT NULL,
+            last_name TEXT NOT NULL,
+            store_id INTEGER NOT NULL,
+            email TEXT,
+            manager_id TEXT
+        )
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_employees_store_id ON employees(store_id)
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_employees_external_id ON employees(external_id)
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_employees_name ON employees(last_name, first_name)
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS task_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id INTEGER NOT NULL,
+            event_type TEXT NOT NULL,
+            actor TEXT NOT NULL,
+            changes TEXT NOT NULL,
+            created_at DATETIME NOT NULL
+        )
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_task_events_task_id ON task_events(task_id, created_at)
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS task_comments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id INTEGER NOT NULL,
+            author TEXT NOT NULL,
+            body TEXT NOT NULL,
+            created_at DATETIME NOT NULL,
+            updated_at DATETIME NOT NULL,
+            edited_at DATETIME,
+            deleted_at DATETIME
+        )
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_task_comments_task_id ON task_comments(task_id, created_at)
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS task_comment_revisions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            comment_id INTEGER NOT NULL,
+            body TEXT NOT NULL,
+            edited_by TEXT NOT NULL,
+            edited_at DATETIME NOT NULL
+        )
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_task_comment_revisions_comment_id ON task_comment_revisions(comment_id)
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS task_comment_mentions (
+            comment_id INTEGER NOT NULL,
+            employee_id INTEGER NOT NULL,
+            PRIMARY KEY (comment_id, employee_id)
+        )
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_task_comment_mentions_employee_id ON task_comment_mentions(employee_id)
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS task_templates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            title TEXT NOT NULL,
+            description TEXT,
+            priority_level TEXT,
+            assigned_to INTEGER,
+            store_ids TEXT NOT NULL DEFAULT '[]',
+            rrule TEXT NOT NULL,
+            starts_on DATE NOT NULL,
+            due_in_hours INTEGER,
+            active BOOLEAN NOT NULL DEFAULT 1,
+            created_at DATETIME NOT NULL,
+            updated_at DATETIME NOT NULL
+        )
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    // One row per generated task; store_key is the store id, or 0 for
+    // templates without stores, so the key stays unique without NULLs.
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS task_template_occurrences (
+            template_id INTEGER NOT NULL,
+            store_key INTEGER NOT NULL,
+            occurs_on DATE NOT NULL,
+            task_id INTEGER,
+            created_at DATETIME NOT NULL,
+            PRIMARY KEY (template_id, store_key, occurs_on)
+        )
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS task_dependencies (
+            task_id INTEGER NOT NULL,
+            depends_on_id INTEGER NOT NULL,
+            created_at DATETIME NOT NULL,
+            PRIMARY KEY (task_id, depends_on_id)
+        )
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_task_dependencies_depends_on_id ON task_dependencies(depends_on_id)
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS store_assignment_settings (
+            store_id INTEGER PRIMARY KEY,
+            strategy TEXT NOT NULL,
+            last_assigned_employee_id INTEGER,
+            updated_at DATETIME NOT NULL
+        )
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS task_tags (
+            task_id INTEGER NOT NULL,
+            tag TEXT NOT NULL,
+            PRIMARY KEY (task_id, tag)
+        )
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_task_tags_tag ON task_tags(tag)
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS task_attachments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id INTEGER NOT NULL,
+            file_name TEXT NOT NULL,
+            content_type TEXT NOT NULL,
+            size_bytes INTEGER NOT NULL,
+            sha256 TEXT NOT NULL,
+            uploaded_by TEXT NOT NULL,
+            created_at DATETIME NOT NULL
+        )
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_task_attachments_task_id ON task_attachments(task_id)
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_task_attachments_sha256 ON task_attachments(sha256)
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    add_column(conn, "employees", "email", "TEXT").await?;
+
+    add_column(conn, "employees", "manager_id", "INTEGER").await?;
+
+    add_column(conn, "employees", "deactivated_at", "DATETIME").await?;
+
+    add_column(conn, "tasks", "status_reason", "TEXT").await?;
+
+    add_column(conn, "tasks", "start_at", "DATETIME").await?;
+
+    add_column(conn, "tasks", "due_at", "DATETIME").await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tasks_due_at ON tasks(due_at)")
+        .execute(&mut *conn)
+        .await?;
+
+    add_column(conn, "tasks", "store_id", "INTEGER").await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tasks_store_id ON tasks(store_id)")
+        .execute(&mut *conn)
+        .await?;
+
+    add_column(conn, "tasks", "parent_id", "INTEGER").await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tasks_parent_id ON tasks(parent_id)")
+        .execute(&mut *conn)
+        .await?;
+
+    add_column(conn, "tasks", "position", "REAL").await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tasks_status_position ON tasks(status, position)")
+        .execute(&mut *conn)
+        .await?;
+
+    enforce_task_encoding(conn).await?;
+
+    Ok(())
+}
+
+/// Statuses and priorities are stored as their variant names. Older rows may
+/// hold the display form ("To Do", "In Progress") or another spelling; those
+/// are rewritten, and a `tasks` table created before the CHECK constraints
+/// existed is rebuilt with them. Values that cannot be mapped stop the
+/// migration rather than being guessed at.
+async fn enforce_task_encoding(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE tasks SET status = CASE LOWER(REPLACE(REPLACE(TRIM(status), ' ', ''), '_', ''))
+            WHEN 'todo' THEN 'ToDo'
+            WHEN 'inprogress' THEN 'InProgress'
+            WHEN 'blocked' THEN 'Blocked'
+            WHEN 'inreview' THEN 'InReview'
+            WHEN 'done' THEN 'Done'
+            WHEN 'cancelled' THEN 'Cancelled'
+            WHEN 'canceled' THEN 'Cancelled'
+            ELSE status
+        END
+        WHERE status NOT IN ('ToDo', 'InProgress', 'Blocked', 'InReview', 'Done', 'Cancelled')
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE tasks SET priority_level = CASE LOWER(TRIM(priority_level))
+            WHEN 'low' THEN 'Low'
+            WHEN 'medium' THEN 'Medium'
+            WHEN 'high' THEN 'High'
+            WHEN '' THEN NULL
+            ELSE priority_level
+        END
+        WHERE priority_level NOT IN ('Low', 'Medium', 'High')
+        "#,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let invalid: Vec<(i32, Option<String>, Option<String>)> = sqlx::query_as(
+        r#"
+        SELECT id, status, priority_level FROM tasks
+        WHERE status IS NULL
+            OR status NOT IN ('ToDo', 'InProgress', 'Blocked', 'InReview', 'Done', 'Cancelled')
+            OR priority_level NOT IN ('Low', 'Medium', 'High')
+        ORDER BY id
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    if !invalid.is_empty() {
+        let rows: Vec<String> = invalid
+            .iter()
+            .map(|(id, status, priority)| {
+                format!("task {} (status {:?}, priority {:?})", id, status, priority)
+            })
+            .collect();
+        return Err(sqlx::Error::Decode(
+            format!(
+                "Cannot migrate tasks with unknown statuses or priorities: {}",
+                rows.join(", ")
+            )
+            .into(),
+        ));
+    }
+
+    let table: String =
+        sqlx::query_scalar("SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'tasks'")
+            .fetch_one(&mut *conn)
+            .await?;
+    if !table.contains("CHECK") {
+        let indexes: Vec<String> = sqlx::query_scalar(
+            "SELECT sql FROM sqlite_master WHERE type = 'index' AND tbl_name = 'tasks' AND sql IS NOT NULL",
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+        let sequence: Option<i64> =
+            sqlx::query_scalar("SELECT seq FROM sqlite_sequence WHERE name = 'tasks'")
+                .fetch_optional(&mut *conn)
+                .await?;
+
+        const COLUMNS: &str = "id, title, description, priority_level, status, assigned_to, store_id, parent_id, status_reason, start_at, due_at, completed_at, created_at, updated_at, deleted_at, position";
+        sqlx::query(
+            r#"
+            CREATE TABLE tasks_checked (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                title TEXT NOT NULL,
+                description TEXT,
+                priority_level TEXT CHECK (priority_level IN ('Low', 'Medium', 'High')),
+                status TEXT NOT NULL DEFAULT 'ToDo'
+                    CHECK (status IN ('ToDo', 'InProgress', 'Blocked', 'InReview', 'Done', 'Cancelled')),
+                assigned_to INTEGER,
+                store_id INTEGER,
+                parent_id INTEGER,
+                status_reason TEXT,
+                start_at DATETIME,
+                due_at DATETIME,
+                completed_at DATETIME,
+                created_at DATETIME NOT NULL,
+                updated_at DATETIME NOT NULL,
+                deleted_at DATETIME,
+                position REAL
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query(&format!(
+            "INSERT INTO tasks_checked ({0}) SELECT {0} FROM tasks",
+            COLUMNS
+        ))
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query("DROP TABLE tasks").execute(&mut *conn).await?;
+        sqlx::query("ALTER TABLE tasks_checked RENAME TO tasks")
+            .execute(&mut *conn)
+            .await?;
+        for index in indexes {
+            sqlx::query(&index).execute(&mut *conn).await?;
+        }
+        // Keep ids of purged tasks from being handed out again.
+        if let Some(sequence) = sequence {
+            let updated =
+                sqlx::query("UPDATE sqlite_sequence SET seq = MAX(seq, ?) WHERE name = 'tasks'")
+                    .bind(sequence)
+                    .execute(&mut *conn)
+                    .await?;
+            if updated.rows_affected() == 0 {
+                sqlx::query("INSERT INTO sqlite_sequence (name, seq) VALUES ('tasks', ?)")
+                    .bind(sequence)
+                    .execute(&mut *conn)
+                    .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn add_column(
+    conn: &mut SqliteConnection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), sqlx::Error> {
+    let exists: bool = sqlx::query_scalar(&format!(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('{}') WHERE name = ?",
+        table
+    ))
+    .bind(column)
+    .fetch_one(&mut *conn)
+    .await?;
+    if !exists {
+        sqlx::query(&format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            table, column, definition
+        ))
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::run_migrations;
+    use sqlx::SqlitePool;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn legacy_pool(rows: &[(&str, Option<&str>)]) -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(
+            r#"
+            CREATE TABLE tasks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                title TEXT NOT NULL,
+                description TEXT,
+                priority_level TEXT,
+                status TEXT NOT NULL DEFAULT 'ToDo',
+                assigned_to INTEGER,
+                completed_at DATETIME,
+                created_at DATETIME NOT NULL,
+                updated_at DATETIME NOT NULL,
+                deleted_at DATETIME
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        for (status, priority) in rows {
+            sqlx::query(
+                "INSERT INTO tasks (title, status, priority_level, created_at, updated_at) VALUES ('Task', ?, ?, '2026-01-01', '2026-01-01')",
+            )
+            .bind(status)
+            .bind(priority)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_migration_repairs_task_encoding_and_adds_checks() {
+        let pool = legacy_pool(&[
+            ("To Do", Some("low")),
+            ("In Progress", None),
+            ("in_review", Some("High")),
+            ("Done", Some("Medium")),
+        ])
+        .await;
+        sqlx::query("DELETE FROM tasks WHERE id = 4")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        run_migrations(&pool.clone().into()).await.unwrap();
+        run_migrations(&pool.clone().into()).await.unwrap();
+
+        let rows: Vec<(i32, String, Option<String>)> =
+            sqlx::query_as("SELECT id, status, priority_level FROM tasks ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            rows,
+            [
+                (1, "ToDo".to_string(), Some("Low".to_string())),
+                (2, "InProgress".to_string(), None),
+                (3, "InReview".to_string(), Some("High".to_string())),
+            ]
+        );
+
+        let rejected = sqlx::query(
+            "INSERT INTO tasks (title, status, created_at, updated_at) VALUES ('Task', 'To Do', '2026-01-01', '2026-01-01')",
+        )
+        .execute(&pool)
+        .await;
+        assert!(rejected.is_err());
+
+        let id = sqlx::query(
+            "INSERT INTO tasks (title, created_at, updated_at) VALUES ('Task', '2026-01-01', '2026-01-01')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+        assert_eq!(id, 5);
+    }
+
+    #[tokio::test]
+    async fn test_migration_refuses_unknown_statuses() {
+        let pool = legacy_pool(&[("ToDo", None), ("Someday", Some("High"))]).await;
+
+        let error = run_migrations(&pool.clone().into())
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("task 2"), "{}", error);
+    }
+}
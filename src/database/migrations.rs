@@ -0,0 +1,494 @@
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::FromRow;
+use std::fmt;
+
+use super::backend::{self, Backend, DbPool, DbTransaction};
+use super::legacy;
+
+/// A schema change, embedded from `migrations/<backend>/`. Every migration
+/// has a script per backend; one without down scripts cannot be reverted.
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sqlite: Scripts,
+    pub postgres: Scripts,
+}
+
+pub struct Scripts {
+    pub up: &'static str,
+    pub down: Option<&'static str>,
+}
+
+/// The up and down scripts of `migrations/<backend>/<file>.{up,down}.sql`.
+macro_rules! scripts {
+    ($backend:literal, $file:literal) => {
+        Scripts {
+            up: include_str!(concat!(
+                "../../migrations/",
+                $backend,
+                "/",
+                $file,
+                ".up.sql"
+            )),
+            down: Some(include_str!(concat!(
+                "../../migrations/",
+                $backend,
+                "/",
+                $file,
+                ".down.sql"
+            ))),
+        }
+    };
+}
+
+/// Every migration, in the order they apply: the one definition of the
+/// schema, shared by the server, the tests and the `migrate` command.
+/// Applied migrations must never be edited; add a new one instead.
+pub static MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial",
+        sqlite: scripts!("sqlite", "0001_initial"),
+        postgres: scripts!("postgres", "0001_initial"),
+    },
+    Migration {
+        version: 2,
+        name: "foreign_keys",
+        sqlite: scripts!("sqlite", "0002_foreign_keys"),
+        postgres: scripts!("postgres", "0002_foreign_keys"),
+    },
+];
+
+const CREATE_MIGRATIONS_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS _migrations (
+        version INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        checksum TEXT NOT NULL,
+        applied_at DATETIME NOT NULL
+    )
+"#;
+
+const CREATE_MIGRATIONS_TABLE_POSTGRES: &str = r#"
+    CREATE TABLE IF NOT EXISTS _migrations (
+        version BIGINT PRIMARY KEY,
+        name TEXT NOT NULL,
+        checksum TEXT NOT NULL,
+        applied_at TIMESTAMPTZ NOT NULL
+    )
+"#;
+
+#[derive(Debug)]
+pub enum MigrationError {
+    Database(sqlx::Error),
+    /// The database has a migration this build does not know about, so it
+    /// was migrated by a newer build.
+    DatabaseAhead {
+        version: i64,
+        latest: i64,
+    },
+    /// An applied migration's script differs from the one in this build.
+    ChecksumMismatch {
+        version: i64,
+        name: String,
+    },
+    Irreversible {
+        version: i64,
+        name: &'static str,
+    },
+    UnknownVersion(i64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: &'static str,
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+#[derive(FromRow)]
+struct AppliedMigration {
+    version: i64,
+    name: String,
+    checksum: String,
+    applied_at: DateTime<Utc>,
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::Database(e) => write!(f, "Database error: {}", e),
+            MigrationError::DatabaseAhead { version, latest } => write!(
+                f,
+                "Database has migration {} but this build only knows migrations up to {}; run a newer build",
+                version, latest
+            ),
+            MigrationError::ChecksumMismatch { version, name } => write!(
+                f,
+                "Migration {:04} ({}) has changed since it was applied",
+                version, name
+            ),
+            MigrationError::Irreversible { version, name } => {
+                write!(f, "Migration {:04} ({}) has no down script", version, name)
+            }
+            MigrationError::UnknownVersion(version) => {
+                write!(f, "There is no migration {}", version)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+impl From<sqlx::Error> for MigrationError {
+    fn from(e: sqlx::Error) -> Self {
+        MigrationError::Database(e)
+    }
+}
+
+impl Migration {
+    pub fn scripts(&self, backend: Backend) -> &Scripts {
+        match backend {
+            Backend::Sqlite => &self.sqlite,
+            Backend::Postgres => &self.postgres,
+        }
+    }
+
+    /// Hex SHA-256 of the backend's up script, recorded when the migration
+    /// is applied so that later edits to it are caught.
+    pub fn checksum(&self, backend: Backend) -> String {
+        format!("{:x}", Sha256::digest(self.scripts(backend).up.as_bytes()))
+    }
+
+    pub fn reversible(&self) -> bool {
+        self.sqlite.down.is_some() && self.postgres.down.is_some()
+    }
+}
+
+/// Every migration with when it was applied, after checking the applied ones
+/// against this build. Nothing is written, so it doubles as a dry run.
+pub async fn migration_status(pool: &DbPool) -> Result<Vec<MigrationStatus>, MigrationError> {
+    let applied = applied_migrations(pool).await?;
+
+    Ok(MIGRATIONS
+        .iter()
+        .map(|migration| MigrationStatus {
+            version: migration.version,
+            name: migration.name,
+            applied_at: applied
+                .iter()
+                .find(|a| a.version == migration.version)
+                .map(|a| a.applied_at),
+        })
+        .collect())
+}
+
+/// Applies pending migrations in order, each in its own transaction, and
+/// returns the versions applied. Refuses to touch a database that is ahead
+/// of this build or whose applied scripts have changed.
+pub async fn run_migrations(pool: &DbPool) -> Result<Vec<i64>, MigrationError> {
+    let create_table = match pool.backend() {
+        Backend::Sqlite => CREATE_MIGRATIONS_TABLE,
+        Backend::Postgres => CREATE_MIGRATIONS_TABLE_POSTGRES,
+    };
+    backend::execute_script(pool, create_table).await?;
+    let applied = applied_migrations(pool).await?;
+
+    let mut versions = Vec::new();
+    for migration in MIGRATIONS
+        .iter()
+        .filter(|m| !applied.iter().any(|a| a.version == m.version))
+    {
+        let mut tx = pool.begin().await?;
+        // SQLite databases from before versioning already have most of the
+        // initial schema; they are upgraded in place instead.
+        let is_legacy = match &mut tx {
+            DbTransaction::Sqlite(conn) if migration.version == 1 => {
+                legacy::is_legacy(conn).await?
+            }
+            _ => false,
+        };
+        match &mut tx {
+            DbTransaction::Sqlite(conn) if is_legacy => legacy::upgrade(conn).await?,
+            _ => backend::execute_script(&mut tx, migration.scripts(pool.backend()).up).await?,
+        }
+        backend::query(
+            "INSERT INTO _migrations (version, name, checksum, applied_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(migration.version)
+        .bind(migration.name)
+        .bind(migration.checksum(pool.backend()))
+        .bind(Utc::now())
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+
+        log::info!(
+            "Applied migration {:04} ({})",
+            migration.version,
+            migration.name
+        );
+        versions.push(migration.version);
+    }
+
+    Ok(versions)
+}
+
+/// The applied migrations newer than `target`, newest first: what
+/// `revert_migrations` would undo. `target` 0 reverts everything.
+pub async fn revert_plan(
+    pool: &DbPool,
+    target: i64,
+) -> Result<Vec<&'static Migration>, MigrationError> {
+    if target != 0 && !MIGRATIONS.iter().any(|m| m.version == target) {
+        return Err(MigrationError::UnknownVersion(target));
+    }
+
+    let applied = applied_migrations(pool).await?;
+    let plan: Vec<&'static Migration> = MIGRATIONS
+        .iter()
+        .rev()
+        .filter(|m| m.version > target && applied.iter().any(|a| a.version == m.version))
+        .collect();
+    if let Some(migration) = plan.iter().find(|m| !m.reversible()) {
+        return Err(MigrationError::Irreversible {
+            version: migration.version,
+            name: migration.name,
+        });
+    }
+
+    Ok(plan)
+}
+
+/// Runs the down scripts of applied migrations newer than `target`, newest
+/// first, each in its own transaction. Returns the versions reverted.
+pub async fn revert_migrations(pool: &DbPool, target: i64) -> Result<Vec<i64>, MigrationError> {
+    let mut versions = Vec::new();
+    for migration in revert_plan(pool, target).await? {
+        let mut tx = pool.begin().await?;
+        let down = migration.scripts(pool.backend()).down.unwrap_or_default();
+        backend::execute_script(&mut tx, down).await?;
+        backend::query("DELETE FROM _migrations WHERE version = ?")
+            .bind(migration.version)
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+
+        log::info!(
+            "Reverted migration {:04} ({})",
+            migration.version,
+            migration.name
+        );
+        versions.push(migration.version);
+    }
+
+    Ok(versions)
+}
+
+/// The migrations recorded in `_migrations`, checked against this build.
+async fn applied_migrations(pool: &DbPool) -> Result<Vec<AppliedMigration>, MigrationError> {
+    let tracked: bool = backend::query_scalar(match pool.backend() {
+        Backend::Sqlite => {
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = '_migrations'"
+        }
+        Backend::Postgres => {
+            "SELECT COUNT(*) > 0 FROM information_schema.tables WHERE table_schema = current_schema() AND table_name = '_migrations'"
+        }
+    })
+    .fetch_one(pool)
+    .await?;
+    if !tracked {
+        return Ok(Vec::new());
+    }
+
+    let applied: Vec<AppliedMigration> = backend::query_as(
+        "SELECT version, name, checksum, applied_at FROM _migrations ORDER BY version",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let latest = MIGRATIONS.last().map_or(0, |m| m.version);
+    for row in &applied {
+        match MIGRATIONS.iter().find(|m| m.version == row.version) {
+            None => {
+                return Err(MigrationError::DatabaseAhead {
+                    version: applied.last().map_or(row.version, |a| a.version),
+                    latest,
+                });
+            }
+            Some(migration) if migration.checksum(pool.backend()) != row.checksum => {
+                return Err(MigrationError::ChecksumMismatch {
+                    version: row.version,
+                    name: row.name.clone(),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn memory_pool() -> DbPool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+            .into()
+    }
+
+    async fn table_exists(pool: &DbPool, name: &str) -> bool {
+        backend::query_scalar(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = ?",
+        )
+        .bind(name)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_migrations_apply_once_and_revert() {
+        let pool = memory_pool().await;
+
+        let status = migration_status(&pool).await.unwrap();
+        assert!(status.iter().all(|s| s.applied_at.is_none()));
+        assert_eq!(run_migrations(&pool).await.unwrap(), [1, 2]);
+        assert!(run_migrations(&pool).await.unwrap().is_empty());
+        let status = migration_status(&pool).await.unwrap();
+        assert!(status.iter().all(|s| s.applied_at.is_some()));
+
+        let plan = revert_plan(&pool, 0).await.unwrap();
+        assert_eq!(plan.iter().map(|m| m.version).collect::<Vec<_>>(), [2, 1]);
+        assert!(matches!(
+            revert_plan(&pool, 42).await,
+            Err(MigrationError::UnknownVersion(42))
+        ));
+        assert_eq!(revert_migrations(&pool, 1).await.unwrap(), [2]);
+        assert!(!table_exists(&pool, "foreign_key_repairs").await);
+        assert!(table_exists(&pool, "tasks").await);
+        assert_eq!(revert_migrations(&pool, 0).await.unwrap(), [1]);
+        assert!(!table_exists(&pool, "tasks").await);
+
+        assert_eq!(run_migrations(&pool).await.unwrap(), [1, 2]);
+        assert!(table_exists(&pool, "tasks").await);
+    }
+
+    #[tokio::test]
+    async fn test_migrations_refuse_changed_or_newer_databases() {
+        let pool = memory_pool().await;
+        run_migrations(&pool).await.unwrap();
+
+        backend::query("UPDATE _migrations SET checksum = 'edited' WHERE version = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(matches!(
+            run_migrations(&pool).await,
+            Err(MigrationError::ChecksumMismatch { version: 1, .. })
+        ));
+
+        backend::query("UPDATE _migrations SET checksum = ? WHERE version = 1")
+            .bind(MIGRATIONS[0].checksum(Backend::Sqlite))
+            .execute(&pool)
+            .await
+            .unwrap();
+        backend::query(
+            "INSERT INTO _migrations (version, name, checksum, applied_at) VALUES (999, 'future', '', ?)",
+        )
+        .bind(Utc::now())
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert!(matches!(
+            run_migrations(&pool).await,
+            Err(MigrationError::DatabaseAhead { version: 999, .. })
+        ));
+        assert!(migration_status(&pool).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_foreign_keys_migration_records_and_clears_orphans() {
+        let pool = memory_pool().await;
+        // A database at version 1 with references to missing employees.
+        backend::execute_script(&pool, CREATE_MIGRATIONS_TABLE)
+            .await
+            .unwrap();
+        backend::execute_script(&pool, MIGRATIONS[0].sqlite.up)
+            .await
+            .unwrap();
+        backend::query("INSERT INTO _migrations (version, name, checksum, applied_at) VALUES (1, 'initial', ?, ?)")
+            .bind(MIGRATIONS[0].checksum(Backend::Sqlite))
+            .bind(Utc::now())
+            .execute(&pool)
+            .await
+            .unwrap();
+        backend::execute_script(
+            &pool,
+            r#"
+            INSERT INTO employees (id, first_name, last_name, store_id, manager_id) VALUES
+                (1, 'Ann', 'Manager', 1, NULL),
+                (2, 'Bob', 'Report', 1, '1'),
+                (3, 'Cat', 'Report', 1, '99');
+            INSERT INTO tasks (id, title, assigned_to, created_at, updated_at) VALUES
+                (1, 'Assigned', 2, '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z'),
+                (2, 'Orphaned', 42, '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z');
+            "#,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(run_migrations(&pool).await.unwrap(), [2]);
+
+        let repairs: Vec<(String, i32, String, String)> = backend::query_as(
+            "SELECT table_name, row_id, column_name, missing_value FROM foreign_key_repairs ORDER BY id",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            repairs,
+            [
+                (
+                    "tasks".to_string(),
+                    2,
+                    "assigned_to".to_string(),
+                    "42".to_string()
+                ),
+                (
+                    "employees".to_string(),
+                    3,
+                    "manager_id".to_string(),
+                    "99".to_string()
+                ),
+            ]
+        );
+        let assignees: Vec<Option<i32>> =
+            backend::query_scalar("SELECT assigned_to FROM tasks ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(assignees, [Some(2), None]);
+        let managers: Vec<Option<i32>> =
+            backend::query_scalar("SELECT manager_id FROM employees ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(managers, [None, Some(1), None]);
+
+        let orphan = backend::query(
+            "INSERT INTO tasks (title, assigned_to, created_at, updated_at) VALUES ('New', 42, ?, ?)",
+        )
+        .bind(Utc::now())
+        .bind(Utc::now())
+        .execute(&pool)
+        .await;
+        assert!(orphan.is_err());
+    }
+}
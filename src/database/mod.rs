@@ -0,0 +1,203 @@
+mod backend;
+mod legacy;
+pub mod migrations;
+
+use crate::config::{AppConfig, PoolConfig};
+use sqlx::pool::PoolOptions;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use std::path::Path;
+use std::str::FromStr;
+
+pub use backend::{
+    Backend, Bindable, DbExecutor, DbPool, DbQueryResult, DbRow, DbTransaction, Decodable,
+    FromDbRow, Query, QueryAs, QueryScalar, query, query_as, query_scalar,
+};
+pub use migrations::{MIGRATIONS, Migration, MigrationError, Scripts, run_migrations};
+
+/// Connects and brings the schema up to date, as the server does at startup.
+pub async fn create_pool(config: &AppConfig) -> Result<DbPool, MigrationError> {
+    let pool = connect(config).await?;
+
+    run_migrations(&pool).await?;
+
+    seed_employees(&pool).await?;
+
+    Ok(pool)
+}
+
+/// Connects to the backend named by `DATABASE_URL` without touching the
+/// schema.
+pub async fn connect(config: &AppConfig) -> Result<DbPool, sqlx::Error> {
+    let settings = &config.database_pool;
+    if Backend::from_url(&config.database_url)? == Backend::Postgres {
+        // Timestamps come back in UTC and date grouping happens in UTC,
+        // whatever the server's default time zone.
+        let options =
+            PgConnectOptions::from_str(&config.database_url)?.options([("TimeZone", "UTC")]);
+        let pool = pool_options(PgPoolOptions::new(), settings)
+            .connect_with(options)
+            .await?;
+        return Ok(pool.into());
+    }
+
+    if let Some(db_path) = extract_db_path(&config.database_url)
+        && let Some(parent) = Path::new(db_path).parent()
+    {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            sqlx::Error::Configuration(format!("Failed to create database directory: {}", e).into())
+        })?;
+    }
+
+    let options = SqliteConnectOptions::from_str(&config.database_url)?
+        .journal_mode(settings.sqlite_journal_mode)
+        .busy_timeout(settings.sqlite_busy_timeout)
+        .synchronous(settings.sqlite_synchronous)
+        .foreign_keys(settings.sqlite_foreign_keys);
+    let pool = pool_options(SqlitePoolOptions::new(), settings)
+        .connect_with(options)
+        .await?;
+
+    Ok(pool.into())
+}
+
+fn pool_options<DB: sqlx::Database>(
+    options: PoolOptions<DB>,
+    settings: &PoolConfig,
+) -> PoolOptions<DB> {
+    options
+        .max_connections(settings.max_connections)
+        .acquire_timeout(settings.acquire_timeout)
+        .idle_timeout(settings.idle_timeout)
+}
+
+fn extract_db_path(database_url: &str) -> Option<&str> {
+    database_url
+        .strip_prefix("sqlite://")
+        .or_else(|| database_url.strip_prefix("sqlite:"))
+}
+
+async fn seed_employees(pool: &DbPool) -> Result<(), sqlx::Error> {
+    let count: i64 = query_scalar("SELECT COUNT(*) FROM employees")
+        .fetch_one(pool)
+        .await?;
+
+    if count == 0 {
+        println!("Seeding employee test data...");
+
+        let employees = vec![
+            ("EMP001", "John", "Smith", 1),
+            ("EMP002", "Sarah", "Johnson", 1),
+            ("EMP003", "Michael", "Brown", 1),
+            ("EMP004", "Emily", "Davis", 2),
+            ("EMP005", "David", "Wilson", 2),
+            ("EMP006", "Lisa", "Anderson", 2),
+            ("EMP007", "James", "Taylor", 3),
+            ("EMP008", "Jennifer", "Martinez", 3),
+            ("EMP009", "Robert", "Garcia", 3),
+            ("EMP010", "Amanda", "Rodriguez", 1),
+            ("EMP011", "Christopher", "Lee", 2),
+            ("EMP012", "Jessica", "White", 3),
+        ];
+
+        let employee_count = employees.len();
+
+        for (external_id, first_name, last_name, store_id) in employees {
+            query(
+                r#"
+                INSERT INTO employees (external_id, first_name, last_name, store_id)
+                VALUES (?, ?, ?, ?)
+                "#,
+            )
+            .bind(external_id)
+            .bind(first_name)
+            .bind(last_name)
+            .bind(store_id)
+            .execute(pool)
+            .await?;
+        }
+
+        println!("Seeded {} employees", employee_count);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::auth::Actor;
+    use crate::models::task::CreateTaskRequest;
+    use crate::repositories::TaskRepository;
+    use std::path::PathBuf;
+
+    /// A config for a fresh database file in its own temporary directory.
+    fn file_config() -> (AppConfig, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("app-db-{}", uuid::Uuid::new_v4()));
+        let config = AppConfig {
+            database_url: format!("sqlite:{}?mode=rwc", dir.join("app.db").display()),
+            ..AppConfig::new()
+        };
+        (config, dir)
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_connections_use_configured_pragmas() {
+        let (mut config, dir) = file_config();
+        config.database_pool.sqlite_busy_timeout = std::time::Duration::from_millis(2500);
+        let pool = connect(&config).await.unwrap();
+
+        let journal_mode: String = query_scalar("PRAGMA journal_mode")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(journal_mode, "wal");
+        for (pragma, expected) in [
+            ("busy_timeout", 2500),
+            ("synchronous", 1),
+            ("foreign_keys", 1),
+        ] {
+            let value: i64 = query_scalar(&format!("PRAGMA {}", pragma))
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(value, expected, "{}", pragma);
+        }
+
+        pool.close().await;
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_concurrent_writers_wait_for_each_other() {
+        let (config, dir) = file_config();
+        let pool = create_pool(&config).await.unwrap();
+        let repository = TaskRepository::new(pool.clone());
+
+        let writers: Vec<_> = (0..20)
+            .map(|n| {
+                let repository = repository.clone();
+                tokio::spawn(async move {
+                    let request = CreateTaskRequest {
+                        title: format!("Task {}", n),
+                        description: None,
+                        priority_level: None,
+                        status: None,
+                        assigned_to: Some(1),
+                        store_id: None,
+                        parent_id: None,
+                        start_at: None,
+                        due_at: None,
+                    };
+                    repository.create(request, &Actor::system("test")).await
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.await.unwrap().unwrap();
+        }
+
+        pool.close().await;
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,5 +1,6 @@
 use actix_web::{HttpResponse, ResponseError};
 use serde::Serialize;
+use sqlx::error::ErrorKind;
 use std::fmt;
 
 #[derive(Debug, Serialize)]
@@ -10,6 +11,8 @@ pub enum ApiError {
     InternalServerError(String),
     ValidationError(String),
     AuthorizationError(String),
+    Forbidden(String),
+    Conflict(String),
 }
 
 #[derive(Serialize)]
@@ -27,10 +30,30 @@ impl fmt::Display for ApiError {
             ApiError::InternalServerError(msg) => write!(f, "Internal Server Error: {}", msg),
             ApiError::ValidationError(msg) => write!(f, "Validation Error: {}", msg),
             ApiError::AuthorizationError(msg) => write!(f, "Authorization Error: {}", msg),
+            ApiError::Forbidden(msg) => write!(f, "Forbidden: {}", msg),
+            ApiError::Conflict(msg) => write!(f, "Conflict: {}", msg),
         }
     }
 }
 
+impl std::error::Error for ApiError {}
+
+/// A write that breaks a foreign key or unique constraint conflicts with
+/// what is already stored; any other database failure is a server error.
+impl From<sqlx::Error> for ApiError {
+    fn from(e: sqlx::Error) -> Self {
+        if let sqlx::Error::Database(db) = &e
+            && matches!(
+                db.kind(),
+                ErrorKind::ForeignKeyViolation | ErrorKind::UniqueViolation
+            )
+        {
+            return ApiError::Conflict(format!("Conflicts with existing data: {}", db.message()));
+        }
+        ApiError::InternalServerError(format!("Database error: {}", e))
+    }
+}
+
 impl ResponseError for ApiError {
     fn error_response(&self) -> HttpResponse {
         match self {
@@ -61,6 +84,16 @@ impl ResponseError for ApiError {
                 error: msg.clone(),
                 code: 401,
             }), 
+            ApiError::Forbidden(msg) => HttpResponse::Forbidden().json(ErrorResponse {
+                success: false,
+                error: msg.clone(),
+                code: 403,
+            }),
+            ApiError::Conflict(msg) => HttpResponse::Conflict().json(ErrorResponse {
+                success: false,
+                error: msg.clone(),
+                code: 409,
+            }),
         }
     }
 }
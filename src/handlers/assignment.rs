@@ -0,0 +1,62 @@
+use crate::models::assignment::*;
+use crate::services::AssignmentService;
+use crate::utils::ApiResponse;
+use actix_web::{HttpResponse, ResponseError, Result, delete, get, put, web};
+
+#[get("/assignment-strategies")]
+pub async fn get_assignment_strategies(
+    service: web::Data<AssignmentService>,
+) -> Result<HttpResponse> {
+    match service.get_all_settings().await {
+        Ok(settings) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            settings,
+            "Assignment strategies retrieved successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[get("/{store_id}/assignment-strategy")]
+pub async fn get_assignment_strategy(
+    service: web::Data<AssignmentService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let store_id = path.into_inner();
+    match service.get_settings(store_id).await {
+        Ok(settings) => Ok(
+            HttpResponse::Ok().json(ApiResponse::success(settings, "Assignment strategy found"))
+        ),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[put("/{store_id}/assignment-strategy")]
+pub async fn set_assignment_strategy(
+    service: web::Data<AssignmentService>,
+    path: web::Path<i32>,
+    req: web::Json<AssignmentSettingsRequest>,
+) -> Result<HttpResponse> {
+    let store_id = path.into_inner();
+    match service.save_settings(store_id, req.into_inner()).await {
+        Ok(settings) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            settings,
+            "Assignment strategy saved successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[delete("/{store_id}/assignment-strategy")]
+pub async fn delete_assignment_strategy(
+    service: web::Data<AssignmentService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let store_id = path.into_inner();
+    match service.delete_settings(store_id).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::<()>::success(
+            (),
+            "Assignment strategy removed successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
@@ -0,0 +1,162 @@
+use crate::errors::ApiError;
+use crate::models::auth::Actor;
+use crate::services::AttachmentService;
+use crate::utils::ApiResponse;
+use actix_multipart::Multipart;
+use actix_web::http::header::{
+    ACCEPT_RANGES, CONTENT_RANGE, ContentDisposition, DispositionParam, DispositionType, ETAG,
+    Range,
+};
+use actix_web::web::{Bytes, BytesMut};
+use actix_web::{HttpResponse, ResponseError, Result, delete, get, post, web};
+use futures_util::TryStreamExt;
+
+#[get("/{id}/attachments")]
+pub async fn get_task_attachments(
+    service: web::Data<AttachmentService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let task_id = path.into_inner();
+    match service.get_attachments(task_id).await {
+        Ok(attachments) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            attachments,
+            "Attachments retrieved successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[post("/{id}/attachments")]
+pub async fn create_task_attachment(
+    service: web::Data<AttachmentService>,
+    actor: Actor,
+    path: web::Path<i32>,
+    payload: Multipart,
+) -> Result<HttpResponse> {
+    let task_id = path.into_inner();
+    let (file_name, data) = match read_file(payload, service.max_bytes()).await {
+        Ok(file) => file,
+        Err(e) => return Ok(e.error_response()),
+    };
+
+    match service
+        .create_attachment(task_id, file_name.as_deref(), data, &actor)
+        .await
+    {
+        Ok(attachment) => Ok(HttpResponse::Created().json(ApiResponse::success(
+            attachment,
+            "Attachment uploaded successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// Serves an attachment's contents. A single `Range: bytes=` range is
+/// answered with `206 Partial Content`; multiple ranges get the whole file.
+#[get("/{id}/attachments/{attachment_id}")]
+pub async fn download_task_attachment(
+    service: web::Data<AttachmentService>,
+    path: web::Path<(i32, i32)>,
+    range: Option<web::Header<Range>>,
+) -> Result<HttpResponse> {
+    let (task_id, attachment_id) = path.into_inner();
+    let attachment = match service.get_attachment(task_id, attachment_id).await {
+        Ok(attachment) => attachment,
+        Err(e) => return Ok(e.error_response()),
+    };
+
+    let size = attachment.size_bytes as u64;
+    let range = match range.map(web::Header::into_inner) {
+        Some(Range::Bytes(specs)) if specs.len() == 1 => {
+            match specs[0].to_satisfiable_range(size) {
+                Some(range) => Some(range),
+                None => {
+                    return Ok(HttpResponse::RangeNotSatisfiable()
+                        .insert_header((CONTENT_RANGE, format!("bytes */{}", size)))
+                        .finish());
+                }
+            }
+        }
+        _ => None,
+    };
+
+    let (start, end) = range.unwrap_or((0, size.saturating_sub(1)));
+    let data = match service.read_attachment(&attachment, start, end).await {
+        Ok(data) => data,
+        Err(e) => return Ok(e.error_response()),
+    };
+
+    let mut response = match range {
+        Some(_) => HttpResponse::PartialContent(),
+        None => HttpResponse::Ok(),
+    };
+    if range.is_some() {
+        response.insert_header((CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, size)));
+    }
+
+    Ok(response
+        .content_type(attachment.content_type.as_str())
+        .insert_header((ACCEPT_RANGES, "bytes"))
+        .insert_header((ETAG, format!("\"{}\"", attachment.sha256)))
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(attachment.file_name)],
+        })
+        .body(data))
+}
+
+#[delete("/{id}/attachments/{attachment_id}")]
+pub async fn delete_task_attachment(
+    service: web::Data<AttachmentService>,
+    actor: Actor,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse> {
+    let (task_id, attachment_id) = path.into_inner();
+    match service
+        .delete_attachment(task_id, attachment_id, &actor)
+        .await
+    {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::<()>::success(
+            (),
+            "Attachment deleted successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+/// Reads the `file` part of a multipart upload, giving up as soon as it
+/// grows past `max_bytes`.
+async fn read_file(
+    mut payload: Multipart,
+    max_bytes: usize,
+) -> Result<(Option<String>, Bytes), ApiError> {
+    let multipart_error = |e| ApiError::BadRequest(format!("Invalid multipart body: {}", e));
+
+    while let Some(mut field) = payload.try_next().await.map_err(multipart_error)? {
+        if field.name() != Some("file") {
+            while field.try_next().await.map_err(multipart_error)?.is_some() {}
+            continue;
+        }
+
+        let file_name = field
+            .content_disposition()
+            .and_then(ContentDisposition::get_filename)
+            .map(str::to_string);
+        let mut data = BytesMut::new();
+        while let Some(chunk) = field.try_next().await.map_err(multipart_error)? {
+            if data.len() + chunk.len() > max_bytes {
+                return Err(ApiError::ValidationError(format!(
+                    "Attachment cannot be larger than {} bytes",
+                    max_bytes
+                )));
+            }
+            data.extend_from_slice(&chunk);
+        }
+
+        return Ok((file_name, data.freeze()));
+    }
+
+    Err(ApiError::ValidationError(
+        "Upload must include a file field".to_string(),
+    ))
+}
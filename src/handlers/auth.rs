@@ -9,7 +9,7 @@ pub async fn login(
     auth_service: web::Data<Mutex<AuthService>>,
     login_req: web::Json<LoginRequest>,
 ) -> Result<HttpResponse> {
-    let service = auth_service.lock().unwrap();
+    let service = auth_service.lock().unwrap().clone();
     match service.authenticate(login_req.into_inner()).await {
         Ok(token_response) => {
             Ok(HttpResponse::Ok().json(ApiResponse::success(token_response, "Login successful")))
@@ -23,7 +23,7 @@ pub async fn refresh_token(
     auth_service: web::Data<Mutex<AuthService>>,
     refresh_req: web::Json<RefreshTokenRequest>,
 ) -> Result<HttpResponse> {
-    let service = auth_service.lock().unwrap();
+    let service = auth_service.lock().unwrap().clone();
     match service
         .refresh_token(refresh_req.refresh_token.clone())
         .await
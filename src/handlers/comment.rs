@@ -0,0 +1,133 @@
+use crate::models::auth::Actor;
+use crate::models::comment::*;
+use crate::services::CommentService;
+use crate::utils::ApiResponse;
+use actix_web::{HttpResponse, ResponseError, Result, delete, get, patch, post, web};
+
+#[get("/{id}/comments")]
+pub async fn get_task_comments(
+    service: web::Data<CommentService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let task_id = path.into_inner();
+    match service.get_comments(task_id).await {
+        Ok(comments) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            comments,
+            "Comments retrieved successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[get("/{id}/comments/{comment_id}")]
+pub async fn get_task_comment(
+    service: web::Data<CommentService>,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse> {
+    let (task_id, comment_id) = path.into_inner();
+    match service.get_comment(task_id, comment_id).await {
+        Ok(comment) => Ok(HttpResponse::Ok().json(ApiResponse::success(comment, "Comment found"))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[post("/{id}/comments")]
+pub async fn create_task_comment(
+    service: web::Data<CommentService>,
+    actor: Actor,
+    path: web::Path<i32>,
+    req: web::Json<CommentRequest>,
+) -> Result<HttpResponse> {
+    let task_id = path.into_inner();
+    match service
+        .create_comment(task_id, req.into_inner(), &actor)
+        .await
+    {
+        Ok(comment) => Ok(HttpResponse::Created().json(ApiResponse::success(
+            comment,
+            "Comment created successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[patch("/{id}/comments/{comment_id}")]
+pub async fn update_task_comment(
+    service: web::Data<CommentService>,
+    actor: Actor,
+    path: web::Path<(i32, i32)>,
+    req: web::Json<CommentRequest>,
+) -> Result<HttpResponse> {
+    let (task_id, comment_id) = path.into_inner();
+    match service
+        .update_comment(task_id, comment_id, req.into_inner(), &actor)
+        .await
+    {
+        Ok(comment) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            comment,
+            "Comment updated successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[delete("/{id}/comments/{comment_id}")]
+pub async fn delete_task_comment(
+    service: web::Data<CommentService>,
+    actor: Actor,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse> {
+    let (task_id, comment_id) = path.into_inner();
+    match service.delete_comment(task_id, comment_id, &actor).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::<()>::success(
+            (),
+            "Comment deleted successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[get("/{id}/comments/{comment_id}/revisions")]
+pub async fn get_task_comment_revisions(
+    service: web::Data<CommentService>,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse> {
+    let (task_id, comment_id) = path.into_inner();
+    match service.get_comment_revisions(task_id, comment_id).await {
+        Ok(revisions) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            revisions,
+            "Comment revisions retrieved successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[get("/{id}/mentions")]
+pub async fn get_task_mentions(
+    service: web::Data<CommentService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let task_id = path.into_inner();
+    match service.get_task_mentions(task_id).await {
+        Ok(employees) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            employees,
+            "Mentioned employees retrieved successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[get("/{id}/mentions")]
+pub async fn get_employee_mentions(
+    service: web::Data<CommentService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let employee_id = path.into_inner();
+    match service.get_employee_mentions(employee_id).await {
+        Ok(comments) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            comments,
+            "Mentions retrieved successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
@@ -1,17 +1,51 @@
+use crate::errors::ApiError;
 use crate::models::employee::*;
-use crate::services::EmployeeService;
+use crate::services::{EmployeeService, ImportOutcome};
 use crate::utils::ApiResponse;
-use actix_web::{HttpResponse, ResponseError, Result, get, web, post};
+use crate::utils::pagination::paginated_response;
+use crate::utils::tabular::{ExportFormat, ExportQuery, export_response};
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, ResponseError, Result, get, post, web};
+use uuid::Uuid;
 
 #[get("/")]
 pub async fn get_employees(
     service: web::Data<EmployeeService>,
+    req: HttpRequest,
     query: web::Query<EmployeeQuery>,
+    export: web::Query<ExportQuery>,
 ) -> Result<HttpResponse> {
+    let format = match ExportFormat::negotiate(export.format.as_deref(), &req) {
+        Ok(format) => format,
+        Err(e) => return Ok(e.error_response()),
+    };
+
+    if format != ExportFormat::Json {
+        return match service.export_employees(query.into_inner()) {
+            Ok(records) => Ok(export_response(format, "employees", records)),
+            Err(e) => Ok(e.error_response()),
+        };
+    }
+
     match service.get_employees(query.into_inner()).await {
-        Ok(response) => Ok(HttpResponse::Ok().json(ApiResponse::success(
-            response,
+        Ok(page) => Ok(paginated_response(
+            &req,
+            &page,
+            "employees",
             "Employees retrieved successfully",
+        )),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[get("/workload")]
+pub async fn get_employee_workload(
+    service: web::Data<EmployeeService>,
+    query: web::Query<WorkloadQuery>,
+) -> Result<HttpResponse> {
+    match service.get_workload(query.into_inner()).await {
+        Ok(workload) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            workload,
+            "Workload retrieved successfully",
         ))),
         Err(e) => Ok(e.error_response()),
     }
@@ -32,9 +66,40 @@ pub async fn get_employee(
     }
 }
 
+#[post("/{id}/deactivate")]
+pub async fn deactivate_employee(
+    service: web::Data<EmployeeService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let employee_id = path.into_inner();
+    match service.deactivate_employee(employee_id).await {
+        Ok(employee) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            employee,
+            "Employee deactivated successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[post("/{id}/reactivate")]
+pub async fn reactivate_employee(
+    service: web::Data<EmployeeService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let employee_id = path.into_inner();
+    match service.reactivate_employee(employee_id).await {
+        Ok(employee) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            employee,
+            "Employee reactivated successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
 #[get("/stores/{store_id}")]
 pub async fn get_employees_by_store(
     service: web::Data<EmployeeService>,
+    req: HttpRequest,
     path: web::Path<i32>,
     query: web::Query<EmployeeQuery>,
 ) -> Result<HttpResponse> {
@@ -44,10 +109,12 @@ pub async fn get_employees_by_store(
         .get_employees_by_store(store_id, query.into_inner())
         .await
     {
-        Ok(response) => Ok(HttpResponse::Ok().json(ApiResponse::success(
-            response,
+        Ok(page) => Ok(paginated_response(
+            &req,
+            &page,
+            "employees",
             "Store employees retrieved successfully",
-        ))),
+        )),
         Err(e) => Ok(e.error_response()),
     }
 }
@@ -68,3 +135,46 @@ pub async fn create_employees(
         Err(e) => Ok(e.error_response()),
     }
 }
+
+#[post("/import")]
+pub async fn import_employees(
+    service: web::Data<EmployeeService>,
+    req: HttpRequest,
+    query: web::Query<EmployeeImportQuery>,
+    body: web::Bytes,
+) -> Result<HttpResponse> {
+    let content_type = req.content_type();
+    if content_type != "text/csv" && content_type != "application/csv" {
+        return Ok(
+            ApiError::BadRequest("Content-Type must be text/csv".to_string()).error_response(),
+        );
+    }
+
+    match service.import_employees(&body, query.into_inner()).await {
+        Ok(ImportOutcome::Completed(report)) => {
+            let message = if report.dry_run {
+                "Import preview generated"
+            } else {
+                "Employees imported successfully"
+            };
+            Ok(HttpResponse::Ok().json(ApiResponse::success(report, message)))
+        }
+        Ok(ImportOutcome::Queued(job)) => {
+            Ok(HttpResponse::Accepted().json(ApiResponse::success(job, "Import job queued")))
+        }
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[get("/import/{job_id}")]
+pub async fn get_import_job(
+    service: web::Data<EmployeeService>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse> {
+    let job_id = path.into_inner();
+
+    match service.get_import_job(job_id) {
+        Ok(job) => Ok(HttpResponse::Ok().json(ApiResponse::success(job, "Import job found"))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
@@ -1,5 +1,5 @@
-use actix_web::{get, post, HttpResponse, Result};
 use crate::utils::ApiResponse;
+use actix_web::{HttpResponse, Result, get, post};
 
 // TEST ROUTES
 #[get("/")]
@@ -11,6 +11,6 @@ pub async fn hello() -> Result<HttpResponse> {
 pub async fn echo(req_body: String) -> Result<HttpResponse> {
     Ok(HttpResponse::Ok().json(ApiResponse::success(
         serde_json::json!({"echoed": req_body}),
-        "Message echoed"
+        "Message echoed",
     )))
-}
\ No newline at end of file
+}
@@ -1,13 +1,23 @@
+pub mod assignment;
+pub mod attachment;
 pub mod auth;
+pub mod comment;
 pub mod employee;
 pub mod health;
 pub mod misc;
 pub mod object;
+pub mod report;
 pub mod task;
+pub mod task_template;
 
+pub use assignment::*;
+pub use attachment::*;
 pub use auth::*;
+pub use comment::*;
 pub use employee::*;
 pub use health::*;
 pub use misc::*;
 pub use object::*;
+pub use report::*;
 pub use task::*;
+pub use task_template::*;
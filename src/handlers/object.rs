@@ -1,18 +1,38 @@
 use crate::models::object::*;
 use crate::services::ObjectService;
 use crate::utils::ApiResponse;
-use actix_web::{HttpResponse, ResponseError, Result, delete, get, patch, post, put, web};
+use crate::utils::pagination::paginated_response;
+use crate::utils::tabular::{ExportFormat, ExportQuery, export_response};
+use actix_web::{
+    HttpRequest, HttpResponse, ResponseError, Result, delete, get, patch, post, put, web,
+};
 
 #[get("/")]
 pub async fn get_objects(
     service: web::Data<ObjectService>,
+    req: HttpRequest,
     query: web::Query<ObjectQuery>,
+    export: web::Query<ExportQuery>,
 ) -> Result<HttpResponse> {
+    let format = match ExportFormat::negotiate(export.format.as_deref(), &req) {
+        Ok(format) => format,
+        Err(e) => return Ok(e.error_response()),
+    };
+
+    if format != ExportFormat::Json {
+        return match service.export_objects(query.into_inner()) {
+            Ok(records) => Ok(export_response(format, "objects", records)),
+            Err(e) => Ok(e.error_response()),
+        };
+    }
+
     match service.get_objects(query.into_inner()).await {
-        Ok(response) => Ok(HttpResponse::Ok().json(ApiResponse::success(
-            response,
+        Ok(page) => Ok(paginated_response(
+            &req,
+            &page,
+            "objects",
             "Objects retrieved successfully",
-        ))),
+        )),
         Err(e) => Ok(e.error_response()),
     }
 }
@@ -0,0 +1,18 @@
+use crate::models::report::TaskReportQuery;
+use crate::services::ReportService;
+use crate::utils::ApiResponse;
+use actix_web::{HttpResponse, ResponseError, Result, get, web};
+
+#[get("/tasks")]
+pub async fn get_task_report(
+    service: web::Data<ReportService>,
+    query: web::Query<TaskReportQuery>,
+) -> Result<HttpResponse> {
+    match service.get_task_report(query.into_inner()).await {
+        Ok(report) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            report,
+            "Task report retrieved successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
@@ -1,17 +1,57 @@
+use crate::models::auth::Actor;
+use crate::models::tag::SetTagsRequest;
 use crate::models::task::*;
+use crate::models::task_board::{BoardQuery, MoveTaskRequest};
+use crate::models::task_bulk::BulkTaskRequest;
+use crate::models::task_dependency::AddDependencyRequest;
 use crate::services::TaskService;
 use crate::utils::ApiResponse;
-use actix_web::{HttpResponse, ResponseError, Result, delete, get, patch, post, web};
+use crate::utils::pagination::paginated_response;
+use crate::utils::tabular::{ExportFormat, ExportQuery, export_response};
+use actix_web::{
+    HttpRequest, HttpResponse, ResponseError, Result, delete, get, patch, post, put, web,
+};
 
 #[get("/")]
 pub async fn get_tasks(
     service: web::Data<TaskService>,
+    actor: Actor,
+    req: HttpRequest,
     query: web::Query<TaskQuery>,
+    export: web::Query<ExportQuery>,
 ) -> Result<HttpResponse> {
-    match service.get_tasks(query.into_inner()).await {
-        Ok(response) => Ok(HttpResponse::Ok().json(ApiResponse::success(
-            response,
+    let format = match ExportFormat::negotiate(export.format.as_deref(), &req) {
+        Ok(format) => format,
+        Err(e) => return Ok(e.error_response()),
+    };
+
+    if format != ExportFormat::Json {
+        return match service.export_tasks(query.into_inner(), &actor) {
+            Ok(records) => Ok(export_response(format, "tasks", records)),
+            Err(e) => Ok(e.error_response()),
+        };
+    }
+
+    match service.get_tasks(query.into_inner(), &actor).await {
+        Ok(page) => Ok(paginated_response(
+            &req,
+            &page,
+            "tasks",
             "Tasks retrieved successfully",
+        )),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[get("/board")]
+pub async fn get_task_board(
+    service: web::Data<TaskService>,
+    query: web::Query<BoardQuery>,
+) -> Result<HttpResponse> {
+    match service.get_board(query.into_inner()).await {
+        Ok(board) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            board,
+            "Task board retrieved successfully",
         ))),
         Err(e) => Ok(e.error_response()),
     }
@@ -20,10 +60,12 @@ pub async fn get_tasks(
 #[get("/{id}")]
 pub async fn get_task(
     service: web::Data<TaskService>,
+    actor: Actor,
     path: web::Path<i32>,
+    query: web::Query<TaskLookupQuery>,
 ) -> Result<HttpResponse> {
     let task_id = path.into_inner();
-    match service.get_task(task_id).await {
+    match service.get_task(task_id, query.into_inner(), &actor).await {
         Ok(task) => Ok(HttpResponse::Ok().json(ApiResponse::success(task, "Task found"))),
         Err(e) => Ok(e.error_response()),
     }
@@ -32,9 +74,10 @@ pub async fn get_task(
 #[post("/")]
 pub async fn create_task(
     service: web::Data<TaskService>,
+    actor: Actor,
     req: web::Json<CreateTaskRequest>,
 ) -> Result<HttpResponse> {
-    match service.create_task(req.into_inner()).await {
+    match service.create_task(req.into_inner(), &actor).await {
         Ok(task) => {
             Ok(HttpResponse::Created()
                 .json(ApiResponse::success(task, "Task created successfully")))
@@ -43,14 +86,43 @@ pub async fn create_task(
     }
 }
 
+/// Bulk requests are all or nothing; when a task fails, the report is sent
+/// back with a 400 and no task is changed.
+#[post("/bulk")]
+pub async fn bulk_update_tasks(
+    service: web::Data<TaskService>,
+    actor: Actor,
+    req: web::Json<BulkTaskRequest>,
+) -> Result<HttpResponse> {
+    match service.bulk_update_tasks(req.into_inner(), &actor).await {
+        Ok(report) if report.failed > 0 && !report.dry_run => {
+            Ok(HttpResponse::BadRequest().json(ApiResponse {
+                success: false,
+                data: Some(report),
+                message: "Some tasks could not be updated; no changes were made".to_string(),
+            }))
+        }
+        Ok(report) => {
+            let message = if report.dry_run {
+                "Bulk operation checked; no changes were made"
+            } else {
+                "Bulk operation applied successfully"
+            };
+            Ok(HttpResponse::Ok().json(ApiResponse::success(report, message)))
+        }
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
 #[patch("/{id}")]
 pub async fn update_task(
     service: web::Data<TaskService>,
+    actor: Actor,
     path: web::Path<i32>,
     req: web::Json<UpdateTaskRequest>,
 ) -> Result<HttpResponse> {
     let task_id = path.into_inner();
-    match service.update_task(task_id, req.into_inner()).await {
+    match service.update_task(task_id, req.into_inner(), &actor).await {
         Ok(task) => {
             Ok(HttpResponse::Ok().json(ApiResponse::success(task, "Task updated successfully")))
         }
@@ -58,13 +130,150 @@ pub async fn update_task(
     }
 }
 
+#[get("/{id}/transitions")]
+pub async fn get_task_transitions(
+    service: web::Data<TaskService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let task_id = path.into_inner();
+    match service.get_task_transitions(task_id).await {
+        Ok(transitions) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            transitions,
+            "Task transitions retrieved successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[post("/{id}/transitions")]
+pub async fn transition_task(
+    service: web::Data<TaskService>,
+    actor: Actor,
+    path: web::Path<i32>,
+    req: web::Json<TransitionTaskRequest>,
+) -> Result<HttpResponse> {
+    let task_id = path.into_inner();
+    match service
+        .transition_task(task_id, req.into_inner(), &actor)
+        .await
+    {
+        Ok(task) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            task,
+            "Task status changed successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[post("/{id}/move")]
+pub async fn move_task(
+    service: web::Data<TaskService>,
+    actor: Actor,
+    path: web::Path<i32>,
+    req: web::Json<MoveTaskRequest>,
+) -> Result<HttpResponse> {
+    let task_id = path.into_inner();
+    match service.move_task(task_id, req.into_inner(), &actor).await {
+        Ok(task) => {
+            Ok(HttpResponse::Ok().json(ApiResponse::success(task, "Task moved successfully")))
+        }
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[get("/{id}/history")]
+pub async fn get_task_history(
+    service: web::Data<TaskService>,
+    actor: Actor,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let task_id = path.into_inner();
+    match service.get_task_history(task_id, &actor).await {
+        Ok(events) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            events,
+            "Task history retrieved successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[get("/{id}/tree")]
+pub async fn get_task_tree(
+    service: web::Data<TaskService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let task_id = path.into_inner();
+    match service.get_task_tree(task_id).await {
+        Ok(tree) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            tree,
+            "Task tree retrieved successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[get("/{id}/dependencies")]
+pub async fn get_task_dependencies(
+    service: web::Data<TaskService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let task_id = path.into_inner();
+    match service.get_task_dependencies(task_id).await {
+        Ok(dependencies) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            dependencies,
+            "Task dependencies retrieved successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[post("/{id}/dependencies")]
+pub async fn add_task_dependency(
+    service: web::Data<TaskService>,
+    actor: Actor,
+    path: web::Path<i32>,
+    req: web::Json<AddDependencyRequest>,
+) -> Result<HttpResponse> {
+    let task_id = path.into_inner();
+    match service
+        .add_task_dependency(task_id, req.into_inner(), &actor)
+        .await
+    {
+        Ok(dependencies) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            dependencies,
+            "Task dependency added successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[delete("/{id}/dependencies/{depends_on}")]
+pub async fn remove_task_dependency(
+    service: web::Data<TaskService>,
+    actor: Actor,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse> {
+    let (task_id, depends_on) = path.into_inner();
+    match service
+        .remove_task_dependency(task_id, depends_on, &actor)
+        .await
+    {
+        Ok(dependencies) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            dependencies,
+            "Task dependency removed successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
 #[delete("/{id}")]
 pub async fn delete_task(
     service: web::Data<TaskService>,
+    actor: Actor,
     path: web::Path<i32>,
 ) -> Result<HttpResponse> {
     let task_id = path.into_inner();
-    match service.delete_task(task_id).await {
+    match service.delete_task(task_id, &actor).await {
         Ok(_) => Ok(
             HttpResponse::Ok().json(ApiResponse::<()>::success((), "Task deleted successfully"))
         ),
@@ -72,16 +281,115 @@ pub async fn delete_task(
     }
 }
 
+#[post("/{id}/restore")]
+pub async fn restore_task(
+    service: web::Data<TaskService>,
+    actor: Actor,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let task_id = path.into_inner();
+    match service.restore_task(task_id, &actor).await {
+        Ok(task) => {
+            Ok(HttpResponse::Ok().json(ApiResponse::success(task, "Task restored successfully")))
+        }
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[put("/{id}/tags")]
+pub async fn set_task_tags(
+    service: web::Data<TaskService>,
+    actor: Actor,
+    path: web::Path<i32>,
+    req: web::Json<SetTagsRequest>,
+) -> Result<HttpResponse> {
+    let task_id = path.into_inner();
+    match service
+        .set_task_tags(task_id, req.into_inner(), &actor)
+        .await
+    {
+        Ok(task) => {
+            Ok(HttpResponse::Ok()
+                .json(ApiResponse::success(task, "Task tags updated successfully")))
+        }
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[get("/")]
+pub async fn get_tags(service: web::Data<TaskService>) -> Result<HttpResponse> {
+    match service.get_tags().await {
+        Ok(tags) => {
+            Ok(HttpResponse::Ok().json(ApiResponse::success(tags, "Tags retrieved successfully")))
+        }
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
 #[patch("/{task_id}/assign/{employee_id}")]
 pub async fn assign_task(
     service: web::Data<TaskService>,
+    actor: Actor,
     path: web::Path<(i32, i32)>,
 ) -> Result<HttpResponse> {
     let (task_id, employee_id) = path.into_inner();
-    match service.assign_task(task_id, employee_id).await {
+    match service.assign_task(task_id, employee_id, &actor).await {
+        Ok(task) => {
+            Ok(HttpResponse::Ok().json(ApiResponse::success(task, "Task assigned successfully")))
+        }
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[post("/{id}/auto-assign")]
+pub async fn auto_assign_task(
+    service: web::Data<TaskService>,
+    actor: Actor,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let task_id = path.into_inner();
+    match service.auto_assign_task(task_id, &actor).await {
         Ok(task) => {
             Ok(HttpResponse::Ok().json(ApiResponse::success(task, "Task assigned successfully")))
         }
         Err(e) => Ok(e.error_response()),
     }
 }
+
+#[delete("/{task_id}/assign")]
+pub async fn unassign_task(
+    service: web::Data<TaskService>,
+    actor: Actor,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let task_id = path.into_inner();
+    match service.unassign_task(task_id, &actor).await {
+        Ok(task) => {
+            Ok(HttpResponse::Ok().json(ApiResponse::success(task, "Task unassigned successfully")))
+        }
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[get("/{id}/tasks")]
+pub async fn get_employee_tasks(
+    service: web::Data<TaskService>,
+    actor: Actor,
+    req: HttpRequest,
+    path: web::Path<i32>,
+    query: web::Query<TaskQuery>,
+) -> Result<HttpResponse> {
+    let employee_id = path.into_inner();
+    match service
+        .get_employee_tasks(employee_id, query.into_inner(), &actor)
+        .await
+    {
+        Ok(page) => Ok(paginated_response(
+            &req,
+            &page,
+            "tasks",
+            "Employee tasks retrieved successfully",
+        )),
+        Err(e) => Ok(e.error_response()),
+    }
+}
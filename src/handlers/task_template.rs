@@ -0,0 +1,105 @@
+use crate::models::task_template::*;
+use crate::services::TaskTemplateService;
+use crate::utils::ApiResponse;
+use actix_web::{HttpResponse, ResponseError, Result, delete, get, patch, post, web};
+
+#[get("/")]
+pub async fn get_task_templates(service: web::Data<TaskTemplateService>) -> Result<HttpResponse> {
+    match service.get_templates().await {
+        Ok(templates) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            templates,
+            "Task templates retrieved successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[get("/{id}")]
+pub async fn get_task_template(
+    service: web::Data<TaskTemplateService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    match service.get_template(id).await {
+        Ok(template) => {
+            Ok(HttpResponse::Ok().json(ApiResponse::success(template, "Task template found")))
+        }
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[post("/")]
+pub async fn create_task_template(
+    service: web::Data<TaskTemplateService>,
+    req: web::Json<CreateTaskTemplateRequest>,
+) -> Result<HttpResponse> {
+    match service.create_template(req.into_inner()).await {
+        Ok(template) => Ok(HttpResponse::Created().json(ApiResponse::success(
+            template,
+            "Task template created successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[patch("/{id}")]
+pub async fn update_task_template(
+    service: web::Data<TaskTemplateService>,
+    path: web::Path<i32>,
+    req: web::Json<UpdateTaskTemplateRequest>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    match service.update_template(id, req.into_inner()).await {
+        Ok(template) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            template,
+            "Task template updated successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[delete("/{id}")]
+pub async fn delete_task_template(
+    service: web::Data<TaskTemplateService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    match service.delete_template(id).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResponse::<()>::success(
+            (),
+            "Task template deleted successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[get("/{id}/occurrences")]
+pub async fn get_task_template_occurrences(
+    service: web::Data<TaskTemplateService>,
+    path: web::Path<i32>,
+    query: web::Query<OccurrenceQuery>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    match service.get_occurrences(id, query.into_inner()).await {
+        Ok(dates) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            dates,
+            "Occurrences retrieved successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
+
+#[post("/{id}/materialize")]
+pub async fn materialize_task_template(
+    service: web::Data<TaskTemplateService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    match service.materialize_template(id).await {
+        Ok(report) => Ok(HttpResponse::Ok().json(ApiResponse::success(
+            report,
+            "Task template materialized successfully",
+        ))),
+        Err(e) => Ok(e.error_response()),
+    }
+}
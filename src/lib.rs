@@ -7,6 +7,7 @@ pub mod utils;
 pub mod errors;
 pub mod middleware;
 pub mod database;
+pub mod storage;
 
 pub use services::*;
 pub use repositories::*;
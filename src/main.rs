@@ -1,23 +1,20 @@
 use actix_web::{App, HttpServer, middleware::Logger, web};
-use num_cpus;
 use std::sync::Mutex;
 use std::{sync::Arc, time::Duration};
 
-mod config;
-mod database;
-mod errors;
-mod handlers;
-mod middleware;
-mod models;
-mod repositories;
-mod services;
-mod utils;
-
-use config::AppConfig;
-use database::create_pool;
-use middleware::AuthMiddleware;
-use repositories::{EmployeeRepository, ObjectRepository, TaskRepository};
-use services::{AuthService, EmployeeService, ObjectService, TaskService};
+use rust_api_framework::config::AppConfig;
+use rust_api_framework::database::{self, create_pool, migrations};
+use rust_api_framework::handlers;
+use rust_api_framework::middleware::AuthMiddleware;
+use rust_api_framework::repositories::{
+    AssignmentRepository, AttachmentRepository, CommentRepository, EmployeeRepository,
+    ObjectRepository, ReportRepository, TaskRepository, TaskTemplateRepository,
+};
+use rust_api_framework::services::{
+    AssignmentService, AttachmentService, AuthService, CommentService, EmployeeService,
+    ObjectService, ReportService, TaskService, TaskTemplateService,
+};
+use rust_api_framework::storage::LocalStorage;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -31,16 +28,49 @@ async fn main() -> std::io::Result<()> {
         config.log_level.to_uppercase()
     );
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        return migrate_command(&config, &args).await;
+    }
+
     println!("--------------------------------");
     println!("Initializing database...");
-    let pool = create_pool(&config)
-        .await
-        .expect("Failed to create database pool");
+    let pool = match create_pool(&config).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("Failed to initialize database: {}", e);
+            std::process::exit(1);
+        }
+    };
 
     println!("Initializing services and repositories...");
     let object_service = Arc::new(ObjectService::new(ObjectRepository::new(pool.clone())));
     let employee_service = Arc::new(EmployeeService::new(EmployeeRepository::new(pool.clone())));
-    let task_service = Arc::new(TaskService::new(TaskRepository::new(pool.clone())));
+    let task_service = Arc::new(TaskService::new(
+        TaskRepository::new(pool.clone()),
+        EmployeeRepository::new(pool.clone()),
+        config.task_sla,
+    ));
+    let comment_service = Arc::new(CommentService::new(
+        CommentRepository::new(pool.clone()),
+        TaskRepository::new(pool.clone()),
+        EmployeeRepository::new(pool.clone()),
+    ));
+    let task_template_service = Arc::new(TaskTemplateService::new(
+        TaskTemplateRepository::new(pool.clone()),
+        config.template_lookahead_days,
+    ));
+    let assignment_service = Arc::new(AssignmentService::new(AssignmentRepository::new(
+        pool.clone(),
+    )));
+    let attachment_service = Arc::new(AttachmentService::new(
+        AttachmentRepository::new(pool.clone()),
+        TaskRepository::new(pool.clone()),
+        LocalStorage::new(&config.attachment_dir),
+        config.attachment_limits.clone(),
+    ));
+    let report_service = Arc::new(ReportService::new(ReportRepository::new(pool.clone())));
     let auth_service = web::Data::new(Mutex::new(AuthService::new()));
     let workers = num_cpus::get();
 
@@ -58,12 +88,38 @@ async fn main() -> std::io::Result<()> {
     );
 
     let server_addr = config.server_address();
+    let max_import_bytes = config.max_import_bytes;
 
     let object_service_data = web::Data::from(object_service);
     let employee_service_data = web::Data::from(employee_service);
     let task_service_data = web::Data::from(task_service.clone());
+    let comment_service_data = web::Data::from(comment_service);
+    let task_template_service_data = web::Data::from(task_template_service.clone());
+    let assignment_service_data = web::Data::from(assignment_service);
+    let attachment_service_data = web::Data::from(attachment_service.clone());
+    let report_service_data = web::Data::from(report_service);
     let auth_service_data = web::Data::new(Mutex::new(AuthService::new()));
 
+    if config.template_scheduler_interval_secs > 0 {
+        println!("Starting task template scheduler...");
+        task_template_service
+            .as_ref()
+            .clone()
+            .spawn_scheduler(Duration::from_secs(config.template_scheduler_interval_secs));
+    }
+
+    if config.task_purge_interval_secs > 0 {
+        println!(
+            "Purging tasks deleted more than {} days ago...",
+            config.task_retention_days
+        );
+        task_service.as_ref().clone().spawn_purge_job(
+            attachment_service.as_ref().clone(),
+            chrono::Duration::days(config.task_retention_days),
+            Duration::from_secs(config.task_purge_interval_secs),
+        );
+    }
+
     // Create and run the HTTP server
     // Test commit
     HttpServer::new(move || {
@@ -71,10 +127,15 @@ async fn main() -> std::io::Result<()> {
             .app_data(object_service_data.clone())
             .app_data(employee_service_data.clone())
             .app_data(task_service_data.clone())
+            .app_data(comment_service_data.clone())
+            .app_data(task_template_service_data.clone())
+            .app_data(assignment_service_data.clone())
+            .app_data(attachment_service_data.clone())
+            .app_data(report_service_data.clone())
             .app_data(auth_service_data.clone())
             .wrap(Logger::default())
             .wrap(AuthMiddleware::new(auth_service.clone()))
-            .configure(configure_routes)
+            .configure(|cfg| configure_routes(cfg, max_import_bytes))
     })
     .workers(num_cpus::get())
     .keep_alive(Duration::from_secs(75))
@@ -84,7 +145,7 @@ async fn main() -> std::io::Result<()> {
     .await
 }
 
-fn configure_routes(config: &mut web::ServiceConfig) {
+fn configure_routes(config: &mut web::ServiceConfig, max_import_bytes: usize) {
     config
         .service(handlers::login)
         .service(handlers::refresh_token)
@@ -100,18 +161,142 @@ fn configure_routes(config: &mut web::ServiceConfig) {
         )
         .service(
             web::scope("/employees")
+                .app_data(web::PayloadConfig::new(max_import_bytes))
+                .service(handlers::import_employees)
+                .service(handlers::get_import_job)
                 .service(handlers::get_employees)
+                .service(handlers::get_employee_workload)
                 .service(handlers::get_employee)
+                .service(handlers::get_employee_tasks)
+                .service(handlers::deactivate_employee)
+                .service(handlers::reactivate_employee)
                 .service(handlers::get_employees_by_store)
+                .service(handlers::get_employee_mentions)
                 .service(handlers::create_employees),
         )
         .service(
             web::scope("/tasks")
                 .service(handlers::get_tasks)
+                .service(handlers::get_task_board)
                 .service(handlers::get_task)
                 .service(handlers::create_task)
+                .service(handlers::bulk_update_tasks)
                 .service(handlers::update_task)
+                .service(handlers::get_task_transitions)
+                .service(handlers::transition_task)
+                .service(handlers::move_task)
+                .service(handlers::get_task_history)
+                .service(handlers::get_task_tree)
+                .service(handlers::get_task_dependencies)
+                .service(handlers::add_task_dependency)
+                .service(handlers::remove_task_dependency)
+                .service(handlers::get_task_comments)
+                .service(handlers::get_task_comment)
+                .service(handlers::create_task_comment)
+                .service(handlers::update_task_comment)
+                .service(handlers::delete_task_comment)
+                .service(handlers::get_task_comment_revisions)
+                .service(handlers::get_task_mentions)
+                .service(handlers::get_task_attachments)
+                .service(handlers::create_task_attachment)
+                .service(handlers::download_task_attachment)
+                .service(handlers::delete_task_attachment)
                 .service(handlers::delete_task)
-                .service(handlers::assign_task),
+                .service(handlers::restore_task)
+                .service(handlers::set_task_tags)
+                .service(handlers::assign_task)
+                .service(handlers::auto_assign_task)
+                .service(handlers::unassign_task),
+        )
+        .service(web::scope("/tags").service(handlers::get_tags))
+        .service(web::scope("/reports").service(handlers::get_task_report))
+        .service(
+            web::scope("/stores")
+                .service(handlers::get_assignment_strategies)
+                .service(handlers::get_assignment_strategy)
+                .service(handlers::set_assignment_strategy)
+                .service(handlers::delete_assignment_strategy),
+        )
+        .service(
+            web::scope("/task-templates")
+                .service(handlers::get_task_templates)
+                .service(handlers::get_task_template)
+                .service(handlers::create_task_template)
+                .service(handlers::update_task_template)
+                .service(handlers::delete_task_template)
+                .service(handlers::get_task_template_occurrences)
+                .service(handlers::materialize_task_template),
         );
 }
+
+const MIGRATE_USAGE: &str =
+    "Usage: rust-api-framework [migrate [--dry-run] | migrate down <version> [--dry-run]]";
+
+/// `migrate` applies pending migrations and `migrate down <version>` reverts
+/// those newer than `version`; `--dry-run` lists what would happen instead.
+async fn migrate_command(config: &AppConfig, args: &[&str]) -> std::io::Result<()> {
+    let (dry_run, args) = match args {
+        [rest @ .., "--dry-run"] => (true, rest),
+        _ => (false, args),
+    };
+    let target = match args {
+        ["migrate"] => None,
+        ["migrate", "down", version] => match version.parse::<i64>() {
+            Ok(version) => Some(version),
+            Err(_) => exit_with_usage(),
+        },
+        _ => exit_with_usage(),
+    };
+
+    let pool = match database::connect(config).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let result = match (target, dry_run) {
+        (None, true) => migrations::migration_status(&pool).await.map(|statuses| {
+            for status in statuses {
+                match status.applied_at {
+                    Some(applied_at) => println!(
+                        "{:04} {} applied {}",
+                        status.version, status.name, applied_at
+                    ),
+                    None => println!("{:04} {} pending", status.version, status.name),
+                }
+            }
+        }),
+        (None, false) => migrations::run_migrations(&pool).await.map(|versions| {
+            println!("Applied {} migration(s)", versions.len());
+        }),
+        (Some(target), true) => migrations::revert_plan(&pool, target).await.map(|plan| {
+            for migration in plan {
+                println!(
+                    "{:04} {} would be reverted",
+                    migration.version, migration.name
+                );
+            }
+        }),
+        (Some(target), false) => {
+            migrations::revert_migrations(&pool, target)
+                .await
+                .map(|versions| {
+                    println!("Reverted {} migration(s)", versions.len());
+                })
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("Migration failed: {}", e);
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn exit_with_usage() -> ! {
+    eprintln!("{}", MIGRATE_USAGE);
+    std::process::exit(2);
+}
@@ -1,9 +1,10 @@
 use crate::errors::ApiError;
+use crate::models::auth::{Actor, Claims};
 use crate::services::AuthService;
 use actix_web::{
-    Error, HttpMessage, ResponseError, web,
+    Error, FromRequest, HttpMessage, HttpRequest, ResponseError, web,
     body::EitherBody,
-    dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+    dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
 };
 use futures_util::future::LocalBoxFuture;
 use std::future::{Ready, ready};
@@ -73,8 +74,8 @@ where
         let token = match auth_header {
             Some(header) => match header.to_str() {
                 Ok(header_str) => {
-                    if header_str.starts_with("Bearer ") {
-                        &header_str[7..]
+                    if let Some(token) = header_str.strip_prefix("Bearer ") {
+                        token
                     } else {
                         return Box::pin(async move {
                             let response = ApiError::BadRequest(
@@ -129,4 +130,19 @@ where
             }),
         }
     }
-}
\ No newline at end of file
+}
+
+/// Extracts the caller from the claims `AuthMiddleware` stored on the request.
+impl FromRequest for Actor {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<Claims>()
+                .map(Actor::from)
+                .ok_or_else(|| ApiError::AuthorizationError("Not authenticated".to_string())),
+        )
+    }
+}
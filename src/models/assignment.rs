@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// How a store picks an assignee for tasks created without one. Only active
+/// employees of the store are considered.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AssignmentStrategy {
+    /// Cycles through the store's employees in id order.
+    RoundRobin,
+    /// The employee with the fewest open tasks; ties go to the lowest id.
+    LeastOpen,
+    /// The store's managers: employees with no active manager in the same
+    /// store. Among several, the one with the fewest open tasks.
+    ManagerHierarchy,
+}
+
+#[derive(Serialize, Clone, Debug, FromRow)]
+pub struct StoreAssignmentSettings {
+    pub store_id: i32,
+    pub strategy: AssignmentStrategy,
+    pub last_assigned_employee_id: Option<i32>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AssignmentSettingsRequest {
+    pub strategy: AssignmentStrategy,
+}
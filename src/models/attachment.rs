@@ -0,0 +1,154 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+const MAX_FILE_NAME_LENGTH: usize = 255;
+
+#[derive(Serialize, Deserialize, Clone, Debug, FromRow)]
+pub struct TaskAttachment {
+    pub id: i32,
+    pub task_id: i32,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    /// Hex SHA-256 of the contents, which is also the storage key.
+    pub sha256: String,
+    pub uploaded_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Upload size and type limits.
+#[derive(Clone, Debug)]
+pub struct AttachmentLimits {
+    pub max_bytes: usize,
+    pub allowed_types: Vec<String>,
+}
+
+impl Default for AttachmentLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: 10 * 1024 * 1024,
+            allowed_types: [
+                "image/jpeg",
+                "image/png",
+                "image/gif",
+                "image/webp",
+                "image/heic",
+                "application/pdf",
+            ]
+            .map(String::from)
+            .to_vec(),
+        }
+    }
+}
+
+impl AttachmentLimits {
+    /// Checks an upload against the limits and returns its content type,
+    /// which is taken from the file's signature rather than the type the
+    /// client declared.
+    pub fn check(&self, data: &[u8]) -> Result<&'static str, String> {
+        if data.is_empty() {
+            return Err("Attachment cannot be empty".to_string());
+        }
+        if data.len() > self.max_bytes {
+            return Err(format!(
+                "Attachment cannot be larger than {} bytes",
+                self.max_bytes
+            ));
+        }
+
+        match sniff_content_type(data) {
+            Some(content_type) if self.allowed_types.iter().any(|t| t == content_type) => {
+                Ok(content_type)
+            }
+            Some(content_type) => Err(format!(
+                "Attachments of type {} are not allowed",
+                content_type
+            )),
+            None => Err("Unsupported attachment type".to_string()),
+        }
+    }
+}
+
+/// File signatures of the supported types: the offset and bytes each file
+/// must contain. WebP files must also start with `RIFF`.
+const SIGNATURES: &[(usize, &[u8], &str)] = &[
+    (0, b"\xFF\xD8\xFF", "image/jpeg"),
+    (0, b"\x89PNG\r\n\x1A\n", "image/png"),
+    (0, b"GIF87a", "image/gif"),
+    (0, b"GIF89a", "image/gif"),
+    (8, b"WEBP", "image/webp"),
+    (4, b"ftypheic", "image/heic"),
+    (4, b"ftypheix", "image/heic"),
+    (0, b"%PDF-", "application/pdf"),
+];
+
+/// Identifies the supported file types from their leading bytes.
+pub fn sniff_content_type(data: &[u8]) -> Option<&'static str> {
+    SIGNATURES
+        .iter()
+        .find(|(offset, signature, content_type)| {
+            data.get(*offset..)
+                .is_some_and(|rest| rest.starts_with(signature))
+                && (*content_type != "image/webp" || data.starts_with(b"RIFF"))
+        })
+        .map(|(_, _, content_type)| *content_type)
+}
+
+/// Reduces a client supplied file name to its last path component, without
+/// control characters, so it is safe to echo back in `Content-Disposition`.
+pub fn sanitize_file_name(name: Option<&str>) -> String {
+    let name: String = name
+        .unwrap_or_default()
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_FILE_NAME_LENGTH)
+        .collect();
+    let name = name.trim();
+
+    if name.is_empty() || name == "." || name == ".." {
+        "attachment".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_type_comes_from_the_file_signature() {
+        let limits = AttachmentLimits::default();
+
+        assert_eq!(limits.check(b"%PDF-1.7\n..."), Ok("application/pdf"));
+        assert_eq!(
+            limits.check(&[0xFF, 0xD8, 0xFF, 0xE0, 0x00]),
+            Ok("image/jpeg")
+        );
+        assert!(limits.check(b"<html></html>").is_err());
+        assert!(limits.check(b"").is_err());
+
+        let pdf_only = AttachmentLimits {
+            max_bytes: 8,
+            allowed_types: vec!["application/pdf".to_string()],
+        };
+        assert!(pdf_only.check(&[0xFF, 0xD8, 0xFF, 0xE0]).is_err());
+        assert!(pdf_only.check(b"%PDF-1.7\n...").is_err());
+    }
+
+    #[test]
+    fn test_file_names_are_sanitized() {
+        assert_eq!(sanitize_file_name(Some("../../etc/passwd")), "passwd");
+        assert_eq!(
+            sanitize_file_name(Some("C:\\photos\\shelf.jpg")),
+            "shelf.jpg"
+        );
+        assert_eq!(sanitize_file_name(Some("bad\nname.pdf")), "badname.pdf");
+        assert_eq!(sanitize_file_name(Some("..")), "attachment");
+        assert_eq!(sanitize_file_name(None), "attachment");
+    }
+}
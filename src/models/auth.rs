@@ -22,7 +22,7 @@ pub struct TokenResponse {
     pub refresh_token: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
     pub exp: i64,
@@ -30,6 +30,37 @@ pub struct Claims {
     pub role: String,
 }
 
+/// The authenticated user behind a request, taken from the verified JWT
+/// claims. Recorded on audit entries so changes can be traced to a person.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Actor {
+    pub username: String,
+    pub role: String,
+}
+
+impl Actor {
+    /// The actor recorded for changes made by background jobs.
+    pub fn system(job: &str) -> Self {
+        Self {
+            username: job.to_string(),
+            role: "system".to_string(),
+        }
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.role == "admin"
+    }
+}
+
+impl From<&Claims> for Actor {
+    fn from(claims: &Claims) -> Self {
+        Self {
+            username: claims.sub.clone(),
+            role: claims.role.clone(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct User {
     pub username: String,
@@ -0,0 +1,144 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+const MAX_COMMENT_LENGTH: usize = 10_000;
+
+#[derive(Serialize, Deserialize, Clone, Debug, FromRow)]
+pub struct TaskComment {
+    pub id: i32,
+    pub task_id: i32,
+    pub author: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// External IDs of the employees mentioned in `body`.
+    #[sqlx(skip)]
+    pub mentions: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Debug, FromRow)]
+pub struct CommentRevision {
+    pub id: i32,
+    pub comment_id: i32,
+    pub body: String,
+    pub edited_by: String,
+    pub edited_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Clone, Debug, FromRow)]
+pub struct MentionedEmployee {
+    pub employee_id: i32,
+    pub external_id: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub comment_count: i64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CommentRequest {
+    pub body: String,
+}
+
+impl CommentRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.body.trim().is_empty() {
+            return Err("Comment cannot be empty".to_string());
+        }
+
+        if self.body.len() > MAX_COMMENT_LENGTH {
+            return Err(format!(
+                "Comment cannot be longer than {} characters",
+                MAX_COMMENT_LENGTH
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds `@EMP001`-style mentions in a comment body, in order of first
+/// appearance. An `@` directly after a word character (as in an email
+/// address) does not start a mention.
+pub fn extract_mentions(body: &str) -> Vec<String> {
+    let mut mentions: Vec<String> = Vec::new();
+    let mut previous: Option<char> = None;
+    let mut chars = body.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        let starts_mention = c == '@' && !previous.is_some_and(is_mention_char);
+        previous = Some(c);
+
+        if !starts_mention {
+            continue;
+        }
+
+        let start = index + 1;
+        let mut end = start;
+        while let Some(&(next_index, next)) = chars.peek()
+            && is_mention_char(next)
+        {
+            end = next_index + next.len_utf8();
+            previous = Some(next);
+            chars.next();
+        }
+
+        let mention = &body[start..end];
+        if !mention.is_empty() && !mentions.iter().any(|m| m == mention) {
+            mentions.push(mention.to_string());
+        }
+    }
+
+    mentions
+}
+
+fn is_mention_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_mentions() {
+        assert_eq!(
+            extract_mentions("@EMP001 please check with @EMP002, then @EMP001 again"),
+            ["EMP001", "EMP002"]
+        );
+    }
+
+    #[test]
+    fn test_extract_mentions_ignores_email_addresses() {
+        assert_eq!(
+            extract_mentions("mail john@example.com or ping (@EMP003)."),
+            ["EMP003"]
+        );
+    }
+
+    #[test]
+    fn test_extract_mentions_ignores_bare_at_sign() {
+        assert!(extract_mentions("meet @ 5pm @").is_empty());
+    }
+
+    #[test]
+    fn test_comment_request_validate() {
+        assert!(
+            CommentRequest {
+                body: "Looks good".to_string()
+            }
+            .validate()
+            .is_ok()
+        );
+        assert_eq!(
+            CommentRequest {
+                body: "  ".to_string()
+            }
+            .validate()
+            .unwrap_err(),
+            "Comment cannot be empty"
+        );
+    }
+}
@@ -1,5 +1,9 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::utils::tabular::Exportable;
 
 #[derive(Serialize, Deserialize, Clone, Debug, FromRow)]
 pub struct Employee {
@@ -9,6 +13,7 @@ pub struct Employee {
     pub last_name: String,
     pub store_id: Option<i32>,
     pub email: Option<String>,
+    pub deactivated_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -20,6 +25,10 @@ pub struct EmployeeQuery {
     pub last_name: Option<String>,
     pub email: Option<String>,
     pub external_id: Option<String>,
+    pub active: Option<bool>,
+    pub sort: Option<String>,
+    pub cursor: Option<String>,
+    pub include_total: Option<bool>,
 }
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
@@ -36,31 +45,165 @@ pub struct CreateEmployeesRequest {
     pub employees: Vec<CreateEmployee>,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+pub struct EmployeeImportQuery {
+    pub dry_run: Option<bool>,
+    pub delimiter: Option<String>,
+    pub background: Option<bool>,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct EmployeeImportReport {
+    pub dry_run: bool,
+    pub total_rows: usize,
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub rejected: Vec<ImportRejection>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ImportRejection {
+    pub line: usize,
+    pub external_id: Option<String>,
+    pub error: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct WorkloadQuery {
+    pub store_id: Option<i32>,
+}
+
+/// Open tasks by priority. Tasks created without a priority are counted as
+/// `unprioritized`.
+#[derive(Serialize, Clone, Debug, Default, PartialEq, FromRow)]
+pub struct PriorityMix {
+    pub high: i64,
+    pub medium: i64,
+    pub low: i64,
+    pub unprioritized: i64,
+}
+
+#[derive(Serialize, Clone, Debug, FromRow)]
+pub struct EmployeeWorkload {
+    pub employee_id: i32,
+    pub external_id: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub store_id: Option<i32>,
+    pub deactivated: bool,
+    pub open_tasks: i64,
+    pub overdue_tasks: i64,
+    #[sqlx(flatten)]
+    pub priorities: PriorityMix,
+}
+
+/// Open tasks per store. A task without its own store counts towards its
+/// assignee's store.
+#[derive(Serialize, Clone, Debug, FromRow)]
+pub struct StoreWorkload {
+    pub store_id: i32,
+    pub open_tasks: i64,
+    pub unassigned_tasks: i64,
+    pub overdue_tasks: i64,
+    #[sqlx(flatten)]
+    pub priorities: PriorityMix,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Workload {
+    pub employees: Vec<EmployeeWorkload>,
+    pub stores: Vec<StoreWorkload>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum ImportJobStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ImportJob {
+    pub id: Uuid,
+    pub status: ImportJobStatus,
+    pub dry_run: bool,
+    pub total_rows: usize,
+    pub report: Option<EmployeeImportReport>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl Exportable for Employee {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "external_id",
+        "first_name",
+        "last_name",
+        "store_id",
+        "email",
+        "deactivated_at",
+    ];
+}
+
+impl CreateEmployee {
+    pub fn validate(&self) -> Result<(), String> {
+        self.validate_fields()?;
+        if self.store_id.is_none() {
+            return Err("Store ID is required".to_string());
+        }
+        Ok(())
+    }
+
+    /// Checks everything but `store_id`, which an import may leave out for
+    /// employees that already exist.
+    pub fn validate_fields(&self) -> Result<(), String> {
+        if self.external_id.trim().is_empty() {
+            return Err("External ID cannot be empty".to_string());
+        }
+        if self.first_name.trim().is_empty() {
+            return Err("First name cannot be empty".to_string());
+        }
+        if self.last_name.trim().is_empty() {
+            return Err("Last name cannot be empty".to_string());
+        }
+        if let Some(email) = &self.email
+            && !email.contains('@')
+        {
+            return Err(format!("Invalid email format: {}", email));
+        }
+        Ok(())
+    }
+}
+
 impl CreateEmployeesRequest {
     pub fn validate(&self) -> Result<(), String> {
         if self.employees.is_empty() {
             return Err("Employee list cannot be empty".to_string());
         }
         for employee in &self.employees {
-            if employee.external_id.trim().is_empty() {
-                return Err("External ID cannot be empty".to_string());
-            }
-            if employee.first_name.trim().is_empty() {
-                return Err("First name cannot be empty".to_string());
-            }
-            if employee.last_name.trim().is_empty() {
-                return Err("Last name cannot be empty".to_string());
-            }
-            if let Some(email) = &employee.email {
-                if !email.contains('@') {
-                    return Err(format!("Invalid email format: {}", email));
-                }
-            }
+            employee.validate()?;
         }
         Ok(())
     }
 }
 
+impl ImportJob {
+    pub fn new(dry_run: bool, total_rows: usize) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            status: ImportJobStatus::Pending,
+            dry_run,
+            total_rows,
+            report: None,
+            error: None,
+            created_at: Utc::now(),
+            finished_at: None,
+        }
+    }
+}
+
 /**
  * Unit tests for Employee model and related structs
  *
@@ -79,6 +222,7 @@ mod tests {
             last_name: "Doe".to_string(),
             store_id: Some(10),
             email: Some("test@test.com".to_string()),
+            deactivated_at: None,
         };
 
         assert_eq!(employee.id, 1);
@@ -113,5 +257,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_create_employee_validate_requires_store_id() {
+        let employee = CreateEmployee {
+            external_id: "ext123".to_string(),
+            first_name: "John".to_string(),
+            last_name: "Doe".to_string(),
+            store_id: None,
+            email: None,
+        };
+
+        assert_eq!(employee.validate().unwrap_err(), "Store ID is required");
+    }
+
+    #[test]
+    fn test_create_employee_validate_invalid_email() {
+        let employee = CreateEmployee {
+            external_id: "ext123".to_string(),
+            first_name: "John".to_string(),
+            last_name: "Doe".to_string(),
+            store_id: Some(1),
+            email: Some("not-an-email".to_string()),
+        };
+
+        assert_eq!(
+            employee.validate().unwrap_err(),
+            "Invalid email format: not-an-email"
+        );
+    }
+
+    #[test]
+    fn test_import_job_new() {
+        let job = ImportJob::new(true, 25);
+
+        assert_eq!(job.status, ImportJobStatus::Pending);
+        assert!(job.dry_run);
+        assert_eq!(job.total_rows, 25);
+        assert!(job.report.is_none());
+        assert!(job.finished_at.is_none());
+    }
+
     // todo
 }
@@ -1,4 +1,14 @@
+pub mod assignment;
+pub mod attachment;
 pub mod auth;
+pub mod comment;
 pub mod employee;
 pub mod object;
+pub mod report;
+pub mod tag;
 pub mod task;
+pub mod task_board;
+pub mod task_bulk;
+pub mod task_dependency;
+pub mod task_event;
+pub mod task_template;
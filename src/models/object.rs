@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+use crate::utils::tabular::Exportable;
+
 #[derive(Serialize, Deserialize, Clone, Debug, FromRow)]
 pub struct Object {
     pub id: i32,
@@ -31,6 +33,9 @@ pub struct ObjectQuery {
     pub limit: Option<usize>,
     pub offset: Option<usize>,
     pub name: Option<String>,
+    pub sort: Option<String>,
+    pub cursor: Option<String>,
+    pub include_total: Option<bool>,
 }
 
 impl Object {
@@ -47,6 +52,11 @@ impl Object {
     }
 }
 
+impl Exportable for Object {
+    const COLUMNS: &'static [&'static str] =
+        &["id", "name", "email", "age", "created_at", "updated_at"];
+}
+
 impl CreateObjectRequest {
     pub fn validate(&self) -> Result<(), String> {
         if self.name.trim().is_empty() {
@@ -57,10 +67,10 @@ impl CreateObjectRequest {
             return Err("Invalid email format".to_string());
         }
 
-        if let Some(age) = self.age {
-            if age > 150 {
-                return Err("Age must be realistic".to_string());
-            }
+        if let Some(age) = self.age
+            && age > 150
+        {
+            return Err("Age must be realistic".to_string());
         }
 
         Ok(())
@@ -69,22 +79,22 @@ impl CreateObjectRequest {
 
 impl UpdateObjectRequest {
     pub fn validate(&self) -> Result<(), String> {
-        if let Some(name) = &self.name {
-            if name.trim().is_empty() {
-                return Err("Name cannot be empty".to_string());
-            }
+        if let Some(name) = &self.name
+            && name.trim().is_empty()
+        {
+            return Err("Name cannot be empty".to_string());
         }
 
-        if let Some(email) = &self.email {
-            if !email.contains('@') {
-                return Err("Invalid email format".to_string());
-            }
+        if let Some(email) = &self.email
+            && !email.contains('@')
+        {
+            return Err("Invalid email format".to_string());
         }
 
-        if let Some(age) = self.age {
-            if age > 150 {
-                return Err("Age must be realistic".to_string());
-            }
+        if let Some(age) = self.age
+            && age > 150
+        {
+            return Err("Age must be realistic".to_string());
         }
 
         Ok(())
@@ -0,0 +1,92 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::task::{PriorityLevel, TaskStatus};
+
+/// What a task report is broken down by. The periods bucket tasks by the
+/// report's date field, in UTC; weeks start on Monday.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportGrouping {
+    Status,
+    Priority,
+    Assignee,
+    Store,
+    Day,
+    Week,
+    Month,
+}
+
+/// The timestamp a report's date range and periods apply to.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportDateField {
+    #[default]
+    Created,
+    /// Only tasks that have been completed are counted.
+    Completed,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TaskReportQuery {
+    pub group_by: Option<ReportGrouping>,
+    pub date_field: Option<ReportDateField>,
+    /// Inclusive start of the date range.
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive end of the date range.
+    pub to: Option<DateTime<Utc>>,
+    pub store_id: Option<i32>,
+    pub assigned_to: Option<i32>,
+    pub priority_level: Option<PriorityLevel>,
+    pub status: Option<TaskStatus>,
+}
+
+/// The value a report row is grouped on; null for tasks without one, such
+/// as unassigned tasks when grouping by assignee.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum ReportKey {
+    Status(TaskStatus),
+    Priority(PriorityLevel),
+    Id(i32),
+    Period(String),
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct TaskReportRow {
+    pub key: Option<ReportKey>,
+    pub total: i64,
+    pub open: i64,
+    /// Throughput: tasks in the row that reached Done.
+    pub completed: i64,
+    pub cancelled: i64,
+    /// Open tasks past their due date.
+    pub overdue: i64,
+    /// Hours from creation to completion over the completed tasks.
+    pub mean_cycle_time_hours: Option<f64>,
+    /// Nearest-rank 90th percentile of the same cycle times.
+    pub p90_cycle_time_hours: Option<f64>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct TaskReport {
+    pub group_by: Option<ReportGrouping>,
+    pub date_field: ReportDateField,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Every task the report covers, across all groups.
+    pub totals: TaskReportRow,
+    pub groups: Vec<TaskReportRow>,
+}
+
+impl TaskReportQuery {
+    pub fn validate(&self) -> Result<(), String> {
+        if let (Some(from), Some(to)) = (self.from, self.to)
+            && from >= to
+        {
+            return Err("from must be before to".to_string());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+pub const MAX_TAG_LENGTH: usize = 50;
+pub const MAX_TAGS_PER_TASK: usize = 20;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SetTagsRequest {
+    pub tags: Vec<String>,
+}
+
+/// A tag and the number of live tasks carrying it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, FromRow)]
+pub struct TagUsage {
+    pub tag: String,
+    pub count: i64,
+}
+
+/// How a `?tags=` filter with several tags is applied.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatch {
+    /// Tasks carrying at least one of the tags.
+    #[default]
+    Any,
+    /// Tasks carrying every one of the tags.
+    All,
+}
+
+/// Tags are compared case-insensitively, so they are stored trimmed and
+/// lowercased.
+pub fn normalize_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() {
+        return Err("Tags cannot be empty".to_string());
+    }
+    if tag.chars().count() > MAX_TAG_LENGTH {
+        return Err(format!(
+            "Tags cannot be longer than {} characters",
+            MAX_TAG_LENGTH
+        ));
+    }
+    if tag.contains(',') || tag.chars().any(char::is_control) {
+        return Err(format!("Invalid tag: {}", tag));
+    }
+
+    Ok(tag)
+}
+
+/// Normalizes a list of tags, dropping duplicates. The result is sorted.
+pub fn normalize_tags<'a>(tags: impl IntoIterator<Item = &'a str>) -> Result<Vec<String>, String> {
+    let mut tags = tags
+        .into_iter()
+        .map(normalize_tag)
+        .collect::<Result<Vec<_>, _>>()?;
+    tags.sort();
+    tags.dedup();
+
+    Ok(tags)
+}
+
+impl SetTagsRequest {
+    /// Returns the normalized tags to store on the task.
+    pub fn normalized(&self) -> Result<Vec<String>, String> {
+        let tags = normalize_tags(self.tags.iter().map(String::as_str))?;
+        if tags.len() > MAX_TAGS_PER_TASK {
+            return Err(format!(
+                "A task cannot have more than {} tags",
+                MAX_TAGS_PER_TASK
+            ));
+        }
+
+        Ok(tags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tags_are_normalized_and_deduplicated() {
+        let request = SetTagsRequest {
+            tags: vec![
+                " Urgent ".to_string(),
+                "freezer".to_string(),
+                "URGENT".to_string(),
+            ],
+        };
+
+        assert_eq!(request.normalized().unwrap(), ["freezer", "urgent"]);
+    }
+
+    #[test]
+    fn test_invalid_tags_are_rejected() {
+        for tag in ["", "   ", "a,b", &"x".repeat(MAX_TAG_LENGTH + 1)] {
+            assert!(normalize_tag(tag).is_err(), "{:?} should be rejected", tag);
+        }
+
+        let request = SetTagsRequest {
+            tags: (0..=MAX_TAGS_PER_TASK)
+                .map(|i| format!("tag{}", i))
+                .collect(),
+        };
+        assert!(request.normalized().is_err());
+    }
+}
@@ -1,8 +1,12 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use std::fmt;
 
+use crate::models::tag::{TagMatch, normalize_tags};
+use crate::models::task_dependency::TaskProgress;
+use crate::utils::tabular::Exportable;
+
 #[derive(Serialize, Deserialize, Clone, Debug, FromRow)]
 pub struct Task {
     pub id: i32,
@@ -11,10 +15,42 @@ pub struct Task {
     pub priority_level: Option<PriorityLevel>,
     pub status: Option<TaskStatus>,
     pub assigned_to: Option<i32>,
+    pub store_id: Option<i32>,
+    pub parent_id: Option<i32>,
+    pub status_reason: Option<String>,
+    pub start_at: Option<DateTime<Utc>>,
+    pub due_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub deleted_at: Option<DateTime<Utc>>,
+    /// Filled in by the service when the task is returned to a client.
+    #[sqlx(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sla: Option<TaskSla>,
+    /// Completion of the task's subtasks, filled in when they are loaded.
+    #[sqlx(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<TaskProgress>,
+    /// Filled in by the service when the task is returned to a client.
+    #[sqlx(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+}
+
+/// Where a task stands against the resolution SLA for its priority.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TaskSla {
+    pub deadline: DateTime<Utc>,
+    pub breached: bool,
+}
+
+/// Hours allowed from creation to completion, per priority level.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SlaPolicy {
+    pub low_hours: i64,
+    pub medium_hours: i64,
+    pub high_hours: i64,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -24,6 +60,10 @@ pub struct CreateTaskRequest {
     pub priority_level: Option<PriorityLevel>,
     pub status: Option<TaskStatus>,
     pub assigned_to: Option<i32>,
+    pub store_id: Option<i32>,
+    pub parent_id: Option<i32>,
+    pub start_at: Option<DateTime<Utc>>,
+    pub due_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -33,6 +73,21 @@ pub struct UpdateTaskRequest {
     pub priority_level: Option<PriorityLevel>,
     pub status: Option<TaskStatus>,
     pub assigned_to: Option<i32>,
+    pub parent_id: Option<i32>,
+    pub start_at: Option<DateTime<Utc>>,
+    pub due_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TransitionTaskRequest {
+    pub status: TaskStatus,
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TaskTransitions {
+    pub status: TaskStatus,
+    pub allowed: Vec<TaskStatus>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -44,8 +99,34 @@ pub struct TaskQuery {
     pub status: Option<TaskStatus>,
     pub priority_level: Option<PriorityLevel>,
     pub assigned_to: Option<i32>,
+    pub store_id: Option<i32>,
+    pub parent_id: Option<i32>,
+    pub overdue: Option<bool>,
+    pub due_before: Option<DateTime<Utc>>,
+    pub due_after: Option<DateTime<Utc>>,
+    /// Comma separated tags, matched according to `tag_match`.
+    pub tags: Option<String>,
+    pub tag_match: Option<TagMatch>,
+    pub sort: Option<String>,
     pub sort_by: Option<String>,
     pub sort_direction: Option<String>,
+    pub cursor: Option<String>,
+    pub include_total: Option<bool>,
+    pub include_deleted: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TaskLookupQuery {
+    pub include_deleted: Option<bool>,
+}
+
+/// What a purge of deleted tasks removed. `attachment_sha256s` are the
+/// contents the purged attachments referred to; other attachments may still
+/// share some of them.
+#[derive(Debug, Default)]
+pub struct PurgedTasks {
+    pub count: u64,
+    pub attachment_sha256s: Vec<String>,
 }
 
 impl fmt::Display for TaskStatus {
@@ -53,7 +134,10 @@ impl fmt::Display for TaskStatus {
         match self {
             TaskStatus::ToDo => write!(f, "To Do"),
             TaskStatus::InProgress => write!(f, "In Progress"),
+            TaskStatus::Blocked => write!(f, "Blocked"),
+            TaskStatus::InReview => write!(f, "In Review"),
             TaskStatus::Done => write!(f, "Done"),
+            TaskStatus::Cancelled => write!(f, "Cancelled"),
         }
     }
 }
@@ -73,7 +157,10 @@ impl fmt::Display for PriorityLevel {
 pub enum TaskStatus {
     ToDo,
     InProgress,
+    Blocked,
+    InReview,
     Done,
+    Cancelled,
 }
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, sqlx::Type)]
@@ -101,10 +188,185 @@ impl Task {
             priority_level: Some(priority_level.unwrap_or(PriorityLevel::Medium)),
             status: status.or(Some(TaskStatus::ToDo)),
             assigned_to,
+            store_id: None,
+            parent_id: None,
+            status_reason: None,
+            start_at: None,
+            due_at: None,
             completed_at: None,
             created_at: now,
             updated_at: now,
             deleted_at: None,
+            sla: None,
+            progress: None,
+            tags: None,
+        }
+    }
+}
+
+impl Exportable for Task {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "title",
+        "description",
+        "priority_level",
+        "status",
+        "assigned_to",
+        "store_id",
+        "parent_id",
+        "status_reason",
+        "start_at",
+        "due_at",
+        "completed_at",
+        "created_at",
+        "updated_at",
+        "deleted_at",
+    ];
+}
+
+impl PriorityLevel {
+    /// The form priorities are stored in: the variant name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PriorityLevel::Low => "Low",
+            PriorityLevel::Medium => "Medium",
+            PriorityLevel::High => "High",
+        }
+    }
+}
+
+impl TaskStatus {
+    /// The form statuses are stored in: the variant name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskStatus::ToDo => "ToDo",
+            TaskStatus::InProgress => "InProgress",
+            TaskStatus::Blocked => "Blocked",
+            TaskStatus::InReview => "InReview",
+            TaskStatus::Done => "Done",
+            TaskStatus::Cancelled => "Cancelled",
+        }
+    }
+
+    /// Every status, in board column order.
+    pub const ALL: [TaskStatus; 6] = [
+        TaskStatus::ToDo,
+        TaskStatus::InProgress,
+        TaskStatus::Blocked,
+        TaskStatus::InReview,
+        TaskStatus::Done,
+        TaskStatus::Cancelled,
+    ];
+
+    /// The statuses a task in this status may move to next.
+    pub fn next_statuses(&self) -> &'static [TaskStatus] {
+        use TaskStatus::*;
+
+        match self {
+            ToDo => &[InProgress, Blocked, Cancelled],
+            InProgress => &[ToDo, Blocked, InReview, Done, Cancelled],
+            Blocked => &[ToDo, InProgress, Cancelled],
+            InReview => &[InProgress, Done, Cancelled],
+            Done => &[ToDo, InProgress],
+            Cancelled => &[ToDo],
+        }
+    }
+
+    /// Done and Cancelled tasks are closed; moving out of them reopens the task.
+    pub fn is_closed(&self) -> bool {
+        matches!(self, TaskStatus::Done | TaskStatus::Cancelled)
+    }
+
+    pub fn check_transition(&self, next: TaskStatus, reason: Option<&str>) -> Result<(), String> {
+        if *self == next {
+            return Err(format!("Task is already {}", next));
+        }
+
+        if !self.next_statuses().contains(&next) {
+            return Err(format!("Cannot move a task from {} to {}", self, next));
+        }
+
+        if self.is_closed() && reason.is_none_or(|reason| reason.trim().is_empty()) {
+            return Err("A reason is required to reopen a task".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for SlaPolicy {
+    fn default() -> Self {
+        Self {
+            low_hours: 168,
+            medium_hours: 72,
+            high_hours: 24,
+        }
+    }
+}
+
+impl SlaPolicy {
+    /// Tasks without a priority are held to the Medium target.
+    pub fn hours_for(&self, priority: Option<PriorityLevel>) -> i64 {
+        match priority.unwrap_or(PriorityLevel::Medium) {
+            PriorityLevel::Low => self.low_hours,
+            PriorityLevel::Medium => self.medium_hours,
+            PriorityLevel::High => self.high_hours,
+        }
+    }
+
+    /// A task breaches its SLA when it was completed after the deadline, or
+    /// is still open past it. Cancelled tasks are never flagged.
+    pub fn evaluate(&self, task: &Task, now: DateTime<Utc>) -> TaskSla {
+        let deadline = task.created_at + Duration::hours(self.hours_for(task.priority_level));
+        let breached = match task.status {
+            Some(TaskStatus::Cancelled) => false,
+            _ => task.completed_at.unwrap_or(now) > deadline,
+        };
+
+        TaskSla { deadline, breached }
+    }
+}
+
+impl Task {
+    /// Past its due date and not yet closed.
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        !self.status.is_some_and(|status| status.is_closed())
+            && self.due_at.is_some_and(|due_at| due_at < now)
+    }
+}
+
+impl TaskQuery {
+    /// Returns the requested sort in `?sort=` syntax, translating the older
+    /// `sort_by`/`sort_direction` pair when `sort` is not given.
+    pub fn sort_spec(&self) -> Result<Option<String>, String> {
+        if self.sort.is_some() {
+            return Ok(self.sort.clone());
+        }
+
+        let sort_by = match &self.sort_by {
+            Some(sort_by) => sort_by,
+            None => return Ok(None),
+        };
+
+        match self
+            .sort_direction
+            .as_deref()
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            None | Some("asc") => Ok(Some(sort_by.clone())),
+            Some("desc") => Ok(Some(format!("-{}", sort_by))),
+            Some(direction) => Err(format!("Invalid sort direction: {}", direction)),
+        }
+    }
+}
+
+impl TaskQuery {
+    /// The normalized tags given in `?tags=`, if any.
+    pub fn tag_filter(&self) -> Result<Vec<String>, String> {
+        match &self.tags {
+            Some(tags) => normalize_tags(tags.split(',').filter(|tag| !tag.trim().is_empty())),
+            None => Ok(Vec::new()),
         }
     }
 }
@@ -115,19 +377,45 @@ impl CreateTaskRequest {
             return Err("Title cannot be empty".to_string());
         }
 
+        validate_schedule(self.start_at, self.due_at)
+    }
+}
+
+/// A task cannot be due before it is scheduled to start.
+pub fn validate_schedule(
+    start_at: Option<DateTime<Utc>>,
+    due_at: Option<DateTime<Utc>>,
+) -> Result<(), String> {
+    if let (Some(start_at), Some(due_at)) = (start_at, due_at)
+        && due_at < start_at
+    {
+        return Err("due_at cannot be before start_at".to_string());
+    }
+
+    Ok(())
+}
+
+impl TransitionTaskRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(reason) = &self.reason
+            && reason.len() > 500
+        {
+            return Err("Reason cannot be longer than 500 characters".to_string());
+        }
+
         Ok(())
     }
 }
 
 impl UpdateTaskRequest {
     pub fn validate(&self) -> Result<(), String> {
-        if let Some(title) = &self.title {
-            if title.trim().is_empty() {
-                return Err("Title cannot be empty".to_string());
-            }
+        if let Some(title) = &self.title
+            && title.trim().is_empty()
+        {
+            return Err("Title cannot be empty".to_string());
         }
 
-        Ok(())
+        validate_schedule(self.start_at, self.due_at)
     }
 }
 
@@ -176,6 +464,93 @@ mod tests {
         assert_eq!(TaskStatus::ToDo.to_string(), "To Do");
         assert_eq!(TaskStatus::InProgress.to_string(), "In Progress");
         assert_eq!(TaskStatus::Done.to_string(), "Done");
+        assert_eq!(TaskStatus::InReview.to_string(), "In Review");
+        assert_eq!(TaskStatus::Cancelled.to_string(), "Cancelled");
+    }
+
+    #[test]
+    fn test_task_status_forward_transitions() {
+        assert!(
+            TaskStatus::ToDo
+                .check_transition(TaskStatus::InProgress, None)
+                .is_ok()
+        );
+        assert!(
+            TaskStatus::InProgress
+                .check_transition(TaskStatus::InReview, None)
+                .is_ok()
+        );
+        assert!(
+            TaskStatus::InReview
+                .check_transition(TaskStatus::Done, None)
+                .is_ok()
+        );
+        assert!(
+            TaskStatus::InProgress
+                .check_transition(TaskStatus::Blocked, None)
+                .is_ok()
+        );
+        assert!(
+            TaskStatus::Blocked
+                .check_transition(TaskStatus::InProgress, None)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_task_status_rejects_disallowed_transitions() {
+        assert_eq!(
+            TaskStatus::ToDo
+                .check_transition(TaskStatus::Done, None)
+                .unwrap_err(),
+            "Cannot move a task from To Do to Done"
+        );
+        assert!(
+            TaskStatus::Cancelled
+                .check_transition(TaskStatus::Done, Some("oops"))
+                .is_err()
+        );
+        assert_eq!(
+            TaskStatus::InProgress
+                .check_transition(TaskStatus::InProgress, None)
+                .unwrap_err(),
+            "Task is already In Progress"
+        );
+    }
+
+    #[test]
+    fn test_task_status_reopen_requires_reason() {
+        assert_eq!(
+            TaskStatus::Done
+                .check_transition(TaskStatus::ToDo, None)
+                .unwrap_err(),
+            "A reason is required to reopen a task"
+        );
+        assert!(
+            TaskStatus::Done
+                .check_transition(TaskStatus::InProgress, Some("  "))
+                .is_err()
+        );
+        assert!(
+            TaskStatus::Cancelled
+                .check_transition(TaskStatus::ToDo, Some("Needed after all"))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_every_status_can_be_left() {
+        for status in [
+            TaskStatus::ToDo,
+            TaskStatus::InProgress,
+            TaskStatus::Blocked,
+            TaskStatus::InReview,
+            TaskStatus::Done,
+            TaskStatus::Cancelled,
+        ] {
+            assert!(!status.next_statuses().is_empty());
+            assert!(!status.next_statuses().contains(&status));
+        }
     }
 
     #[test]
@@ -193,6 +568,10 @@ mod tests {
             priority_level: Some(PriorityLevel::Medium),
             status: Some(TaskStatus::ToDo),
             assigned_to: Some(1),
+            store_id: None,
+            parent_id: None,
+            start_at: None,
+            due_at: None,
         };
 
         assert!(request.validate().is_ok());
@@ -206,6 +585,10 @@ mod tests {
             priority_level: None,
             status: None,
             assigned_to: None,
+            store_id: None,
+            parent_id: None,
+            start_at: None,
+            due_at: None,
         };
 
         assert!(request.validate().is_err());
@@ -220,6 +603,10 @@ mod tests {
             priority_level: None,
             status: None,
             assigned_to: None,
+            store_id: None,
+            parent_id: None,
+            start_at: None,
+            due_at: None,
         };
 
         assert!(request.validate().is_err());
@@ -234,6 +621,9 @@ mod tests {
             priority_level: Some(PriorityLevel::High),
             status: Some(TaskStatus::Done),
             assigned_to: Some(2),
+            parent_id: None,
+            start_at: None,
+            due_at: None,
         };
 
         assert!(request.validate().is_ok());
@@ -247,6 +637,9 @@ mod tests {
             priority_level: None,
             status: None,
             assigned_to: None,
+            parent_id: None,
+            start_at: None,
+            due_at: None,
         };
 
         assert!(request.validate().is_ok());
@@ -260,6 +653,9 @@ mod tests {
             priority_level: None,
             status: None,
             assigned_to: None,
+            parent_id: None,
+            start_at: None,
+            due_at: None,
         };
 
         assert!(request.validate().is_err());
@@ -308,4 +704,130 @@ mod tests {
 
         let _json = serde_json::to_string(&task).expect("Should serialize successfully");
     }
+
+    fn sort_query(
+        sort: Option<&str>,
+        sort_by: Option<&str>,
+        sort_direction: Option<&str>,
+    ) -> TaskQuery {
+        TaskQuery {
+            limit: None,
+            offset: None,
+            id: None,
+            title: None,
+            status: None,
+            priority_level: None,
+            assigned_to: None,
+            store_id: None,
+            parent_id: None,
+            overdue: None,
+            due_before: None,
+            due_after: None,
+            sort: sort.map(str::to_string),
+            sort_by: sort_by.map(str::to_string),
+            sort_direction: sort_direction.map(str::to_string),
+            cursor: None,
+            include_total: None,
+            include_deleted: None,
+            tags: None,
+            tag_match: None,
+        }
+    }
+
+    #[test]
+    fn test_task_query_sort_spec() {
+        assert_eq!(sort_query(None, None, None).sort_spec().unwrap(), None);
+        assert_eq!(
+            sort_query(Some("-title"), Some("id"), None)
+                .sort_spec()
+                .unwrap(),
+            Some("-title".to_string())
+        );
+        assert_eq!(
+            sort_query(None, Some("title"), Some("DESC"))
+                .sort_spec()
+                .unwrap(),
+            Some("-title".to_string())
+        );
+        assert_eq!(
+            sort_query(None, Some("title"), None).sort_spec().unwrap(),
+            Some("title".to_string())
+        );
+        assert!(
+            sort_query(None, Some("title"), Some("sideways"))
+                .sort_spec()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_validate_schedule() {
+        let start = Utc::now();
+
+        assert!(validate_schedule(Some(start), Some(start + Duration::hours(1))).is_ok());
+        assert!(validate_schedule(None, Some(start)).is_ok());
+        assert_eq!(
+            validate_schedule(Some(start), Some(start - Duration::hours(1))).unwrap_err(),
+            "due_at cannot be before start_at"
+        );
+    }
+
+    #[test]
+    fn test_task_is_overdue() {
+        let now = Utc::now();
+        let mut task = Task::new(1, "Count stock".to_string(), None, None, None, None);
+        assert!(!task.is_overdue(now));
+
+        task.due_at = Some(now - Duration::hours(1));
+        assert!(task.is_overdue(now));
+
+        task.status = Some(TaskStatus::Done);
+        assert!(!task.is_overdue(now));
+    }
+
+    #[test]
+    fn test_sla_policy_evaluate() {
+        let policy = SlaPolicy::default();
+        let mut task = Task::new(
+            1,
+            "Count stock".to_string(),
+            None,
+            Some(PriorityLevel::High),
+            None,
+            None,
+        );
+        let deadline = task.created_at + Duration::hours(24);
+
+        let sla = policy.evaluate(&task, task.created_at + Duration::hours(2));
+        assert_eq!(sla.deadline, deadline);
+        assert!(!sla.breached);
+        assert!(
+            policy
+                .evaluate(&task, deadline + Duration::minutes(1))
+                .breached
+        );
+
+        task.completed_at = Some(deadline - Duration::minutes(1));
+        assert!(
+            !policy
+                .evaluate(&task, deadline + Duration::days(3))
+                .breached
+        );
+
+        task.completed_at = None;
+        task.status = Some(TaskStatus::Cancelled);
+        assert!(
+            !policy
+                .evaluate(&task, deadline + Duration::days(3))
+                .breached
+        );
+    }
+
+    #[test]
+    fn test_sla_policy_defaults_to_medium() {
+        let policy = SlaPolicy::default();
+
+        assert_eq!(policy.hours_for(None), policy.medium_hours);
+        assert_eq!(policy.hours_for(Some(PriorityLevel::Low)), 168);
+    }
 }
@@ -0,0 +1,280 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::tag::TagMatch;
+use crate::models::task::{PriorityLevel, Task, TaskQuery, TaskStatus};
+
+pub const DEFAULT_BOARD_COLUMN_SIZE: usize = 20;
+pub const MAX_BOARD_COLUMN_SIZE: usize = 100;
+
+/// What splits the board into horizontal lanes.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Swimlane {
+    Assignee,
+    Priority,
+}
+
+/// `GET /tasks/board` parameters. `limit` and `offset` page every column
+/// separately; `status` narrows the board to one column, so a client can
+/// load more of it.
+#[derive(Deserialize, Debug, Clone)]
+pub struct BoardQuery {
+    pub swimlane: Option<Swimlane>,
+    pub status: Option<TaskStatus>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    pub title: Option<String>,
+    pub priority_level: Option<PriorityLevel>,
+    pub assigned_to: Option<i32>,
+    pub store_id: Option<i32>,
+    pub parent_id: Option<i32>,
+    pub overdue: Option<bool>,
+    pub due_before: Option<DateTime<Utc>>,
+    pub due_after: Option<DateTime<Utc>>,
+    pub tags: Option<String>,
+    pub tag_match: Option<TagMatch>,
+}
+
+/// The lane a task falls in. Without swimlanes every task is in `All`.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum LaneKey {
+    All,
+    Assignee(Option<i32>),
+    Priority(Option<PriorityLevel>),
+}
+
+#[derive(Serialize, Debug)]
+pub struct Board {
+    pub swimlane: Option<Swimlane>,
+    pub lanes: Vec<BoardLane>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BoardLane {
+    pub key: LaneKey,
+    pub columns: Vec<BoardColumn>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BoardColumn {
+    pub status: TaskStatus,
+    /// Every task in the column, not just the page returned.
+    pub total: i64,
+    pub tasks: Vec<Task>,
+}
+
+/// Moves a task on the board: into another column when `status` differs
+/// from its current one, and to just after `after_id` or just before
+/// `before_id` within the column. With neither, the task goes to the top.
+#[derive(Deserialize, Debug, Clone)]
+pub struct MoveTaskRequest {
+    pub status: Option<TaskStatus>,
+    /// Required to move a task out of Done or Cancelled.
+    pub reason: Option<String>,
+    pub after_id: Option<i32>,
+    pub before_id: Option<i32>,
+}
+
+impl BoardQuery {
+    /// The `(limit, offset)` applied to each column.
+    pub fn page(&self) -> Result<(usize, usize), String> {
+        let limit = self.limit.unwrap_or(DEFAULT_BOARD_COLUMN_SIZE);
+        if limit == 0 || limit > MAX_BOARD_COLUMN_SIZE {
+            return Err(format!(
+                "limit must be between 1 and {}",
+                MAX_BOARD_COLUMN_SIZE
+            ));
+        }
+
+        Ok((limit, self.offset.unwrap_or(0)))
+    }
+
+    /// The board's filters as a task list query.
+    pub fn task_query(&self) -> TaskQuery {
+        TaskQuery {
+            limit: None,
+            offset: None,
+            id: None,
+            title: self.title.clone(),
+            status: None,
+            priority_level: self.priority_level,
+            assigned_to: self.assigned_to,
+            store_id: self.store_id,
+            parent_id: self.parent_id,
+            overdue: self.overdue,
+            due_before: self.due_before,
+            due_after: self.due_after,
+            tags: self.tags.clone(),
+            tag_match: self.tag_match,
+            sort: None,
+            sort_by: None,
+            sort_direction: None,
+            cursor: None,
+            include_total: None,
+            include_deleted: None,
+        }
+    }
+}
+
+impl MoveTaskRequest {
+    pub fn validate(&self, id: i32) -> Result<(), String> {
+        if self.after_id.is_some() && self.before_id.is_some() {
+            return Err("Give after_id or before_id, not both".to_string());
+        }
+
+        if self.after_id == Some(id) || self.before_id == Some(id) {
+            return Err("A task cannot be placed next to itself".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+impl LaneKey {
+    /// Lanes run assignees by id and priorities from High down, with the
+    /// unassigned or unprioritized lane last.
+    fn rank(&self) -> (bool, i32) {
+        match self {
+            LaneKey::All => (false, 0),
+            LaneKey::Assignee(employee_id) => (employee_id.is_none(), employee_id.unwrap_or(0)),
+            LaneKey::Priority(priority) => (
+                priority.is_none(),
+                match priority {
+                    Some(PriorityLevel::High) => 0,
+                    Some(PriorityLevel::Medium) => 1,
+                    Some(PriorityLevel::Low) | None => 2,
+                },
+            ),
+        }
+    }
+}
+
+impl Board {
+    /// Lays out `columns` for every lane that has tasks. `totals` counts
+    /// the tasks in each lane and column; `cards` are the tasks to show, in
+    /// column order.
+    pub fn build(
+        swimlane: Option<Swimlane>,
+        columns: &[TaskStatus],
+        totals: Vec<(LaneKey, TaskStatus, i64)>,
+        cards: Vec<(LaneKey, Task)>,
+    ) -> Self {
+        let mut keys: Vec<LaneKey> = Vec::new();
+        for (key, _, _) in &totals {
+            if !keys.contains(key) {
+                keys.push(*key);
+            }
+        }
+        if keys.is_empty() && swimlane.is_none() {
+            keys.push(LaneKey::All);
+        }
+        keys.sort_by_key(LaneKey::rank);
+
+        let mut lanes: Vec<BoardLane> = keys
+            .into_iter()
+            .map(|key| BoardLane {
+                key,
+                columns: columns
+                    .iter()
+                    .map(|status| BoardColumn {
+                        status: *status,
+                        total: totals
+                            .iter()
+                            .find(|(k, s, _)| *k == key && s == status)
+                            .map_or(0, |(_, _, total)| *total),
+                        tasks: Vec::new(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        for (key, task) in cards {
+            let status = task.status.unwrap_or(TaskStatus::ToDo);
+            if let Some(column) = lanes
+                .iter_mut()
+                .find(|lane| lane.key == key)
+                .and_then(|lane| lane.columns.iter_mut().find(|c| c.status == status))
+            {
+                column.tasks.push(task);
+            }
+        }
+
+        Self { swimlane, lanes }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: i32, status: TaskStatus, assigned_to: Option<i32>) -> Task {
+        Task::new(
+            id,
+            format!("Task {}", id),
+            None,
+            None,
+            Some(status),
+            assigned_to,
+        )
+    }
+
+    #[test]
+    fn test_board_groups_cards_into_lanes_and_columns() {
+        let totals = vec![
+            (LaneKey::Assignee(None), TaskStatus::ToDo, 1),
+            (LaneKey::Assignee(Some(7)), TaskStatus::ToDo, 3),
+            (LaneKey::Assignee(Some(7)), TaskStatus::Done, 1),
+            (LaneKey::Assignee(Some(2)), TaskStatus::InProgress, 1),
+        ];
+        let cards = vec![
+            (LaneKey::Assignee(None), task(1, TaskStatus::ToDo, None)),
+            (
+                LaneKey::Assignee(Some(7)),
+                task(2, TaskStatus::ToDo, Some(7)),
+            ),
+            (
+                LaneKey::Assignee(Some(7)),
+                task(3, TaskStatus::ToDo, Some(7)),
+            ),
+            (
+                LaneKey::Assignee(Some(7)),
+                task(4, TaskStatus::Done, Some(7)),
+            ),
+            (
+                LaneKey::Assignee(Some(2)),
+                task(5, TaskStatus::InProgress, Some(2)),
+            ),
+        ];
+
+        let board = Board::build(
+            Some(Swimlane::Assignee),
+            &[TaskStatus::ToDo, TaskStatus::InProgress, TaskStatus::Done],
+            totals,
+            cards,
+        );
+
+        let keys: Vec<LaneKey> = board.lanes.iter().map(|lane| lane.key).collect();
+        assert_eq!(
+            keys,
+            [
+                LaneKey::Assignee(Some(2)),
+                LaneKey::Assignee(Some(7)),
+                LaneKey::Assignee(None)
+            ]
+        );
+        let to_do = &board.lanes[1].columns[0];
+        assert_eq!((to_do.total, to_do.tasks.len()), (3, 2));
+        assert_eq!(board.lanes[1].columns[1].total, 0);
+        assert_eq!(board.lanes[1].columns[2].tasks[0].id, 4);
+    }
+
+    #[test]
+    fn test_empty_board_still_has_its_columns() {
+        let board = Board::build(None, &TaskStatus::ALL, Vec::new(), Vec::new());
+
+        assert_eq!(board.lanes.len(), 1);
+        assert_eq!(board.lanes[0].columns.len(), TaskStatus::ALL.len());
+    }
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::tag::normalize_tags;
+use crate::models::task::{Task, TaskQuery, TaskStatus, TransitionTaskRequest};
+
+/// The most tasks one bulk request may touch.
+pub const MAX_BULK_TASKS: usize = 500;
+
+/// One action applied to many tasks, chosen either by `ids` or by a `filter`
+/// taking the same fields as `GET /tasks`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct BulkTaskRequest {
+    #[serde(flatten)]
+    pub action: BulkAction,
+    pub ids: Option<Vec<i32>>,
+    pub filter: Option<TaskQuery>,
+    /// Runs every item and reports the outcome without keeping any change.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum BulkAction {
+    UpdateStatus {
+        status: TaskStatus,
+        reason: Option<String>,
+    },
+    /// Assigns every task to `employee_id`, or unassigns them when it is null.
+    Assign {
+        employee_id: Option<i32>,
+    },
+    /// Adds and removes tags, keeping each task's other tags.
+    Tag {
+        #[serde(default)]
+        add: Vec<String>,
+        #[serde(default)]
+        remove: Vec<String>,
+    },
+    Delete,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BulkItemResult {
+    pub id: i32,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The task as the action left it; not given for deletes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<Task>,
+}
+
+/// Bulk requests are all or nothing: `applied` is false when any item failed
+/// or on a dry run, and then no task was changed.
+#[derive(Serialize, Debug)]
+pub struct BulkTaskReport {
+    pub dry_run: bool,
+    pub applied: bool,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BulkItemResult>,
+}
+
+impl BulkTaskRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        match (&self.ids, &self.filter) {
+            (Some(_), Some(_)) | (None, None) => {
+                return Err("Give either ids or a filter".to_string());
+            }
+            (Some(ids), None) if ids.is_empty() => {
+                return Err("ids cannot be empty".to_string());
+            }
+            (Some(ids), None) if ids.len() > MAX_BULK_TASKS => {
+                return Err(format!(
+                    "A bulk request cannot touch more than {} tasks",
+                    MAX_BULK_TASKS
+                ));
+            }
+            _ => {}
+        }
+
+        match &self.action {
+            BulkAction::UpdateStatus { status, reason } => TransitionTaskRequest {
+                status: *status,
+                reason: reason.clone(),
+            }
+            .validate(),
+            BulkAction::Tag { add, remove } if add.is_empty() && remove.is_empty() => {
+                Err("Give tags to add or remove".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl BulkAction {
+    /// Normalizes the tags of a `Tag` action, as tags are stored.
+    pub fn normalized(self) -> Result<Self, String> {
+        match self {
+            BulkAction::Tag { add, remove } => Ok(BulkAction::Tag {
+                add: normalize_tags(add.iter().map(String::as_str))?,
+                remove: normalize_tags(remove.iter().map(String::as_str))?,
+            }),
+            action => Ok(action),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_bulk_request_takes_ids_or_a_filter() {
+        let request: BulkTaskRequest = serde_json::from_value(json!({
+            "action": "update_status",
+            "status": "Done",
+            "filter": {"store_id": 3, "tags": "weekly"},
+            "dry_run": true
+        }))
+        .unwrap();
+
+        assert!(request.validate().is_ok());
+        assert!(request.dry_run);
+        assert_eq!(request.filter.unwrap().store_id, Some(3));
+        assert_eq!(
+            request.action,
+            BulkAction::UpdateStatus {
+                status: TaskStatus::Done,
+                reason: None
+            }
+        );
+
+        for invalid in [
+            json!({"action": "delete"}),
+            json!({"action": "delete", "ids": []}),
+            json!({"action": "delete", "ids": [1], "filter": {}}),
+            json!({"action": "tag", "ids": [1]}),
+        ] {
+            let request: BulkTaskRequest = serde_json::from_value(invalid).unwrap();
+            assert!(request.validate().is_err());
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::models::task::{Task, TaskStatus};
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AddDependencyRequest {
+    pub depends_on: i32,
+}
+
+/// The tasks that must be finished before a task can start, and the tasks
+/// waiting on it.
+#[derive(Serialize, Debug)]
+pub struct TaskDependencies {
+    pub blocked_by: Vec<Task>,
+    pub blocking: Vec<Task>,
+}
+
+/// Completion of the leaf subtasks under a task. Cancelled subtasks do not
+/// count towards either number.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct TaskProgress {
+    pub completed: usize,
+    pub total: usize,
+    pub percent: u8,
+}
+
+impl TaskProgress {
+    fn new(completed: usize, total: usize) -> Self {
+        let percent = (completed * 100).checked_div(total).unwrap_or(0) as u8;
+
+        Self {
+            completed,
+            total,
+            percent,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct TaskNode {
+    #[serde(flatten)]
+    pub task: Task,
+    /// Open tasks this task depends on.
+    pub blocked_by: Vec<i32>,
+    pub subtasks: Vec<TaskNode>,
+}
+
+impl TaskNode {
+    /// Assembles `root` and its descendants into a tree, setting `progress`
+    /// on every task that has subtasks. `blockers` pairs a task id with an
+    /// open task it depends on.
+    pub fn build(root: Task, descendants: Vec<Task>, blockers: &[(i32, i32)]) -> Self {
+        let mut children: HashMap<i32, Vec<Task>> = HashMap::new();
+        for task in descendants {
+            if let Some(parent_id) = task.parent_id {
+                children.entry(parent_id).or_default().push(task);
+            }
+        }
+
+        Self::assemble(root, &mut children, blockers)
+    }
+
+    fn assemble(
+        mut task: Task,
+        children: &mut HashMap<i32, Vec<Task>>,
+        blockers: &[(i32, i32)],
+    ) -> Self {
+        let subtasks: Vec<TaskNode> = children
+            .remove(&task.id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|child| Self::assemble(child, children, blockers))
+            .collect();
+
+        if !subtasks.is_empty() {
+            let (completed, total) = subtasks.iter().fold((0, 0), |(completed, total), node| {
+                let (done, count) = node.leaf_counts();
+                (completed + done, total + count)
+            });
+            task.progress = Some(TaskProgress::new(completed, total));
+        }
+
+        let blocked_by = blockers
+            .iter()
+            .filter(|(task_id, _)| *task_id == task.id)
+            .map(|(_, depends_on_id)| *depends_on_id)
+            .collect();
+
+        Self {
+            task,
+            blocked_by,
+            subtasks,
+        }
+    }
+
+    /// (completed, total) leaves under this node, counting the node itself
+    /// when it has no subtasks.
+    fn leaf_counts(&self) -> (usize, usize) {
+        match self.task.progress {
+            Some(progress) => (progress.completed, progress.total),
+            None => match self.task.status {
+                Some(TaskStatus::Cancelled) => (0, 0),
+                Some(TaskStatus::Done) => (1, 1),
+                _ => (0, 1),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: i32, parent_id: Option<i32>, status: TaskStatus) -> Task {
+        let mut task = Task::new(id, format!("Task {}", id), None, None, Some(status), None);
+        task.parent_id = parent_id;
+        task
+    }
+
+    #[test]
+    fn test_build_rolls_progress_up_from_leaves() {
+        let root = task(1, None, TaskStatus::InProgress);
+        let descendants = vec![
+            task(2, Some(1), TaskStatus::Done),
+            task(3, Some(1), TaskStatus::InProgress),
+            task(4, Some(3), TaskStatus::Done),
+            task(5, Some(3), TaskStatus::ToDo),
+            task(6, Some(3), TaskStatus::Cancelled),
+        ];
+
+        let tree = TaskNode::build(root, descendants, &[(5, 2)]);
+
+        assert_eq!(tree.task.progress, Some(TaskProgress::new(2, 3)));
+        assert_eq!(tree.task.progress.unwrap().percent, 66);
+        assert_eq!(tree.subtasks.len(), 2);
+
+        let middle = &tree.subtasks[1];
+        assert_eq!(middle.task.progress, Some(TaskProgress::new(1, 2)));
+        assert_eq!(middle.subtasks[1].blocked_by, [2]);
+        assert!(tree.subtasks[0].task.progress.is_none());
+    }
+
+    #[test]
+    fn test_build_without_subtasks_has_no_progress() {
+        let tree = TaskNode::build(task(1, None, TaskStatus::ToDo), vec![], &[]);
+
+        assert!(tree.task.progress.is_none());
+        assert!(tree.subtasks.is_empty());
+    }
+}
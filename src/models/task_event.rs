@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+use sqlx::types::Json;
+use std::collections::BTreeMap;
+
+use crate::models::task::Task;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TaskEventType {
+    Created,
+    Updated,
+    StatusChanged,
+    Assigned,
+    Deleted,
+    Restored,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FieldChange {
+    pub from: Value,
+    pub to: Value,
+}
+
+pub type TaskChanges = BTreeMap<String, FieldChange>;
+
+#[derive(Serialize, Clone, Debug, FromRow)]
+pub struct TaskEvent {
+    pub id: i64,
+    pub task_id: i32,
+    pub event_type: TaskEventType,
+    pub actor: String,
+    pub changes: Json<TaskChanges>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Fields whose changes are not worth recording; `updated_at` moves on every
+/// write and the event carries its own timestamp.
+const UNTRACKED_FIELDS: &[&str] = &["id", "created_at", "updated_at"];
+
+/// Field-level differences between two versions of a task. A missing
+/// `before` (task creation) reports every set field as changed from null.
+pub fn diff_tasks(before: Option<&Task>, after: &Task) -> TaskChanges {
+    let before = before
+        .and_then(|task| serde_json::to_value(task).ok())
+        .unwrap_or(Value::Null);
+    let after = serde_json::to_value(after).unwrap_or(Value::Null);
+
+    let mut changes = TaskChanges::new();
+    if let Value::Object(fields) = after {
+        for (field, to) in fields {
+            if UNTRACKED_FIELDS.contains(&field.as_str()) {
+                continue;
+            }
+
+            let from = before.get(&field).cloned().unwrap_or(Value::Null);
+            if from != to {
+                changes.insert(field, FieldChange { from, to });
+            }
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::task::{PriorityLevel, TaskStatus};
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_tasks_reports_only_changed_fields() {
+        let before = Task::new(1, "Count stock".to_string(), None, None, None, None);
+        let mut after = before.clone();
+        after.title = "Count stock in aisle 3".to_string();
+        after.status = Some(TaskStatus::InProgress);
+        after.updated_at = Utc::now();
+
+        let changes = diff_tasks(Some(&before), &after);
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(
+            changes["title"],
+            FieldChange {
+                from: json!("Count stock"),
+                to: json!("Count stock in aisle 3"),
+            }
+        );
+        assert_eq!(changes["status"].from, json!("ToDo"));
+        assert_eq!(changes["status"].to, json!("InProgress"));
+    }
+
+    #[test]
+    fn test_diff_tasks_on_create_lists_set_fields() {
+        let task = Task::new(
+            1,
+            "Count stock".to_string(),
+            None,
+            Some(PriorityLevel::High),
+            None,
+            Some(4),
+        );
+
+        let changes = diff_tasks(None, &task);
+
+        assert_eq!(changes["title"].from, Value::Null);
+        assert_eq!(changes["assigned_to"].to, json!(4));
+        assert!(!changes.contains_key("description"));
+        assert!(!changes.contains_key("created_at"));
+    }
+
+    #[test]
+    fn test_diff_tasks_identical_is_empty() {
+        let task = Task::new(1, "Count stock".to_string(), None, None, None, None);
+
+        assert!(diff_tasks(Some(&task), &task.clone()).is_empty());
+    }
+}
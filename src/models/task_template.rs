@@ -0,0 +1,514 @@
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use sqlx::types::Json;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::models::task::PriorityLevel;
+
+/// Upper bound on how far a single preview or materialization may reach.
+pub const MAX_OCCURRENCE_WINDOW_DAYS: i64 = 366;
+
+#[derive(Serialize, Deserialize, Clone, Debug, FromRow)]
+pub struct TaskTemplate {
+    pub id: i32,
+    pub title: String,
+    pub description: Option<String>,
+    pub priority_level: Option<PriorityLevel>,
+    pub assigned_to: Option<i32>,
+    /// Stores that get their own task for every occurrence. When empty, each
+    /// occurrence creates a single task with no store.
+    pub store_ids: Json<Vec<i32>>,
+    pub rrule: String,
+    pub starts_on: NaiveDate,
+    pub due_in_hours: Option<i64>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CreateTaskTemplateRequest {
+    pub title: String,
+    pub description: Option<String>,
+    pub priority_level: Option<PriorityLevel>,
+    pub assigned_to: Option<i32>,
+    pub store_ids: Option<Vec<i32>>,
+    pub rrule: String,
+    pub starts_on: Option<NaiveDate>,
+    pub due_in_hours: Option<i64>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct UpdateTaskTemplateRequest {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub priority_level: Option<PriorityLevel>,
+    pub assigned_to: Option<i32>,
+    pub store_ids: Option<Vec<i32>>,
+    pub rrule: Option<String>,
+    pub starts_on: Option<NaiveDate>,
+    pub due_in_hours: Option<i64>,
+    pub active: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct OccurrenceQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct MaterializeReport {
+    pub templates: usize,
+    pub created_task_ids: Vec<i32>,
+    pub existing: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// A subset of the iCalendar RRULE (RFC 5545): `FREQ` of DAILY, WEEKLY or
+/// MONTHLY, with `INTERVAL`, `BYDAY` (weekly), `BYMONTHDAY` (monthly) and an
+/// end given by either `UNTIL` or `COUNT`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub by_weekday: Vec<Weekday>,
+    pub by_month_day: Vec<u32>,
+    pub until: Option<NaiveDate>,
+    pub count: Option<u32>,
+}
+
+impl FromStr for Recurrence {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        let input = input.strip_prefix("RRULE:").unwrap_or(input);
+
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut by_weekday = Vec::new();
+        let mut by_month_day = Vec::new();
+        let mut until = None;
+        let mut count = None;
+
+        for part in input.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid RRULE part: {}", part))?;
+
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        other => return Err(format!("Unsupported FREQ: {}", other)),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .parse()
+                        .ok()
+                        .filter(|interval| (1..=366).contains(interval))
+                        .ok_or_else(|| "INTERVAL must be between 1 and 366".to_string())?
+                }
+                "BYDAY" => {
+                    for day in value.split(',') {
+                        let weekday = parse_weekday(day)?;
+                        if !by_weekday.contains(&weekday) {
+                            by_weekday.push(weekday);
+                        }
+                    }
+                }
+                "BYMONTHDAY" => {
+                    for day in value.split(',') {
+                        let day = day
+                            .parse()
+                            .ok()
+                            .filter(|day| (1..=31).contains(day))
+                            .ok_or_else(|| "BYMONTHDAY must be between 1 and 31".to_string())?;
+                        if !by_month_day.contains(&day) {
+                            by_month_day.push(day);
+                        }
+                    }
+                }
+                "UNTIL" => {
+                    let date = value.get(..8).unwrap_or(value);
+                    until = Some(
+                        NaiveDate::parse_from_str(date, "%Y%m%d")
+                            .map_err(|_| "UNTIL must be a date like 20261231".to_string())?,
+                    )
+                }
+                "COUNT" => {
+                    count = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|count| *count >= 1)
+                            .ok_or_else(|| "COUNT must be a positive number".to_string())?,
+                    )
+                }
+                other => return Err(format!("Unsupported RRULE part: {}", other)),
+            }
+        }
+
+        let frequency = frequency.ok_or_else(|| "RRULE must include FREQ".to_string())?;
+
+        if !by_weekday.is_empty() && frequency != Frequency::Weekly {
+            return Err("BYDAY is only supported with FREQ=WEEKLY".to_string());
+        }
+        if !by_month_day.is_empty() && frequency != Frequency::Monthly {
+            return Err("BYMONTHDAY is only supported with FREQ=MONTHLY".to_string());
+        }
+        if until.is_some() && count.is_some() {
+            return Err("RRULE cannot have both UNTIL and COUNT".to_string());
+        }
+
+        by_weekday.sort_by_key(|day| day.num_days_from_monday());
+        by_month_day.sort_unstable();
+
+        Ok(Self {
+            frequency,
+            interval,
+            by_weekday,
+            by_month_day,
+            until,
+            count,
+        })
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+        };
+        write!(f, "FREQ={}", frequency)?;
+
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_weekday.is_empty() {
+            let days: Vec<&str> = self.by_weekday.iter().map(weekday_code).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self.by_month_day.iter().map(u32::to_string).collect();
+            write!(f, ";BYMONTHDAY={}", days.join(","))?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%d"))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Recurrence {
+    /// Dates between `from` and `to` (inclusive) on which the rule fires,
+    /// for a series starting on `starts_on`. `COUNT` is counted from the
+    /// start of the series, not from `from`.
+    pub fn occurrences(
+        &self,
+        starts_on: NaiveDate,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Vec<NaiveDate> {
+        let last = self.until.map_or(to, |until| until.min(to));
+        let mut dates = Vec::new();
+        let mut emitted = 0;
+
+        for period in 0.. {
+            let Some((period_start, candidates)) = self.period(starts_on, period) else {
+                break;
+            };
+            if period_start > last {
+                break;
+            }
+
+            for date in candidates {
+                if date < starts_on {
+                    continue;
+                }
+                if date > last || self.count.is_some_and(|count| emitted >= count) {
+                    return dates;
+                }
+
+                emitted += 1;
+                if date >= from {
+                    dates.push(date);
+                }
+            }
+        }
+
+        dates
+    }
+
+    /// The first day of the `index`-th period of the series and the candidate
+    /// dates inside it, in order.
+    fn period(&self, starts_on: NaiveDate, index: u32) -> Option<(NaiveDate, Vec<NaiveDate>)> {
+        let step = self.interval.checked_mul(index)?;
+
+        match self.frequency {
+            Frequency::Daily => {
+                let date = starts_on.checked_add_days(Days::new(step as u64))?;
+                Some((date, vec![date]))
+            }
+            Frequency::Weekly => {
+                let monday = starts_on
+                    .checked_sub_days(Days::new(starts_on.weekday().num_days_from_monday() as u64))?
+                    .checked_add_days(Days::new(7 * step as u64))?;
+                let weekdays = if self.by_weekday.is_empty() {
+                    vec![starts_on.weekday()]
+                } else {
+                    self.by_weekday.clone()
+                };
+                let dates = weekdays
+                    .iter()
+                    .filter_map(|day| {
+                        monday.checked_add_days(Days::new(day.num_days_from_monday() as u64))
+                    })
+                    .collect();
+                Some((monday, dates))
+            }
+            Frequency::Monthly => {
+                let first = starts_on
+                    .with_day(1)?
+                    .checked_add_months(Months::new(step))?;
+                let days = if self.by_month_day.is_empty() {
+                    vec![starts_on.day()]
+                } else {
+                    self.by_month_day.clone()
+                };
+                let dates = days.iter().filter_map(|day| first.with_day(*day)).collect();
+                Some((first, dates))
+            }
+        }
+    }
+}
+
+fn parse_weekday(code: &str) -> Result<Weekday, String> {
+    match code.trim().to_ascii_uppercase().as_str() {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        other => Err(format!("Invalid BYDAY value: {}", other)),
+    }
+}
+
+fn weekday_code(day: &Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn validate_fields(title: Option<&str>, due_in_hours: Option<i64>) -> Result<(), String> {
+    if let Some(title) = title
+        && title.trim().is_empty()
+    {
+        return Err("Title cannot be empty".to_string());
+    }
+
+    if let Some(hours) = due_in_hours
+        && hours <= 0
+    {
+        return Err("due_in_hours must be positive".to_string());
+    }
+
+    Ok(())
+}
+
+impl CreateTaskTemplateRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_fields(Some(&self.title), self.due_in_hours)?;
+        self.rrule.parse::<Recurrence>()?;
+
+        Ok(())
+    }
+}
+
+impl UpdateTaskTemplateRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_fields(self.title.as_deref(), self.due_in_hours)?;
+        if let Some(rrule) = &self.rrule {
+            rrule.parse::<Recurrence>()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TaskTemplate {
+    pub fn recurrence(&self) -> Result<Recurrence, String> {
+        self.rrule.parse()
+    }
+
+    /// The stores to create a task for on each occurrence; `None` stands for
+    /// a single task that belongs to no store.
+    pub fn target_stores(&self) -> Vec<Option<i32>> {
+        if self.store_ids.is_empty() {
+            vec![None]
+        } else {
+            self.store_ids.iter().copied().map(Some).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn dates(values: &[&str]) -> Vec<NaiveDate> {
+        values.iter().map(|value| date(value)).collect()
+    }
+
+    #[test]
+    fn test_parse_and_display_round_trip() {
+        let rule: Recurrence = "RRULE:FREQ=weekly;INTERVAL=2;BYDAY=TH,MO;UNTIL=20261231"
+            .parse()
+            .unwrap();
+
+        assert_eq!(rule.frequency, Frequency::Weekly);
+        assert_eq!(rule.by_weekday, [Weekday::Mon, Weekday::Thu]);
+        assert_eq!(
+            rule.to_string(),
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH;UNTIL=20261231"
+        );
+        assert_eq!(rule.to_string().parse::<Recurrence>().unwrap(), rule);
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_rules() {
+        assert!("INTERVAL=2".parse::<Recurrence>().is_err());
+        assert!("FREQ=YEARLY".parse::<Recurrence>().is_err());
+        assert!("FREQ=DAILY;BYDAY=MO".parse::<Recurrence>().is_err());
+        assert!("FREQ=MONTHLY;BYMONTHDAY=32".parse::<Recurrence>().is_err());
+        assert!("FREQ=DAILY;INTERVAL=0".parse::<Recurrence>().is_err());
+        assert!(
+            "FREQ=DAILY;COUNT=3;UNTIL=20260101"
+                .parse::<Recurrence>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_daily_with_interval_and_count() {
+        let rule: Recurrence = "FREQ=DAILY;INTERVAL=3;COUNT=3".parse().unwrap();
+
+        assert_eq!(
+            rule.occurrences(date("2026-01-01"), date("2026-01-01"), date("2026-12-31")),
+            dates(&["2026-01-01", "2026-01-04", "2026-01-07"])
+        );
+        assert_eq!(
+            rule.occurrences(date("2026-01-01"), date("2026-01-05"), date("2026-12-31")),
+            dates(&["2026-01-07"])
+        );
+    }
+
+    #[test]
+    fn test_weekly_on_weekdays_skips_days_before_start() {
+        // 2026-01-07 is a Wednesday.
+        let rule: Recurrence = "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR".parse().unwrap();
+
+        assert_eq!(
+            rule.occurrences(date("2026-01-07"), date("2026-01-01"), date("2026-01-31")),
+            dates(&["2026-01-09", "2026-01-19", "2026-01-23"])
+        );
+    }
+
+    #[test]
+    fn test_weekly_defaults_to_start_weekday() {
+        let rule: Recurrence = "FREQ=WEEKLY;UNTIL=20260121".parse().unwrap();
+
+        assert_eq!(
+            rule.occurrences(date("2026-01-07"), date("2026-01-01"), date("2026-12-31")),
+            dates(&["2026-01-07", "2026-01-14", "2026-01-21"])
+        );
+    }
+
+    #[test]
+    fn test_monthly_skips_months_without_the_day() {
+        let rule: Recurrence = "FREQ=MONTHLY;BYMONTHDAY=31;COUNT=3".parse().unwrap();
+
+        assert_eq!(
+            rule.occurrences(date("2026-01-01"), date("2026-01-01"), date("2026-12-31")),
+            dates(&["2026-01-31", "2026-03-31", "2026-05-31"])
+        );
+    }
+
+    #[test]
+    fn test_target_stores() {
+        let mut template = TaskTemplate {
+            id: 1,
+            title: "Weekly cleaning".to_string(),
+            description: None,
+            priority_level: None,
+            assigned_to: None,
+            store_ids: Json(vec![]),
+            rrule: "FREQ=WEEKLY".to_string(),
+            starts_on: date("2026-01-05"),
+            due_in_hours: None,
+            active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        assert_eq!(template.target_stores(), [None]);
+
+        template.store_ids = Json(vec![1, 3]);
+        assert_eq!(template.target_stores(), [Some(1), Some(3)]);
+    }
+
+    #[test]
+    fn test_create_request_validate() {
+        let request = CreateTaskTemplateRequest {
+            title: "Monthly inventory".to_string(),
+            description: None,
+            priority_level: None,
+            assigned_to: None,
+            store_ids: Some(vec![1]),
+            rrule: "FREQ=MONTHLY;BYMONTHDAY=1".to_string(),
+            starts_on: None,
+            due_in_hours: Some(48),
+        };
+        assert!(request.validate().is_ok());
+
+        let mut invalid = request.clone();
+        invalid.rrule = "FREQ=HOURLY".to_string();
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = request;
+        invalid.due_in_hours = Some(0);
+        assert_eq!(
+            invalid.validate().unwrap_err(),
+            "due_in_hours must be positive"
+        );
+    }
+}
@@ -0,0 +1,327 @@
+use crate::database::{self, DbExecutor, DbPool, DbTransaction};
+use crate::errors::ApiError;
+use crate::models::assignment::*;
+use chrono::Utc;
+
+/// Open tasks assigned to `e.id`, for ordering candidates by load.
+const OPEN_TASKS: &str = "(SELECT COUNT(*) FROM tasks t WHERE t.assigned_to = e.id AND t.deleted_at IS NULL AND t.status NOT IN ('Done', 'Cancelled'))";
+
+#[derive(Clone)]
+pub struct AssignmentRepository {
+    pool: DbPool,
+}
+
+impl AssignmentRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find_all(&self) -> Result<Vec<StoreAssignmentSettings>, ApiError> {
+        database::query_as(
+            r#"
+            SELECT store_id, strategy, last_assigned_employee_id, updated_at
+            FROM store_assignment_settings
+            ORDER BY store_id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(ApiError::from)
+    }
+
+    pub async fn find_by_store(&self, store_id: i32) -> Result<StoreAssignmentSettings, ApiError> {
+        Self::settings(&self.pool, store_id)
+            .await?
+            .ok_or_else(|| ApiError::NotFound("Store has no assignment strategy".to_string()))
+    }
+
+    /// Sets the store's strategy. Changing strategy restarts the round-robin
+    /// rotation.
+    pub async fn save(
+        &self,
+        store_id: i32,
+        strategy: AssignmentStrategy,
+    ) -> Result<StoreAssignmentSettings, ApiError> {
+        database::query(
+            r#"
+            INSERT INTO store_assignment_settings (store_id, strategy, updated_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT (store_id) DO UPDATE SET
+                strategy = excluded.strategy,
+                last_assigned_employee_id = CASE
+                    WHEN store_assignment_settings.strategy = excluded.strategy
+                    THEN store_assignment_settings.last_assigned_employee_id
+                END,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(store_id)
+        .bind(strategy)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(ApiError::from)?;
+
+        self.find_by_store(store_id).await
+    }
+
+    pub async fn delete(&self, store_id: i32) -> Result<(), ApiError> {
+        let result = database::query("DELETE FROM store_assignment_settings WHERE store_id = ?")
+            .bind(store_id)
+            .execute(&self.pool)
+            .await
+            .map_err(ApiError::from)?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApiError::NotFound(
+                "Store has no assignment strategy".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Picks an assignee using the store's strategy, advancing the
+    /// round-robin rotation. `None` when the store has no strategy or no
+    /// active employees.
+    pub(crate) async fn pick(
+        conn: &mut DbTransaction,
+        store_id: i32,
+    ) -> Result<Option<i32>, ApiError> {
+        let Some(settings) = Self::settings(&mut *conn, store_id).await? else {
+            return Ok(None);
+        };
+
+        let sql = match settings.strategy {
+            AssignmentStrategy::RoundRobin => r#"
+                SELECT e.id FROM employees e
+                WHERE e.store_id = ? AND e.deactivated_at IS NULL
+                ORDER BY CASE WHEN e.id > ? THEN 0 ELSE 1 END, e.id
+                LIMIT 1
+                "#
+            .to_string(),
+            AssignmentStrategy::LeastOpen => format!(
+                r#"
+                SELECT e.id FROM employees e
+                WHERE e.store_id = ? AND e.deactivated_at IS NULL
+                ORDER BY {}, e.id
+                LIMIT 1
+                "#,
+                OPEN_TASKS
+            ),
+            AssignmentStrategy::ManagerHierarchy => format!(
+                r#"
+                SELECT e.id FROM employees e
+                WHERE e.store_id = ? AND e.deactivated_at IS NULL
+                    AND NOT EXISTS (
+                        SELECT 1 FROM employees m
+                        WHERE m.id = e.manager_id AND m.store_id = e.store_id AND m.deactivated_at IS NULL
+                    )
+                ORDER BY {}, e.id
+                LIMIT 1
+                "#,
+                OPEN_TASKS
+            ),
+        };
+
+        let mut query = database::query_scalar::<i32>(&sql).bind(store_id);
+        if settings.strategy == AssignmentStrategy::RoundRobin {
+            query = query.bind(settings.last_assigned_employee_id.unwrap_or(0));
+        }
+        let employee_id = query
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(ApiError::from)?;
+
+        if settings.strategy == AssignmentStrategy::RoundRobin && employee_id.is_some() {
+            database::query(
+                "UPDATE store_assignment_settings SET last_assigned_employee_id = ? WHERE store_id = ?",
+            )
+            .bind(employee_id)
+            .bind(store_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(ApiError::from)?;
+        }
+
+        Ok(employee_id)
+    }
+
+    async fn settings<'c>(
+        executor: impl Into<DbExecutor<'c>>,
+        store_id: i32,
+    ) -> Result<Option<StoreAssignmentSettings>, ApiError> {
+        database::query_as(
+            r#"
+            SELECT store_id, strategy, last_assigned_employee_id, updated_at
+            FROM store_assignment_settings
+            WHERE store_id = ?
+            "#,
+        )
+        .bind(store_id)
+        .fetch_optional(executor)
+        .await
+        .map_err(ApiError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::run_migrations;
+    use crate::models::auth::Actor;
+    use crate::models::task::CreateTaskRequest;
+    use crate::repositories::TaskRepository;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn repository() -> AssignmentRepository {
+        let pool: DbPool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+            .into();
+        run_migrations(&pool).await.unwrap();
+
+        // 1 manages 2 and 3; 4 is deactivated; 5 works in another store.
+        for (external_id, store_id, manager_id, deactivated) in [
+            ("EMP001", 1, None, false),
+            ("EMP002", 1, Some(1), false),
+            ("EMP003", 1, Some(1), false),
+            ("EMP004", 1, None, true),
+            ("EMP005", 2, None, false),
+        ] {
+            database::query(
+                "INSERT INTO employees (external_id, first_name, last_name, store_id, manager_id, deactivated_at) VALUES (?, 'Test', 'Employee', ?, ?, ?)",
+            )
+            .bind(external_id)
+            .bind(store_id)
+            .bind(manager_id)
+            .bind(deactivated.then(Utc::now))
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        AssignmentRepository::new(pool)
+    }
+
+    async fn open_task(repository: &AssignmentRepository, assigned_to: i32) {
+        let now = Utc::now();
+        database::query(
+            "INSERT INTO tasks (title, status, assigned_to, created_at, updated_at) VALUES ('Open', 'ToDo', ?, ?, ?)",
+        )
+        .bind(assigned_to)
+        .bind(now)
+        .bind(now)
+        .execute(&repository.pool)
+        .await
+        .unwrap();
+    }
+
+    async fn next_assignee(
+        repository: &AssignmentRepository,
+        store_id: i32,
+    ) -> Result<Option<i32>, ApiError> {
+        let mut tx = repository.pool.begin().await?;
+        let employee_id = AssignmentRepository::pick(&mut tx, store_id).await?;
+        tx.commit().await?;
+
+        Ok(employee_id)
+    }
+
+    #[tokio::test]
+    async fn test_store_without_strategy_picks_nobody() {
+        let repository = repository().await;
+
+        assert_eq!(next_assignee(&repository, 1).await.unwrap(), None);
+        assert!(matches!(
+            repository.find_by_store(1).await,
+            Err(ApiError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_round_robin_cycles_through_active_employees() {
+        let repository = repository().await;
+        repository
+            .save(1, AssignmentStrategy::RoundRobin)
+            .await
+            .unwrap();
+
+        let mut picks = Vec::new();
+        for _ in 0..4 {
+            picks.push(next_assignee(&repository, 1).await.unwrap().unwrap());
+        }
+
+        assert_eq!(picks, [1, 2, 3, 1]);
+        let settings = repository.find_by_store(1).await.unwrap();
+        assert_eq!(settings.last_assigned_employee_id, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_task_creation_takes_one_rotation_slot() {
+        let repository = repository().await;
+        repository
+            .save(1, AssignmentStrategy::RoundRobin)
+            .await
+            .unwrap();
+        let tasks = TaskRepository::new(repository.pool.clone());
+        let request = |parent_id: Option<i32>| CreateTaskRequest {
+            title: "Restock".to_string(),
+            description: None,
+            priority_level: None,
+            status: None,
+            assigned_to: None,
+            store_id: Some(1),
+            parent_id,
+            start_at: None,
+            due_at: None,
+        };
+        let actor = Actor::system("test");
+        let last_assigned = async || {
+            repository
+                .find_by_store(1)
+                .await
+                .unwrap()
+                .last_assigned_employee_id
+        };
+
+        let task = tasks.create(request(None), &actor).await.unwrap();
+        assert_eq!(task.assigned_to, Some(1));
+        assert_eq!(last_assigned().await, Some(1));
+
+        let failed = tasks.create(request(Some(999)), &actor).await;
+        assert!(failed.is_err());
+        assert_eq!(last_assigned().await, Some(1));
+
+        let task = tasks.create(request(None), &actor).await.unwrap();
+        assert_eq!(task.assigned_to, Some(2));
+        assert_eq!(last_assigned().await, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_least_open_and_manager_hierarchy_use_open_task_counts() {
+        let repository = repository().await;
+        open_task(&repository, 1).await;
+        open_task(&repository, 2).await;
+
+        repository
+            .save(1, AssignmentStrategy::LeastOpen)
+            .await
+            .unwrap();
+        assert_eq!(next_assignee(&repository, 1).await.unwrap(), Some(3));
+
+        repository
+            .save(1, AssignmentStrategy::ManagerHierarchy)
+            .await
+            .unwrap();
+        assert_eq!(next_assignee(&repository, 1).await.unwrap(), Some(1));
+
+        repository
+            .save(2, AssignmentStrategy::ManagerHierarchy)
+            .await
+            .unwrap();
+        assert_eq!(next_assignee(&repository, 2).await.unwrap(), Some(5));
+    }
+}
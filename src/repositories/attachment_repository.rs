@@ -0,0 +1,104 @@
+use crate::database::{self, DbPool};
+use crate::errors::ApiError;
+use crate::models::attachment::TaskAttachment;
+use chrono::Utc;
+
+const ATTACHMENT_COLUMNS: &str =
+    "id, task_id, file_name, content_type, size_bytes, sha256, uploaded_by, created_at";
+
+#[derive(Clone)]
+pub struct AttachmentRepository {
+    pool: DbPool,
+}
+
+impl AttachmentRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find_by_task(&self, task_id: i32) -> Result<Vec<TaskAttachment>, ApiError> {
+        let sql = format!(
+            "SELECT {} FROM task_attachments WHERE task_id = ? ORDER BY created_at, id",
+            ATTACHMENT_COLUMNS
+        );
+
+        database::query_as::<TaskAttachment>(&sql)
+            .bind(task_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(ApiError::from)
+    }
+
+    pub async fn find_by_id(
+        &self,
+        task_id: i32,
+        attachment_id: i32,
+    ) -> Result<TaskAttachment, ApiError> {
+        let sql = format!(
+            "SELECT {} FROM task_attachments WHERE id = ? AND task_id = ?",
+            ATTACHMENT_COLUMNS
+        );
+
+        database::query_as::<TaskAttachment>(&sql)
+            .bind(attachment_id)
+            .bind(task_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(ApiError::from)?
+            .ok_or_else(|| ApiError::NotFound("Attachment not found".to_string()))
+    }
+
+    pub async fn create(
+        &self,
+        task_id: i32,
+        file_name: &str,
+        content_type: &str,
+        size_bytes: i64,
+        sha256: &str,
+        uploaded_by: &str,
+    ) -> Result<TaskAttachment, ApiError> {
+        let id: i32 = database::query_scalar(
+            r#"
+            INSERT INTO task_attachments (task_id, file_name, content_type, size_bytes, sha256, uploaded_by, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            RETURNING id
+            "#,
+        )
+        .bind(task_id)
+        .bind(file_name)
+        .bind(content_type)
+        .bind(size_bytes)
+        .bind(sha256)
+        .bind(uploaded_by)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(ApiError::from)?;
+
+        self.find_by_id(task_id, id).await
+    }
+
+    /// Whether any attachment refers to the contents stored under `sha256`.
+    pub async fn is_referenced(&self, sha256: &str) -> Result<bool, ApiError> {
+        let id = database::query_scalar::<i32>(
+            "SELECT id FROM task_attachments WHERE sha256 = ? LIMIT 1",
+        )
+        .bind(sha256)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(ApiError::from)?;
+
+        Ok(id.is_some())
+    }
+
+    /// Removes an attachment's metadata. Its contents stay in storage.
+    pub async fn delete(&self, attachment: &TaskAttachment) -> Result<(), ApiError> {
+        database::query("DELETE FROM task_attachments WHERE id = ?")
+            .bind(attachment.id)
+            .execute(&self.pool)
+            .await
+            .map_err(ApiError::from)?;
+
+        Ok(())
+    }
+}
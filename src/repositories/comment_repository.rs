@@ -0,0 +1,398 @@
+use crate::database::{self, DbPool, DbTransaction};
+use crate::errors::ApiError;
+use crate::models::comment::*;
+use chrono::Utc;
+use std::collections::HashMap;
+
+const COMMENT_COLUMNS: &str =
+    "c.id, c.task_id, c.author, c.body, c.created_at, c.updated_at, c.edited_at, c.deleted_at";
+
+#[derive(Clone)]
+pub struct CommentRepository {
+    pool: DbPool,
+}
+
+impl CommentRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find_by_task(&self, task_id: i32) -> Result<Vec<TaskComment>, ApiError> {
+        let sql = format!(
+            r#"
+            SELECT {}
+            FROM task_comments c
+            WHERE c.task_id = ? AND c.deleted_at IS NULL
+            ORDER BY c.created_at, c.id
+            "#,
+            COMMENT_COLUMNS
+        );
+
+        let comments = database::query_as::<TaskComment>(&sql)
+            .bind(task_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(ApiError::from)?;
+
+        self.with_mentions(comments).await
+    }
+
+    pub async fn find_by_id(&self, task_id: i32, comment_id: i32) -> Result<TaskComment, ApiError> {
+        let sql = format!(
+            r#"
+            SELECT {}
+            FROM task_comments c
+            WHERE c.id = ? AND c.task_id = ? AND c.deleted_at IS NULL
+            "#,
+            COMMENT_COLUMNS
+        );
+
+        let comment = database::query_as::<TaskComment>(&sql)
+            .bind(comment_id)
+            .bind(task_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(ApiError::from)?
+            .ok_or_else(|| ApiError::NotFound("Comment not found".to_string()))?;
+
+        let mut comments = self.with_mentions(vec![comment]).await?;
+        Ok(comments.remove(0))
+    }
+
+    /// Comments on any task that mention the given employee, newest first.
+    pub async fn find_mentioning(&self, employee_id: i32) -> Result<Vec<TaskComment>, ApiError> {
+        let sql = format!(
+            r#"
+            SELECT {}
+            FROM task_comments c
+            JOIN task_comment_mentions m ON m.comment_id = c.id
+            JOIN tasks t ON t.id = c.task_id AND t.deleted_at IS NULL
+            WHERE m.employee_id = ? AND c.deleted_at IS NULL
+            ORDER BY c.created_at DESC, c.id DESC
+            "#,
+            COMMENT_COLUMNS
+        );
+
+        let comments = database::query_as::<TaskComment>(&sql)
+            .bind(employee_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(ApiError::from)?;
+
+        self.with_mentions(comments).await
+    }
+
+    /// Employees mentioned anywhere in a task's live comments.
+    pub async fn mentioned_employees(
+        &self,
+        task_id: i32,
+    ) -> Result<Vec<MentionedEmployee>, ApiError> {
+        database::query_as::<MentionedEmployee>(
+            r#"
+            SELECT e.id AS employee_id, e.external_id, e.first_name, e.last_name,
+                   COUNT(*) AS comment_count
+            FROM task_comment_mentions m
+            JOIN task_comments c ON c.id = m.comment_id
+            JOIN employees e ON e.id = m.employee_id
+            WHERE c.task_id = ? AND c.deleted_at IS NULL
+            GROUP BY e.id, e.external_id, e.first_name, e.last_name
+            ORDER BY e.external_id
+            "#,
+        )
+        .bind(task_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(ApiError::from)
+    }
+
+    pub async fn create(
+        &self,
+        task_id: i32,
+        author: &str,
+        body: &str,
+    ) -> Result<TaskComment, ApiError> {
+        let now = Utc::now();
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Transaction error: {}", e)))?;
+
+        let comment_id: i32 = database::query_scalar(
+            r#"
+            INSERT INTO task_comments (task_id, author, body, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?)
+            RETURNING id
+            "#,
+        )
+        .bind(task_id)
+        .bind(author)
+        .bind(body)
+        .bind(now)
+        .bind(now)
+        .fetch_one(&mut tx)
+        .await
+        .map_err(ApiError::from)?;
+
+        Self::save_mentions(&mut tx, comment_id, body).await?;
+
+        tx.commit().await.map_err(|e| {
+            ApiError::InternalServerError(format!("Transaction commit error: {}", e))
+        })?;
+
+        self.find_by_id(task_id, comment_id).await
+    }
+
+    /// Replaces a comment's body, keeping the previous body as a revision.
+    pub async fn update(
+        &self,
+        comment: &TaskComment,
+        body: &str,
+        editor: &str,
+    ) -> Result<TaskComment, ApiError> {
+        let now = Utc::now();
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Transaction error: {}", e)))?;
+
+        database::query(
+            r#"
+            INSERT INTO task_comment_revisions (comment_id, body, edited_by, edited_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(comment.id)
+        .bind(&comment.body)
+        .bind(editor)
+        .bind(now)
+        .execute(&mut tx)
+        .await
+        .map_err(ApiError::from)?;
+
+        database::query(
+            "UPDATE task_comments SET body = ?, edited_at = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(body)
+        .bind(now)
+        .bind(now)
+        .bind(comment.id)
+        .execute(&mut tx)
+        .await
+        .map_err(ApiError::from)?;
+
+        database::query("DELETE FROM task_comment_mentions WHERE comment_id = ?")
+            .bind(comment.id)
+            .execute(&mut tx)
+            .await
+            .map_err(ApiError::from)?;
+        Self::save_mentions(&mut tx, comment.id, body).await?;
+
+        tx.commit().await.map_err(|e| {
+            ApiError::InternalServerError(format!("Transaction commit error: {}", e))
+        })?;
+
+        self.find_by_id(comment.task_id, comment.id).await
+    }
+
+    pub async fn delete(&self, comment_id: i32) -> Result<(), ApiError> {
+        let now = Utc::now();
+        database::query("UPDATE task_comments SET deleted_at = ?, updated_at = ? WHERE id = ?")
+            .bind(now)
+            .bind(now)
+            .bind(comment_id)
+            .execute(&self.pool)
+            .await
+            .map_err(ApiError::from)?;
+
+        Ok(())
+    }
+
+    pub async fn revisions(&self, comment_id: i32) -> Result<Vec<CommentRevision>, ApiError> {
+        database::query_as::<CommentRevision>(
+            r#"
+            SELECT id, comment_id, body, edited_by, edited_at
+            FROM task_comment_revisions
+            WHERE comment_id = ?
+            ORDER BY edited_at, id
+            "#,
+        )
+        .bind(comment_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(ApiError::from)
+    }
+
+    /// Links the comment to every employee whose `external_id` is mentioned
+    /// in `body`. Mentions of unknown IDs are left as plain text.
+    async fn save_mentions(
+        conn: &mut DbTransaction,
+        comment_id: i32,
+        body: &str,
+    ) -> Result<(), ApiError> {
+        let mentions = extract_mentions(body);
+        if mentions.is_empty() {
+            return Ok(());
+        }
+
+        let placeholders = vec!["?"; mentions.len()].join(", ");
+        let sql = format!(
+            r#"
+            INSERT INTO task_comment_mentions (comment_id, employee_id)
+            SELECT ?, id FROM employees WHERE external_id IN ({})
+            ON CONFLICT DO NOTHING
+            "#,
+            placeholders
+        );
+
+        let mut query = database::query(&sql).bind(comment_id);
+        for external_id in &mentions {
+            query = query.bind(external_id);
+        }
+
+        query.execute(&mut *conn).await.map_err(ApiError::from)?;
+
+        Ok(())
+    }
+
+    async fn with_mentions(
+        &self,
+        mut comments: Vec<TaskComment>,
+    ) -> Result<Vec<TaskComment>, ApiError> {
+        if comments.is_empty() {
+            return Ok(comments);
+        }
+
+        let mut mentions: HashMap<i32, Vec<String>> = HashMap::new();
+        for chunk in comments.chunks(500) {
+            let placeholders = vec!["?"; chunk.len()].join(", ");
+            let sql = format!(
+                r#"
+                SELECT m.comment_id, e.external_id
+                FROM task_comment_mentions m
+                JOIN employees e ON e.id = m.employee_id
+                WHERE m.comment_id IN ({})
+                ORDER BY e.external_id
+                "#,
+                placeholders
+            );
+
+            let mut query = database::query_as::<(i32, String)>(&sql);
+            for comment in chunk {
+                query = query.bind(comment.id);
+            }
+
+            let rows = query.fetch_all(&self.pool).await.map_err(ApiError::from)?;
+            for (comment_id, external_id) in rows {
+                mentions.entry(comment_id).or_default().push(external_id);
+            }
+        }
+
+        for comment in &mut comments {
+            comment.mentions = mentions.remove(&comment.id).unwrap_or_default();
+        }
+
+        Ok(comments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::run_migrations;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn repository() -> CommentRepository {
+        let pool: DbPool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+            .into();
+        run_migrations(&pool).await.unwrap();
+
+        for (external_id, first_name) in [("EMP001", "John"), ("EMP002", "Sarah")] {
+            database::query(
+                "INSERT INTO employees (external_id, first_name, last_name, store_id) VALUES (?, ?, 'Test', 1)",
+            )
+            .bind(external_id)
+            .bind(first_name)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        database::query(
+            "INSERT INTO tasks (title, status, created_at, updated_at) VALUES ('Task', 'ToDo', ?, ?)",
+        )
+        .bind(Utc::now())
+        .bind(Utc::now())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        CommentRepository::new(pool)
+    }
+
+    #[tokio::test]
+    async fn test_create_resolves_known_mentions() {
+        let repository = repository().await;
+
+        let comment = repository
+            .create(1, "admin", "@EMP002 and @EMP001, not @EMP999")
+            .await
+            .unwrap();
+
+        assert_eq!(comment.mentions, ["EMP001", "EMP002"]);
+        let mentioned = repository.mentioned_employees(1).await.unwrap();
+        assert_eq!(mentioned.len(), 2);
+        assert_eq!(mentioned[0].comment_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_update_keeps_revision_and_refreshes_mentions() {
+        let repository = repository().await;
+        let comment = repository.create(1, "admin", "Ask @EMP001").await.unwrap();
+
+        let edited = repository
+            .update(&comment, "Ask @EMP002 instead", "admin")
+            .await
+            .unwrap();
+
+        assert_eq!(edited.mentions, ["EMP002"]);
+        assert!(edited.edited_at.is_some());
+        let revisions = repository.revisions(comment.id).await.unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].body, "Ask @EMP001");
+        assert!(repository.find_mentioning(1).await.unwrap().is_empty());
+        assert_eq!(repository.find_mentioning(2).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_deleted_comments_are_hidden() {
+        let repository = repository().await;
+        let comment = repository.create(1, "admin", "Ask @EMP001").await.unwrap();
+
+        repository.delete(comment.id).await.unwrap();
+
+        assert!(repository.find_by_task(1).await.unwrap().is_empty());
+        assert!(repository.find_by_id(1, comment.id).await.is_err());
+        assert!(repository.find_mentioning(1).await.unwrap().is_empty());
+        assert!(repository.mentioned_employees(1).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mentions_on_deleted_tasks_are_hidden() {
+        let repository = repository().await;
+        repository.create(1, "admin", "Ask @EMP001").await.unwrap();
+        assert_eq!(repository.find_mentioning(1).await.unwrap().len(), 1);
+
+        database::query("UPDATE tasks SET deleted_at = ? WHERE id = 1")
+            .bind(Utc::now())
+            .execute(&repository.pool)
+            .await
+            .unwrap();
+
+        assert!(repository.find_mentioning(1).await.unwrap().is_empty());
+    }
+}
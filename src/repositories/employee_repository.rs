@@ -1,21 +1,60 @@
+use crate::database::{self, DbPool};
 use crate::errors::ApiError;
 use crate::models::employee::*;
-use sqlx::{Row, SqlitePool};
+use crate::repositories::keyset::{PageRequest, fetch_page};
+use crate::repositories::query_builder::{Filter, SelectQuery, Sort, SortField};
+use crate::repositories::row_stream::stream_rows;
+use crate::utils::pagination::Page;
+use chrono::Utc;
+use futures_util::Stream;
+use std::collections::HashSet;
+
+const DEFAULT_EMPLOYEE_SORT: &str = "last_name,first_name";
+
+static EMPLOYEE_SORT_FIELDS: &[SortField] = &[
+    SortField {
+        name: "id",
+        expression: "id",
+    },
+    SortField {
+        name: "external_id",
+        expression: "COALESCE(external_id, '')",
+    },
+    SortField {
+        name: "first_name",
+        expression: "first_name",
+    },
+    SortField {
+        name: "last_name",
+        expression: "last_name",
+    },
+    SortField {
+        name: "store_id",
+        expression: "store_id",
+    },
+    SortField {
+        name: "email",
+        expression: "COALESCE(email, '')",
+    },
+];
+
+const EMPLOYEE_COLUMNS: &str =
+    "id, external_id, first_name, last_name, store_id, email, manager_id, deactivated_at";
 
 #[derive(Clone)]
 pub struct EmployeeRepository {
-    pool: SqlitePool,
+    pool: DbPool,
 }
 
 impl EmployeeRepository {
-    pub fn new(pool: SqlitePool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 
     pub async fn find_by_id(&self, id: i32) -> Result<Employee, ApiError> {
-        let row = sqlx::query(
+        let row = database::query(
             r#"
-            SELECT id, external_id, first_name, last_name, store_id, email
+            SELECT id, external_id, first_name, last_name, store_id, email, deactivated_at
             FROM employees
             WHERE id = ?
             "#,
@@ -23,7 +62,7 @@ impl EmployeeRepository {
         .bind(id)
         .fetch_optional(&self.pool)
         .await
-        .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?
+        .map_err(ApiError::from)?
         .ok_or_else(|| ApiError::NotFound("Employee not found".to_string()))?;
 
         let employee = Employee {
@@ -33,103 +72,152 @@ impl EmployeeRepository {
             last_name: row.get("last_name"),
             email: row.get("email"),
             store_id: row.get("store_id"),
+            deactivated_at: row.get("deactivated_at"),
         };
 
         Ok(employee)
     }
 
-    pub async fn find_all(&self, query: EmployeeQuery) -> Result<(Vec<Employee>, usize), ApiError> {
-        let mut sql = String::from(
-            "SELECT id, external_id, first_name, last_name, store_id, email, manager_id FROM employees",
-        );
-        let mut count_sql = String::from("SELECT COUNT(*) FROM employees");
-        let mut conditions = Vec::new();
-        let mut params: Vec<String> = Vec::new();
+    pub async fn find_all(&self, query: EmployeeQuery) -> Result<Page<Employee>, ApiError> {
+        let select = Self::select(&query)?;
+        let page = PageRequest::new(query.limit, query.offset, query.cursor, query.include_total)?;
 
-        if let Some(store_id) = query.store_id {
-            conditions.push("store_id = ?");
-            params.push(store_id.to_string());
-        }
+        fetch_page(&self.pool, select, page).await
+    }
 
-        if let Some(first_name) = &query.first_name {
-            conditions.push("first_name LIKE ?");
-            params.push(format!("%{}%", first_name));
-        }
+    /// Streams every employee matching the query's filters. The default page
+    /// size does not apply; `limit`/`offset` are honoured only when given.
+    pub fn export(
+        &self,
+        query: EmployeeQuery,
+    ) -> Result<impl Stream<Item = Result<Employee, ApiError>> + 'static, ApiError> {
+        let select = Self::select(&query)?
+            .limit(query.limit)
+            .offset(query.offset.unwrap_or(0));
 
-        if let Some(last_name) = &query.last_name {
-            conditions.push("last_name LIKE ?");
-            params.push(format!("%{}%", last_name));
-        }
+        Ok(stream_rows(self.pool.clone(), select, |row| {
+            row.decode::<Employee>()
+        }))
+    }
 
-        if let Some(email) = &query.email {
-            sql.push_str(" AND email = ?");
-            params.push(email.clone());
-        }
+    fn select(query: &EmployeeQuery) -> Result<SelectQuery, ApiError> {
+        let filter = Filter::new()
+            .eq("store_id", query.store_id)
+            .contains("first_name", query.first_name.as_deref())
+            .contains("last_name", query.last_name.as_deref())
+            .eq("email", query.email.as_deref())
+            .eq("external_id", query.external_id.as_deref());
 
-        if let Some(external_id) = &query.external_id {
-            sql.push_str(" AND external_id = ?");
-            params.push(external_id.clone());
-        }
+        let filter = match query.active {
+            Some(true) => filter.condition("deactivated_at IS NULL", vec![]),
+            Some(false) => filter.condition("deactivated_at IS NOT NULL", vec![]),
+            None => filter,
+        };
+
+        let sort = Sort::parse(
+            query.sort.as_deref().unwrap_or(DEFAULT_EMPLOYEE_SORT),
+            EMPLOYEE_SORT_FIELDS,
+        )
+        .map_err(ApiError::ValidationError)?;
+
+        Ok(SelectQuery::new("employees", EMPLOYEE_COLUMNS)
+            .filter(filter)
+            .order_by(sort))
+    }
+
+    /// Marks the employee as deactivated. Deactivating twice keeps the
+    /// original timestamp.
+    pub async fn deactivate(&self, id: i32) -> Result<Employee, ApiError> {
+        let result = database::query(
+            "UPDATE employees SET deactivated_at = COALESCE(deactivated_at, ?) WHERE id = ?",
+        )
+        .bind(Utc::now())
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(ApiError::from)?;
 
-        if !conditions.is_empty() {
-            let where_clause = conditions.join(" AND ");
-            sql.push_str(&format!(" WHERE {}", where_clause));
-            count_sql.push_str(&format!(" WHERE {}", where_clause));
+        if result.rows_affected() == 0 {
+            return Err(ApiError::NotFound("Employee not found".to_string()));
         }
 
-        sql.push_str(" ORDER BY last_name ASC, first_name ASC");
+        self.find_by_id(id).await
+    }
 
-        let limit = query.limit.unwrap_or(10);
-        let offset = query.offset.unwrap_or(0);
-        sql.push_str(&format!(" LIMIT {} OFFSET {}", limit, offset));
+    pub async fn reactivate(&self, id: i32) -> Result<Employee, ApiError> {
+        let result = database::query("UPDATE employees SET deactivated_at = NULL WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(ApiError::from)?;
 
-        let total: i64 = if params.is_empty() {
-            sqlx::query(&count_sql)
-                .fetch_one(&self.pool)
-                .await
-                .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?
-                .get(0)
-        } else {
-            let mut query = sqlx::query(&count_sql);
-            for param in &params {
-                query = query.bind(param);
-            }
-            query
-                .fetch_one(&self.pool)
-                .await
-                .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?
-                .get(0)
-        };
+        if result.rows_affected() == 0 {
+            return Err(ApiError::NotFound("Employee not found".to_string()));
+        }
 
-        let rows = if params.is_empty() {
-            sqlx::query(&sql)
-                .fetch_all(&self.pool)
-                .await
-                .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?
-        } else {
-            let mut query = sqlx::query(&sql);
-            for param in &params {
-                query = query.bind(param);
-            }
-            query
-                .fetch_all(&self.pool)
-                .await
-                .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?
-        };
+        self.find_by_id(id).await
+    }
 
-        let employees: Vec<Employee> = rows
-            .into_iter()
-            .map(|row| Employee {
-                id: row.get("id"),
-                external_id: row.get("external_id"),
-                first_name: row.get("first_name"),
-                last_name: row.get("last_name"),
-                store_id: row.get("store_id"),
-                email: row.get("email"),
-            })
-            .collect();
+    /// Open (not Done, Cancelled or deleted) tasks per employee and per
+    /// store, optionally limited to one store.
+    pub async fn workload(&self, store_id: Option<i32>) -> Result<Workload, ApiError> {
+        let now = Utc::now();
+
+        let employees = database::query_as::<EmployeeWorkload>(
+            r#"
+            SELECT e.id AS employee_id, e.external_id, e.first_name, e.last_name, e.store_id,
+                   e.deactivated_at IS NOT NULL AS deactivated,
+                   COUNT(t.id) AS open_tasks,
+                   COUNT(*) FILTER (WHERE t.due_at < ?) AS overdue_tasks,
+                   COUNT(*) FILTER (WHERE t.priority_level = 'High') AS high,
+                   COUNT(*) FILTER (WHERE t.priority_level = 'Medium') AS medium,
+                   COUNT(*) FILTER (WHERE t.priority_level = 'Low') AS low,
+                   COUNT(t.id) FILTER (WHERE t.priority_level IS NULL) AS unprioritized
+            FROM employees e
+            LEFT JOIN tasks t
+                ON t.assigned_to = e.id
+                AND t.deleted_at IS NULL
+                AND t.status NOT IN ('Done', 'Cancelled')
+            WHERE ? IS NULL OR e.store_id = ?
+            GROUP BY e.id
+            ORDER BY open_tasks DESC, e.last_name, e.first_name, e.id
+            "#,
+        )
+        .bind(now)
+        .bind(store_id)
+        .bind(store_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(ApiError::from)?;
+
+        let stores = database::query_as::<StoreWorkload>(
+            r#"
+            SELECT COALESCE(t.store_id, e.store_id) AS store_id,
+                   COUNT(*) AS open_tasks,
+                   COUNT(*) FILTER (WHERE t.assigned_to IS NULL) AS unassigned_tasks,
+                   COUNT(*) FILTER (WHERE t.due_at < ?) AS overdue_tasks,
+                   COUNT(*) FILTER (WHERE t.priority_level = 'High') AS high,
+                   COUNT(*) FILTER (WHERE t.priority_level = 'Medium') AS medium,
+                   COUNT(*) FILTER (WHERE t.priority_level = 'Low') AS low,
+                   COUNT(*) FILTER (WHERE t.priority_level IS NULL) AS unprioritized
+            FROM tasks t
+            LEFT JOIN employees e ON e.id = t.assigned_to
+            WHERE t.deleted_at IS NULL
+                AND t.status NOT IN ('Done', 'Cancelled')
+                AND COALESCE(t.store_id, e.store_id) IS NOT NULL
+                AND (? IS NULL OR COALESCE(t.store_id, e.store_id) = ?)
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+        )
+        .bind(now)
+        .bind(store_id)
+        .bind(store_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(ApiError::from)?;
 
-        Ok((employees, total as usize))
+        Ok(Workload { employees, stores })
     }
 
     pub async fn create_bulk(
@@ -149,10 +237,11 @@ impl EmployeeRepository {
         let mut created_ids = Vec::new();
 
         for employee in employees {
-            let result = sqlx::query(
+            let id: i32 = database::query_scalar(
                 r#"
                 INSERT INTO employees (external_id, first_name, last_name, store_id, email)
                 VALUES (?, ?, ?, ?, ?)
+                RETURNING id
                 "#,
             )
             .bind(&employee.external_id)
@@ -160,11 +249,11 @@ impl EmployeeRepository {
             .bind(&employee.last_name)
             .bind(employee.store_id)
             .bind(&employee.email)
-            .execute(&mut *tx)
+            .fetch_one(&mut tx)
             .await
-            .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?;
+            .map_err(ApiError::from)?;
 
-            created_ids.push(result.last_insert_rowid() as i32);
+            created_ids.push(id);
         }
         tx.commit().await.map_err(|e| {
             ApiError::InternalServerError(format!("Transaction commit error: {}", e))
@@ -177,4 +266,432 @@ impl EmployeeRepository {
 
         Ok(created_employees)
     }
+
+    pub async fn find_existing_external_ids(
+        &self,
+        external_ids: &[String],
+    ) -> Result<HashSet<String>, ApiError> {
+        let mut existing = HashSet::new();
+
+        for chunk in external_ids.chunks(500) {
+            let placeholders = vec!["?"; chunk.len()].join(", ");
+            let sql = format!(
+                "SELECT external_id FROM employees WHERE external_id IN ({})",
+                placeholders
+            );
+
+            let mut query = database::query_scalar::<String>(&sql);
+            for external_id in chunk {
+                query = query.bind(external_id);
+            }
+
+            let found = query.fetch_all(&self.pool).await.map_err(ApiError::from)?;
+            existing.extend(found);
+        }
+
+        Ok(existing)
+    }
+
+    /// Inserts new employees and updates existing ones (matched on
+    /// `external_id`) in a single transaction.
+    pub async fn import(
+        &self,
+        creates: Vec<CreateEmployee>,
+        updates: Vec<CreateEmployee>,
+        update_columns: &[&str],
+    ) -> Result<(), ApiError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Transaction error: {}", e)))?;
+
+        for employee in creates {
+            database::query(
+                r#"
+                INSERT INTO employees (external_id, first_name, last_name, store_id, email)
+                VALUES (?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&employee.external_id)
+            .bind(&employee.first_name)
+            .bind(&employee.last_name)
+            .bind(employee.store_id)
+            .bind(&employee.email)
+            .execute(&mut tx)
+            .await
+            .map_err(ApiError::from)?;
+        }
+
+        let mut assignments = vec!["first_name = ?", "last_name = ?"];
+        for column in update_columns {
+            match *column {
+                "store_id" => assignments.push("store_id = ?"),
+                "email" => assignments.push("email = ?"),
+                _ => {}
+            }
+        }
+        let update_sql = format!(
+            "UPDATE employees SET {} WHERE external_id = ?",
+            assignments.join(", ")
+        );
+
+        for employee in updates {
+            let mut query = database::query(&update_sql)
+                .bind(employee.first_name)
+                .bind(employee.last_name);
+            for column in update_columns {
+                query = match *column {
+                    "store_id" => query.bind(employee.store_id),
+                    "email" => query.bind(employee.email.clone()),
+                    _ => query,
+                };
+            }
+            query
+                .bind(employee.external_id)
+                .execute(&mut tx)
+                .await
+                .map_err(ApiError::from)?;
+        }
+
+        tx.commit().await.map_err(|e| {
+            ApiError::InternalServerError(format!("Transaction commit error: {}", e))
+        })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::run_migrations;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn seeded_repository() -> (EmployeeRepository, Vec<CreateEmployee>) {
+        let pool: DbPool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+            .into();
+        run_migrations(&pool).await.unwrap();
+
+        let employees: Vec<CreateEmployee> = [
+            ("EMP001", "John", "Smith", 1, Some("john@example.com")),
+            ("EMP002", "Sarah", "Johnson", 1, None),
+            (
+                "EMP003",
+                "Johnny",
+                "Smithers",
+                2,
+                Some("johnny@example.com"),
+            ),
+            ("EMP004", "Emily", "Davis", 12, Some("john@example.com")),
+        ]
+        .into_iter()
+        .map(
+            |(external_id, first_name, last_name, store_id, email)| CreateEmployee {
+                external_id: external_id.to_string(),
+                first_name: first_name.to_string(),
+                last_name: last_name.to_string(),
+                store_id: Some(store_id),
+                email: email.map(str::to_string),
+            },
+        )
+        .collect();
+
+        let repository = EmployeeRepository::new(pool);
+        repository.create_bulk(employees.clone()).await.unwrap();
+
+        (repository, employees)
+    }
+
+    fn matches(employee: &CreateEmployee, query: &EmployeeQuery) -> bool {
+        query
+            .store_id
+            .is_none_or(|id| employee.store_id == Some(id))
+            && query
+                .first_name
+                .as_ref()
+                .is_none_or(|name| employee.first_name.contains(name.as_str()))
+            && query
+                .last_name
+                .as_ref()
+                .is_none_or(|name| employee.last_name.contains(name.as_str()))
+            && query
+                .email
+                .as_ref()
+                .is_none_or(|email| employee.email.as_ref() == Some(email))
+            && query
+                .external_id
+                .as_ref()
+                .is_none_or(|id| &employee.external_id == id)
+    }
+
+    #[tokio::test]
+    async fn test_find_all_every_filter_combination() {
+        let (repository, employees) = seeded_repository().await;
+
+        for mask in 0..32u8 {
+            let query = EmployeeQuery {
+                limit: Some(100),
+                offset: None,
+                store_id: (mask & 1 != 0).then_some(1),
+                first_name: (mask & 2 != 0).then(|| "John".to_string()),
+                last_name: (mask & 4 != 0).then(|| "Smith".to_string()),
+                email: (mask & 8 != 0).then(|| "john@example.com".to_string()),
+                external_id: (mask & 16 != 0).then(|| "EMP001".to_string()),
+                active: None,
+                sort: None,
+                cursor: None,
+                include_total: None,
+            };
+
+            let mut expected: Vec<String> = employees
+                .iter()
+                .filter(|employee| matches(employee, &query))
+                .map(|employee| employee.external_id.clone())
+                .collect();
+            expected.sort();
+
+            let page = repository.find_all(query.clone()).await.unwrap();
+            let mut found: Vec<String> = page.items.into_iter().map(|e| e.external_id).collect();
+            found.sort();
+
+            assert_eq!(found, expected, "filter mask {:05b}", mask);
+            assert_eq!(page.total, Some(expected.len()), "filter mask {:05b}", mask);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_all_store_id_is_compared_as_integer() {
+        let (repository, _) = seeded_repository().await;
+
+        let query = EmployeeQuery {
+            limit: None,
+            offset: None,
+            store_id: Some(1),
+            first_name: None,
+            last_name: None,
+            email: None,
+            external_id: None,
+            active: None,
+            sort: None,
+            cursor: None,
+            include_total: None,
+        };
+
+        let page = repository.find_all(query).await.unwrap();
+
+        assert_eq!(page.total, Some(2));
+        assert!(page.items.iter().all(|e| e.store_id == Some(1)));
+    }
+
+    #[tokio::test]
+    async fn test_find_all_total_ignores_pagination() {
+        let (repository, _) = seeded_repository().await;
+
+        let query = EmployeeQuery {
+            limit: Some(1),
+            offset: Some(1),
+            store_id: None,
+            first_name: Some("John".to_string()),
+            last_name: None,
+            email: None,
+            external_id: None,
+            active: None,
+            sort: None,
+            cursor: None,
+            include_total: None,
+        };
+
+        let page = repository.find_all(query).await.unwrap();
+
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.total, Some(2));
+        assert_eq!(page.offset, Some(1));
+        assert!(page.prev_cursor.is_some());
+        assert!(page.next_cursor.is_none());
+    }
+
+    fn page_query(sort: &str, cursor: Option<String>) -> EmployeeQuery {
+        EmployeeQuery {
+            limit: Some(1),
+            offset: None,
+            store_id: None,
+            first_name: None,
+            last_name: None,
+            email: None,
+            external_id: None,
+            active: None,
+            sort: Some(sort.to_string()),
+            cursor,
+            include_total: Some(false),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_all_cursor_walks_forward_and_back() {
+        let (repository, _) = seeded_repository().await;
+        let sort = "store_id,-email";
+
+        let mut all = page_query(sort, None);
+        all.limit = Some(100);
+        let expected: Vec<String> = repository
+            .find_all(all)
+            .await
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|e| e.external_id)
+            .collect();
+        assert_eq!(expected, ["EMP001", "EMP002", "EMP003", "EMP004"]);
+
+        let mut forward = Vec::new();
+        let mut page = repository.find_all(page_query(sort, None)).await.unwrap();
+        assert!(page.total.is_none());
+        assert!(page.prev_cursor.is_none());
+        loop {
+            forward.extend(page.items.iter().map(|e| e.external_id.clone()));
+            match page.next_cursor.clone() {
+                Some(cursor) => {
+                    page = repository
+                        .find_all(page_query(sort, Some(cursor)))
+                        .await
+                        .unwrap()
+                }
+                None => break,
+            }
+        }
+        assert_eq!(forward, expected);
+
+        let mut backward = Vec::new();
+        loop {
+            backward.extend(page.items.iter().map(|e| e.external_id.clone()));
+            match page.prev_cursor.clone() {
+                Some(cursor) => {
+                    page = repository
+                        .find_all(page_query(sort, Some(cursor)))
+                        .await
+                        .unwrap()
+                }
+                None => break,
+            }
+        }
+        backward.reverse();
+        assert_eq!(backward, expected);
+    }
+
+    #[tokio::test]
+    async fn test_find_all_rejects_cursor_from_another_sort() {
+        let (repository, _) = seeded_repository().await;
+
+        let page = repository
+            .find_all(page_query("last_name", None))
+            .await
+            .unwrap();
+        let cursor = page.next_cursor.unwrap();
+
+        let result = repository
+            .find_all(page_query("first_name", Some(cursor)))
+            .await;
+
+        assert!(matches!(result, Err(ApiError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_deactivated_employees_are_filtered_and_counted() {
+        let (repository, _) = seeded_repository().await;
+        let employee = repository.deactivate(2).await.unwrap();
+        let deactivated_at = employee.deactivated_at.unwrap();
+        assert_eq!(
+            repository.deactivate(2).await.unwrap().deactivated_at,
+            Some(deactivated_at)
+        );
+
+        let mut query = page_query("id", None);
+        query.active = Some(false);
+        let page = repository.find_all(query).await.unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].id, 2);
+
+        let employee = repository.reactivate(2).await.unwrap();
+        assert!(employee.deactivated_at.is_none());
+        assert!(matches!(
+            repository.deactivate(99).await,
+            Err(ApiError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_workload_counts_open_tasks_per_employee_and_store() {
+        let (repository, _) = seeded_repository().await;
+        let now = Utc::now();
+
+        for (title, priority, status, assigned_to, store_id, due_at) in [
+            ("Count stock", Some("High"), "ToDo", Some(1), None, None),
+            (
+                "Clean",
+                None,
+                "InProgress",
+                Some(1),
+                None,
+                Some(now - chrono::Duration::days(1)),
+            ),
+            ("Done already", Some("Low"), "Done", Some(1), None, None),
+            ("Unassigned", Some("Low"), "ToDo", None, Some(2), None),
+            (
+                "Other store",
+                Some("Medium"),
+                "ToDo",
+                Some(2),
+                Some(12),
+                None,
+            ),
+        ] {
+            database::query(
+                "INSERT INTO tasks (title, priority_level, status, assigned_to, store_id, due_at, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(title)
+            .bind(priority)
+            .bind(status)
+            .bind(assigned_to)
+            .bind(store_id)
+            .bind(due_at)
+            .bind(now)
+            .bind(now)
+            .execute(&repository.pool)
+            .await
+            .unwrap();
+        }
+
+        let workload = repository.workload(None).await.unwrap();
+
+        let john = &workload.employees[0];
+        assert_eq!(john.employee_id, 1);
+        assert_eq!((john.open_tasks, john.overdue_tasks), (2, 1));
+        assert_eq!(
+            john.priorities,
+            PriorityMix {
+                high: 1,
+                medium: 0,
+                low: 0,
+                unprioritized: 1,
+            }
+        );
+        assert_eq!(workload.employees.len(), 4);
+        assert_eq!(workload.employees[3].open_tasks, 0);
+
+        let stores: Vec<(i32, i64, i64)> = workload
+            .stores
+            .iter()
+            .map(|store| (store.store_id, store.open_tasks, store.unassigned_tasks))
+            .collect();
+        assert_eq!(stores, [(1, 2, 0), (2, 1, 1), (12, 1, 0)]);
+
+        let store = repository.workload(Some(2)).await.unwrap();
+        assert_eq!(store.employees.len(), 1);
+        assert_eq!(store.stores.len(), 1);
+    }
 }
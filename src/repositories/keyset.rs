@@ -0,0 +1,262 @@
+use crate::database::{self, DbPool, DbRow, FromDbRow};
+use crate::errors::ApiError;
+use crate::repositories::query_builder::{BindValue, BindValues, SelectQuery};
+use crate::utils::pagination::Page;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_PAGE_SIZE: usize = 10;
+pub const MAX_PAGE_SIZE: usize = 100;
+
+#[derive(Clone, Debug)]
+pub struct PageRequest {
+    pub limit: usize,
+    pub offset: usize,
+    pub cursor: Option<String>,
+    pub include_total: bool,
+}
+
+impl PageRequest {
+    pub fn new(
+        limit: Option<usize>,
+        offset: Option<usize>,
+        cursor: Option<String>,
+        include_total: Option<bool>,
+    ) -> Result<Self, ApiError> {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        if limit == 0 || limit > MAX_PAGE_SIZE {
+            return Err(ApiError::ValidationError(format!(
+                "limit must be between 1 and {}",
+                MAX_PAGE_SIZE
+            )));
+        }
+
+        Ok(Self {
+            limit,
+            offset: offset.unwrap_or(0),
+            cursor,
+            include_total: include_total.unwrap_or(true),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+enum CursorDirection {
+    #[serde(rename = "next")]
+    Next,
+    #[serde(rename = "prev")]
+    Prev,
+}
+
+/// The decoded form of a page token: the sort key values of the row the page
+/// starts after (or before), plus the sort it was issued for.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Cursor {
+    sort: String,
+    direction: CursorDirection,
+    keys: Vec<BindValue>,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    fn decode(token: &str) -> Result<Self, String> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(token)
+            .map_err(|_| "Invalid cursor".to_string())?;
+        serde_json::from_slice(&bytes).map_err(|_| "Invalid cursor".to_string())
+    }
+}
+
+/// Fetches one page of `select`. With a cursor the page is located by keyset
+/// on the active sort, otherwise by `offset`; either way the returned page
+/// carries cursors for its neighbours.
+pub async fn fetch_page<T>(
+    pool: &DbPool,
+    select: SelectQuery,
+    request: PageRequest,
+) -> Result<Page<T>, ApiError>
+where
+    T: FromDbRow,
+{
+    let sort = select.sort().cloned().ok_or_else(|| {
+        ApiError::InternalServerError("Paginated queries must be sorted".to_string())
+    })?;
+    let signature = sort.signature();
+
+    let total = if request.include_total {
+        let count_sql = select.count_sql();
+        let total: i64 = database::query_scalar(&count_sql)
+            .bind_values(select.binds())
+            .fetch_one(pool)
+            .await
+            .map_err(ApiError::from)?;
+        Some(total as usize)
+    } else {
+        None
+    };
+
+    let cursor = request
+        .cursor
+        .as_deref()
+        .map(Cursor::decode)
+        .transpose()
+        .map_err(ApiError::ValidationError)?;
+
+    if let Some(cursor) = &cursor
+        && cursor.sort != signature
+    {
+        return Err(ApiError::ValidationError(
+            "Cursor does not match the requested sort".to_string(),
+        ));
+    }
+
+    let backwards = cursor
+        .as_ref()
+        .is_some_and(|cursor| cursor.direction == CursorDirection::Prev);
+
+    let page_select = match &cursor {
+        Some(cursor) => select
+            .seek(&cursor.keys, backwards)
+            .map_err(ApiError::ValidationError)?
+            .limit(Some(request.limit + 1)),
+        None => select.limit(Some(request.limit + 1)).offset(request.offset),
+    };
+
+    let sql = page_select.sql();
+    let rows = database::query(&sql)
+        .bind_values(page_select.binds())
+        .fetch_all(pool)
+        .await
+        .map_err(ApiError::from)?;
+
+    let has_more = rows.len() > request.limit;
+    let mut entries = Vec::with_capacity(rows.len());
+    for row in rows.into_iter().take(request.limit) {
+        let item = row
+            .decode::<T>()
+            .and_then(|item| Ok((item, sort_keys(&row, sort.keys().len())?)))
+            .map_err(ApiError::from)?;
+        entries.push(item);
+    }
+
+    if backwards {
+        entries.reverse();
+    }
+
+    let (has_next, has_prev) = if backwards {
+        (true, has_more)
+    } else {
+        (has_more, cursor.is_some() || request.offset > 0)
+    };
+
+    let make_cursor = |direction, keys: &Vec<BindValue>| {
+        Cursor {
+            sort: signature.clone(),
+            direction,
+            keys: keys.clone(),
+        }
+        .encode()
+    };
+
+    let next_cursor = entries
+        .last()
+        .filter(|_| has_next)
+        .map(|(_, keys)| make_cursor(CursorDirection::Next, keys));
+    let prev_cursor = entries
+        .first()
+        .filter(|_| has_prev)
+        .map(|(_, keys)| make_cursor(CursorDirection::Prev, keys));
+
+    Ok(Page {
+        items: entries.into_iter().map(|(item, _)| item).collect(),
+        total,
+        limit: request.limit,
+        offset: cursor.is_none().then_some(request.offset),
+        next_cursor,
+        prev_cursor,
+    })
+}
+
+/// Reads the `sort_key_N` columns back as the values to bind when seeking.
+/// SQLite keys are compared as stored, so timestamps stay text; PostgreSQL
+/// keys keep their column type.
+fn sort_keys(row: &DbRow, count: usize) -> Result<Vec<BindValue>, sqlx::Error> {
+    (0..count)
+        .map(|index| {
+            let column = format!("sort_key_{}", index);
+            let column = column.as_str();
+            let key = row.try_get::<i64, _>(column).map(BindValue::Int);
+            let key = match row {
+                DbRow::Sqlite(_) => key,
+                DbRow::Postgres(_) => key
+                    .or_else(|_| row.try_get::<i32, _>(column).map(BindValue::from))
+                    .or_else(|_| {
+                        row.try_get::<DateTime<Utc>, _>(column)
+                            .map(BindValue::DateTime)
+                    }),
+            };
+            key.or_else(|_| row.try_get::<String, _>(column).map(BindValue::Text))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = Cursor {
+            sort: "-created_at,id".to_string(),
+            direction: CursorDirection::Next,
+            keys: vec![
+                BindValue::Text("2024-01-01T00:00:00Z".to_string()),
+                BindValue::Int(42),
+            ],
+        };
+
+        let token = cursor.encode();
+
+        assert!(!token.contains('='));
+        assert_eq!(Cursor::decode(&token).unwrap(), cursor);
+    }
+
+    #[test]
+    fn test_cursor_decode_rejects_garbage() {
+        assert_eq!(
+            Cursor::decode("not a cursor").unwrap_err(),
+            "Invalid cursor"
+        );
+        assert!(Cursor::decode(&URL_SAFE_NO_PAD.encode(b"{}")).is_err());
+    }
+
+    #[test]
+    fn test_page_request_limit_bounds() {
+        let page = PageRequest::new(None, None, None, None).unwrap();
+        assert_eq!(page.limit, DEFAULT_PAGE_SIZE);
+        assert!(PageRequest::new(Some(MAX_PAGE_SIZE), None, None, None).is_ok());
+    }
+
+    #[test]
+    fn test_page_request_rejects_oversized_limit() {
+        for limit in [MAX_PAGE_SIZE + 1, 1_000_000_000_000, usize::MAX] {
+            assert!(matches!(
+                PageRequest::new(Some(limit), None, None, None),
+                Err(ApiError::ValidationError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_page_request_rejects_zero_limit() {
+        assert!(matches!(
+            PageRequest::new(Some(0), None, None, None),
+            Err(ApiError::ValidationError(_))
+        ));
+    }
+}
@@ -1,7 +1,20 @@
+pub mod assignment_repository;
+pub mod attachment_repository;
+pub mod comment_repository;
 pub mod employee_repository;
+pub mod keyset;
 pub mod object_repository;
+pub mod query_builder;
+pub mod report_repository;
+pub mod row_stream;
 pub mod task_repository;
+pub mod task_template_repository;
 
+pub use assignment_repository::*;
+pub use attachment_repository::*;
+pub use comment_repository::*;
 pub use employee_repository::*;
 pub use object_repository::*;
+pub use report_repository::*;
 pub use task_repository::*;
+pub use task_template_repository::*;
@@ -1,25 +1,64 @@
+use crate::database::{self, DbPool};
 use crate::errors::ApiError;
 use crate::models::object::*;
+use crate::repositories::keyset::{PageRequest, fetch_page};
+use crate::repositories::query_builder::{
+    BindValue, BindValues, Filter, SelectQuery, Sort, SortField,
+};
+use crate::repositories::row_stream::stream_rows;
+use crate::utils::pagination::Page;
 use chrono::Utc;
-use sqlx::{Row, SqlitePool};
+use futures_util::Stream;
+
+const DEFAULT_OBJECT_SORT: &str = "-created_at";
+
+static OBJECT_SORT_FIELDS: &[SortField] = &[
+    SortField {
+        name: "id",
+        expression: "id",
+    },
+    SortField {
+        name: "name",
+        expression: "name",
+    },
+    SortField {
+        name: "email",
+        expression: "email",
+    },
+    SortField {
+        name: "age",
+        expression: "COALESCE(age, 0)",
+    },
+    SortField {
+        name: "created_at",
+        expression: "created_at",
+    },
+    SortField {
+        name: "updated_at",
+        expression: "updated_at",
+    },
+];
+
+const OBJECT_COLUMNS: &str = "id, name, email, age, created_at, updated_at";
 
 #[derive(Clone)]
 pub struct ObjectRepository {
-    pool: SqlitePool,
+    pool: DbPool,
 }
 
 impl ObjectRepository {
-    pub fn new(pool: SqlitePool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 
     pub async fn create(&self, req: CreateObjectRequest) -> Result<Object, ApiError> {
         let now = Utc::now();
 
-        let result = sqlx::query(
+        let id: i32 = database::query_scalar(
             r#"
             INSERT INTO objects (name, email, age, created_at, updated_at)
             VALUES (?, ?, ?, ?, ?)
+            RETURNING id
             "#,
         )
         .bind(&req.name)
@@ -27,16 +66,15 @@ impl ObjectRepository {
         .bind(req.age)
         .bind(now)
         .bind(now)
-        .execute(&self.pool)
+        .fetch_one(&self.pool)
         .await
-        .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?;
+        .map_err(ApiError::from)?;
 
-        let id = result.last_insert_rowid() as i32;
         Ok(Object::new(id, req.name, req.email, req.age))
     }
 
     pub async fn find_by_id(&self, id: i32) -> Result<Object, ApiError> {
-        let row = sqlx::query(
+        let row = database::query(
             r#"
             SELECT id, name, email, age, created_at, updated_at
             FROM objects
@@ -46,7 +84,7 @@ impl ObjectRepository {
         .bind(id)
         .fetch_optional(&self.pool)
         .await
-        .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?
+        .map_err(ApiError::from)?
         .ok_or_else(|| ApiError::NotFound("Object not found".to_string()))?;
 
         let object = Object {
@@ -61,121 +99,81 @@ impl ObjectRepository {
         Ok(object)
     }
 
-    pub async fn find_all(&self, query: ObjectQuery) -> Result<(Vec<Object>, usize), ApiError> {
-        let mut sql =
-            String::from("SELECT id, name, email, age, created_at, updated_at FROM objects");
-        let mut count_sql = String::from("SELECT COUNT(*) FROM objects");
-        let mut conditions = Vec::new();
-        let mut params: Vec<String> = Vec::new();
+    pub async fn find_all(&self, query: ObjectQuery) -> Result<Page<Object>, ApiError> {
+        let select = Self::select(&query)?;
+        let page = PageRequest::new(query.limit, query.offset, query.cursor, query.include_total)?;
 
-        if let Some(name) = &query.name {
-            conditions.push("name LIKE ?");
-            params.push(format!("%{}%", name));
-        }
+        fetch_page(&self.pool, select, page).await
+    }
 
-        if !conditions.is_empty() {
-            let where_clause = conditions.join(" AND ");
-            sql.push_str(&format!(" WHERE {}", where_clause));
-            count_sql.push_str(&format!(" WHERE {}", where_clause));
-        }
+    /// Streams every object matching the query's filters. The default page
+    /// size does not apply; `limit`/`offset` are honoured only when given.
+    pub fn export(
+        &self,
+        query: ObjectQuery,
+    ) -> Result<impl Stream<Item = Result<Object, ApiError>> + 'static, ApiError> {
+        let select = Self::select(&query)?
+            .limit(query.limit)
+            .offset(query.offset.unwrap_or(0));
+
+        Ok(stream_rows(self.pool.clone(), select, |row| {
+            row.decode::<Object>()
+        }))
+    }
 
-        sql.push_str(" ORDER BY created_at DESC");
-
-        let limit = query.limit.unwrap_or(10);
-        let offset = query.offset.unwrap_or(0);
-        sql.push_str(&format!(" LIMIT {} OFFSET {}", limit, offset));
-
-        let total: i64 = if params.is_empty() {
-            sqlx::query(&count_sql)
-                .fetch_one(&self.pool)
-                .await
-                .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?
-                .get(0)
-        } else {
-            let mut query = sqlx::query(&count_sql);
-            for param in &params {
-                query = query.bind(param);
-            }
-            query
-                .fetch_one(&self.pool)
-                .await
-                .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?
-                .get(0)
-        };
+    fn select(query: &ObjectQuery) -> Result<SelectQuery, ApiError> {
+        let filter = Filter::new().contains("name", query.name.as_deref());
 
-        let rows = if params.is_empty() {
-            sqlx::query(&sql)
-                .fetch_all(&self.pool)
-                .await
-                .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?
-        } else {
-            let mut query = sqlx::query(&sql);
-            for param in &params {
-                query = query.bind(param);
-            }
-            query
-                .fetch_all(&self.pool)
-                .await
-                .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?
-        };
+        let sort = Sort::parse(
+            query.sort.as_deref().unwrap_or(DEFAULT_OBJECT_SORT),
+            OBJECT_SORT_FIELDS,
+        )
+        .map_err(ApiError::ValidationError)?;
 
-        let objects: Vec<Object> = rows
-            .into_iter()
-            .map(|row| Object {
-                id: row.get("id"),
-                name: row.get("name"),
-                email: row.get("email"),
-                age: row.get("age"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            })
-            .collect();
-
-        Ok((objects, total as usize))
+        Ok(SelectQuery::new("objects", OBJECT_COLUMNS)
+            .filter(filter)
+            .order_by(sort))
     }
 
     pub async fn update(&self, id: i32, req: UpdateObjectRequest) -> Result<Object, ApiError> {
         self.find_by_id(id).await?;
 
         let mut sql = String::from("UPDATE objects SET updated_at = ?");
-        let mut params: Vec<String> = Vec::new();
+        let mut params: Vec<BindValue> = Vec::new();
         let now = Utc::now();
 
         if let Some(name) = &req.name {
             sql.push_str(", name = ?");
-            params.push(name.clone());
+            params.push(name.as_str().into());
         }
         if let Some(email) = &req.email {
             sql.push_str(", email = ?");
-            params.push(email.clone());
+            params.push(email.as_str().into());
         }
         if let Some(age) = req.age {
             sql.push_str(", age = ?");
-            params.push(age.to_string());
+            params.push(age.into());
         }
 
         sql.push_str(" WHERE id = ?");
 
-        let mut query = sqlx::query(&sql).bind(now);
-        for param in &params {
-            query = query.bind(param);
-        }
-        query = query.bind(id);
-
-        query
+        database::query(&sql)
+            .bind(now)
+            .bind_values(&params)
+            .bind(id)
             .execute(&self.pool)
             .await
-            .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?;
+            .map_err(ApiError::from)?;
 
         self.find_by_id(id).await
     }
 
     pub async fn delete(&self, id: i32) -> Result<(), ApiError> {
-        let result = sqlx::query("DELETE FROM objects WHERE id = ?")
+        let result = database::query("DELETE FROM objects WHERE id = ?")
             .bind(id)
             .execute(&self.pool)
             .await
-            .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?;
+            .map_err(ApiError::from)?;
 
         if result.rows_affected() == 0 {
             return Err(ApiError::NotFound("Object not found".to_string()));
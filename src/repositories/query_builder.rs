@@ -0,0 +1,610 @@
+use crate::database::{Decodable, FromDbRow, Query, QueryAs, QueryScalar};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A typed bind parameter. Values keep their type instead of being
+/// stringified, so integer columns compare as integers, and the type is kept
+/// when serialized so PostgreSQL gets timestamps back as timestamps.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum BindValue {
+    Int(i64),
+    Text(String),
+    DateTime(DateTime<Utc>),
+}
+
+impl From<i32> for BindValue {
+    fn from(value: i32) -> Self {
+        BindValue::Int(value as i64)
+    }
+}
+
+impl From<i64> for BindValue {
+    fn from(value: i64) -> Self {
+        BindValue::Int(value)
+    }
+}
+
+impl From<String> for BindValue {
+    fn from(value: String) -> Self {
+        BindValue::Text(value)
+    }
+}
+
+impl From<DateTime<Utc>> for BindValue {
+    fn from(value: DateTime<Utc>) -> Self {
+        BindValue::DateTime(value)
+    }
+}
+
+impl From<&str> for BindValue {
+    fn from(value: &str) -> Self {
+        BindValue::Text(value.to_string())
+    }
+}
+
+/// A set of `AND`-ed conditions and the values bound to their placeholders.
+#[derive(Clone, Debug, Default)]
+pub struct Filter {
+    conditions: Vec<String>,
+    binds: Vec<BindValue>,
+}
+
+impl Filter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `column = ?` when `value` is present.
+    pub fn eq<V: Into<BindValue>>(mut self, column: &str, value: Option<V>) -> Self {
+        if let Some(value) = value {
+            self.conditions.push(format!("{} = ?", column));
+            self.binds.push(value.into());
+        }
+        self
+    }
+
+    /// Adds `column < ?` when `value` is present.
+    pub fn lt<V: Into<BindValue>>(mut self, column: &str, value: Option<V>) -> Self {
+        if let Some(value) = value {
+            self.conditions.push(format!("{} < ?", column));
+            self.binds.push(value.into());
+        }
+        self
+    }
+
+    /// Adds `column > ?` when `value` is present.
+    pub fn gt<V: Into<BindValue>>(mut self, column: &str, value: Option<V>) -> Self {
+        if let Some(value) = value {
+            self.conditions.push(format!("{} > ?", column));
+            self.binds.push(value.into());
+        }
+        self
+    }
+
+    /// Adds a case-insensitive substring match on `column` when `value` is
+    /// present. `%`, `_` and `\` in the value are matched literally.
+    pub fn contains(mut self, column: &str, value: Option<&str>) -> Self {
+        if let Some(value) = value {
+            let escaped = value
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            self.conditions
+                .push(format!("LOWER({}) LIKE LOWER(?) ESCAPE '\\'", column));
+            self.binds.push(BindValue::Text(format!("%{}%", escaped)));
+        }
+        self
+    }
+
+    /// Adds a raw condition. `binds` must match the placeholders in `sql`.
+    pub fn condition(mut self, sql: impl Into<String>, binds: Vec<BindValue>) -> Self {
+        self.conditions.push(sql.into());
+        self.binds.extend(binds);
+        self
+    }
+
+    pub fn where_clause(&self) -> String {
+        if self.conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", self.conditions.join(" AND "))
+        }
+    }
+
+    pub fn binds(&self) -> &[BindValue] {
+        &self.binds
+    }
+}
+
+/// A field clients may sort on, and the SQL expression that orders it. The
+/// expression is never taken from user input and must never evaluate to NULL,
+/// so that keyset comparisons against a cursor stay well defined.
+#[derive(Debug)]
+pub struct SortField {
+    pub name: &'static str,
+    pub expression: &'static str,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+#[derive(Clone, Debug)]
+pub struct SortKey {
+    pub field: &'static SortField,
+    pub direction: SortDirection,
+}
+
+/// A validated sort order such as `-priority_level,created_at`, always ending
+/// in an `id` tiebreaker so rows with equal keys come back in a stable order.
+#[derive(Clone, Debug)]
+pub struct Sort {
+    keys: Vec<SortKey>,
+}
+
+impl SortDirection {
+    fn as_sql(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => "ASC",
+            SortDirection::Desc => "DESC",
+        }
+    }
+
+    fn reversed(&self) -> Self {
+        match self {
+            SortDirection::Asc => SortDirection::Desc,
+            SortDirection::Desc => SortDirection::Asc,
+        }
+    }
+}
+
+impl Sort {
+    /// Parses a comma separated list of field names, each optionally
+    /// prefixed with `-` for descending order, against `fields`.
+    pub fn parse(input: &str, fields: &'static [SortField]) -> Result<Self, String> {
+        let mut keys: Vec<SortKey> = Vec::new();
+
+        for part in input.split(',').map(str::trim) {
+            let (name, direction) = match part.strip_prefix('-') {
+                Some(name) => (name, SortDirection::Desc),
+                None => (part.strip_prefix('+').unwrap_or(part), SortDirection::Asc),
+            };
+
+            let field = fields
+                .iter()
+                .find(|field| field.name == name)
+                .ok_or_else(|| {
+                    let allowed: Vec<&str> = fields.iter().map(|field| field.name).collect();
+                    format!(
+                        "Cannot sort by '{}'. Sortable fields: {}",
+                        name,
+                        allowed.join(", ")
+                    )
+                })?;
+
+            if keys.iter().any(|key| key.field.name == field.name) {
+                return Err(format!("Duplicate sort field: {}", name));
+            }
+
+            keys.push(SortKey { field, direction });
+        }
+
+        if !keys.iter().any(|key| key.field.name == "id") {
+            let id = fields
+                .iter()
+                .find(|field| field.name == "id")
+                .ok_or_else(|| "Sortable fields must include id".to_string())?;
+            keys.push(SortKey {
+                field: id,
+                direction: SortDirection::Asc,
+            });
+        }
+
+        Ok(Self { keys })
+    }
+
+    pub fn keys(&self) -> &[SortKey] {
+        &self.keys
+    }
+
+    /// A canonical form of the sort such as `-priority_level,created_at,id`.
+    pub fn signature(&self) -> String {
+        self.keys
+            .iter()
+            .map(|key| match key.direction {
+                SortDirection::Asc => key.field.name.to_string(),
+                SortDirection::Desc => format!("-{}", key.field.name),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    pub fn reversed(&self) -> Self {
+        Self {
+            keys: self
+                .keys
+                .iter()
+                .map(|key| SortKey {
+                    field: key.field,
+                    direction: key.direction.reversed(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Builds the keyset condition selecting rows that sort strictly after
+    /// `values`, e.g. `(a > ?) OR (a = ? AND b < ?) OR ...`.
+    fn seek_condition(&self, values: &[BindValue]) -> (String, Vec<BindValue>) {
+        let mut branches = Vec::new();
+        let mut binds = Vec::new();
+
+        for (index, key) in self.keys.iter().enumerate() {
+            let mut terms = Vec::new();
+            for (previous, value) in self.keys[..index].iter().zip(values) {
+                terms.push(format!("{} = ?", previous.field.expression));
+                binds.push(value.clone());
+            }
+
+            let operator = match key.direction {
+                SortDirection::Asc => ">",
+                SortDirection::Desc => "<",
+            };
+            terms.push(format!("{} {} ?", key.field.expression, operator));
+            binds.push(values[index].clone());
+
+            branches.push(format!("({})", terms.join(" AND ")));
+        }
+
+        (format!("({})", branches.join(" OR ")), binds)
+    }
+
+    pub fn order_by(&self) -> String {
+        self.keys
+            .iter()
+            .map(|key| format!("{} {}", key.field.expression, key.direction.as_sql()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// A `SELECT` over one table whose data and count statements are generated
+/// from the same filter, so they can never disagree.
+#[derive(Clone, Debug)]
+pub struct SelectQuery {
+    table: &'static str,
+    columns: &'static str,
+    filter: Filter,
+    sort: Option<Sort>,
+    limit: Option<usize>,
+    offset: usize,
+}
+
+impl SelectQuery {
+    pub fn new(table: &'static str, columns: &'static str) -> Self {
+        Self {
+            table,
+            columns,
+            filter: Filter::new(),
+            sort: None,
+            limit: None,
+            offset: 0,
+        }
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn order_by(mut self, sort: Sort) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+
+    pub fn sort(&self) -> Option<&Sort> {
+        self.sort.as_ref()
+    }
+
+    /// Restricts the query to rows sorting after the given sort key values.
+    /// With `backwards`, the sort is reversed first so the rows immediately
+    /// before the key come back (in reverse order).
+    pub fn seek(mut self, values: &[BindValue], backwards: bool) -> Result<Self, String> {
+        let sort = self
+            .sort
+            .take()
+            .ok_or_else(|| "Cannot seek an unsorted query".to_string())?;
+        if values.len() != sort.keys().len() {
+            return Err("Cursor does not match the sort order".to_string());
+        }
+
+        let sort = if backwards { sort.reversed() } else { sort };
+        let (condition, binds) = sort.seek_condition(values);
+
+        self.filter = self.filter.condition(condition, binds);
+        self.sort = Some(sort);
+        Ok(self)
+    }
+
+    pub fn limit(mut self, limit: Option<usize>) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn sql(&self) -> String {
+        let mut columns = self.columns.to_string();
+        if let Some(sort) = &self.sort {
+            for (index, key) in sort.keys().iter().enumerate() {
+                columns.push_str(&format!(", {} AS sort_key_{}", key.field.expression, index));
+            }
+        }
+
+        let mut sql = format!(
+            "SELECT {} FROM {}{}",
+            columns,
+            self.table,
+            self.filter.where_clause()
+        );
+
+        if let Some(sort) = &self.sort {
+            sql.push_str(&format!(" ORDER BY {}", sort.order_by()));
+        }
+
+        match self.limit {
+            Some(limit) => sql.push_str(&format!(" LIMIT {} OFFSET {}", limit, self.offset)),
+            // SQLite needs a LIMIT before OFFSET and PostgreSQL rejects -1.
+            None if self.offset > 0 => {
+                sql.push_str(&format!(" LIMIT {} OFFSET {}", i64::MAX, self.offset))
+            }
+            None => {}
+        }
+
+        sql
+    }
+
+    pub fn count_sql(&self) -> String {
+        format!(
+            "SELECT COUNT(*) FROM {}{}",
+            self.table,
+            self.filter.where_clause()
+        )
+    }
+
+    pub fn binds(&self) -> &[BindValue] {
+        self.filter.binds()
+    }
+}
+
+/// Binds a list of `BindValue`s onto any of the database query types.
+pub trait BindValues<'q> {
+    fn bind_values(self, values: &'q [BindValue]) -> Self;
+}
+
+macro_rules! impl_bind_values {
+    ($query:ty $(, $generic:ident: $($bound:tt)+)?) => {
+        impl<'q $(, $generic: $($bound)+)?> BindValues<'q> for $query {
+            fn bind_values(mut self, values: &'q [BindValue]) -> Self {
+                for value in values {
+                    self = match value {
+                        BindValue::Int(value) => self.bind(*value),
+                        BindValue::Text(value) => self.bind(value.as_str()),
+                        BindValue::DateTime(value) => self.bind(*value),
+                    };
+                }
+                self
+            }
+        }
+    };
+}
+
+impl_bind_values!(Query<'q>);
+impl_bind_values!(QueryAs<'q, O>, O: FromDbRow);
+impl_bind_values!(QueryScalar<'q, O>, O: for<'r> Decodable<'r> + Send + Unpin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static FIELDS: &[SortField] = &[
+        SortField {
+            name: "id",
+            expression: "id",
+        },
+        SortField {
+            name: "created_at",
+            expression: "created_at",
+        },
+        SortField {
+            name: "priority_level",
+            expression: "CASE priority_level WHEN 'High' THEN 3 ELSE 0 END",
+        },
+    ];
+
+    #[test]
+    fn test_empty_filter() {
+        let sort = Sort::parse("id", FIELDS).unwrap();
+        let select = SelectQuery::new("employees", "id").order_by(sort);
+
+        assert_eq!(
+            select.sql(),
+            "SELECT id, id AS sort_key_0 FROM employees ORDER BY id ASC"
+        );
+        assert_eq!(select.count_sql(), "SELECT COUNT(*) FROM employees");
+        assert!(select.binds().is_empty());
+    }
+
+    #[test]
+    fn test_filter_skips_missing_values() {
+        let filter = Filter::new()
+            .eq("store_id", Some(2))
+            .eq::<String>("email", None)
+            .contains("first_name", None)
+            .contains("last_name", Some("Smith"));
+
+        assert_eq!(
+            filter.where_clause(),
+            " WHERE store_id = ? AND LOWER(last_name) LIKE LOWER(?) ESCAPE '\\'"
+        );
+        assert_eq!(
+            filter.binds(),
+            &[BindValue::Int(2), BindValue::Text("%Smith%".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_contains_escapes_wildcards() {
+        let filter = Filter::new().contains("name", Some("100%_a\\b"));
+
+        assert_eq!(
+            filter.binds(),
+            &[BindValue::Text("%100\\%\\_a\\\\b%".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_range_filters() {
+        let cutoff = Utc::now();
+        let filter = Filter::new()
+            .gt("due_at", Some(cutoff))
+            .lt::<DateTime<Utc>>("due_at", None);
+
+        assert_eq!(filter.where_clause(), " WHERE due_at > ?");
+        assert_eq!(filter.binds(), &[BindValue::DateTime(cutoff)]);
+    }
+
+    #[test]
+    fn test_data_and_count_share_filter() {
+        let sort = Sort::parse("-created_at", FIELDS).unwrap();
+        let select = SelectQuery::new("tasks", "id, title")
+            .filter(Filter::new().eq("assigned_to", Some(7)))
+            .order_by(sort)
+            .limit(Some(10))
+            .offset(20);
+
+        assert_eq!(
+            select.sql(),
+            "SELECT id, title, created_at AS sort_key_0, id AS sort_key_1 FROM tasks WHERE assigned_to = ? ORDER BY created_at DESC, id ASC LIMIT 10 OFFSET 20"
+        );
+        assert_eq!(
+            select.count_sql(),
+            "SELECT COUNT(*) FROM tasks WHERE assigned_to = ?"
+        );
+    }
+
+    #[test]
+    fn test_offset_without_limit() {
+        let select = SelectQuery::new("objects", "id").offset(5);
+
+        assert_eq!(
+            select.sql(),
+            "SELECT id FROM objects LIMIT 9223372036854775807 OFFSET 5"
+        );
+    }
+
+    #[test]
+    fn test_sort_parse_multiple_fields() {
+        let sort = Sort::parse("-priority_level, created_at", FIELDS).unwrap();
+
+        assert_eq!(
+            sort.order_by(),
+            "CASE priority_level WHEN 'High' THEN 3 ELSE 0 END DESC, created_at ASC, id ASC"
+        );
+    }
+
+    #[test]
+    fn test_sort_keeps_explicit_id() {
+        let sort = Sort::parse("-id", FIELDS).unwrap();
+
+        assert_eq!(sort.order_by(), "id DESC");
+    }
+
+    #[test]
+    fn test_sort_rejects_unknown_field() {
+        let error = Sort::parse("created_at; DROP TABLE tasks", FIELDS).unwrap_err();
+
+        assert_eq!(
+            error,
+            "Cannot sort by 'created_at; DROP TABLE tasks'. Sortable fields: id, created_at, priority_level"
+        );
+    }
+
+    #[test]
+    fn test_sort_rejects_duplicate_field() {
+        assert!(Sort::parse("created_at,-created_at", FIELDS).is_err());
+    }
+
+    #[test]
+    fn test_seek_forward() {
+        let sort = Sort::parse("-created_at", FIELDS).unwrap();
+        let select = SelectQuery::new("tasks", "id")
+            .filter(Filter::new().eq("assigned_to", Some(7)))
+            .order_by(sort)
+            .seek(
+                &[BindValue::Text("2024-01-01".to_string()), BindValue::Int(3)],
+                false,
+            )
+            .unwrap();
+
+        assert_eq!(
+            select.sql(),
+            "SELECT id, created_at AS sort_key_0, id AS sort_key_1 FROM tasks WHERE assigned_to = ? AND ((created_at < ?) OR (created_at = ? AND id > ?)) ORDER BY created_at DESC, id ASC"
+        );
+        assert_eq!(
+            select.count_sql(),
+            "SELECT COUNT(*) FROM tasks WHERE assigned_to = ? AND ((created_at < ?) OR (created_at = ? AND id > ?))"
+        );
+        assert_eq!(
+            select.binds(),
+            &[
+                BindValue::Int(7),
+                BindValue::Text("2024-01-01".to_string()),
+                BindValue::Text("2024-01-01".to_string()),
+                BindValue::Int(3)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_seek_backwards_reverses_sort() {
+        let sort = Sort::parse("-created_at", FIELDS).unwrap();
+        let select = SelectQuery::new("tasks", "id")
+            .order_by(sort)
+            .seek(
+                &[BindValue::Text("2024-01-01".to_string()), BindValue::Int(3)],
+                true,
+            )
+            .unwrap();
+
+        assert_eq!(
+            select.sql(),
+            "SELECT id, created_at AS sort_key_0, id AS sort_key_1 FROM tasks WHERE ((created_at > ?) OR (created_at = ? AND id < ?)) ORDER BY created_at ASC, id DESC"
+        );
+    }
+
+    #[test]
+    fn test_seek_rejects_mismatched_values() {
+        let sort = Sort::parse("-created_at", FIELDS).unwrap();
+        let result = SelectQuery::new("tasks", "id")
+            .order_by(sort)
+            .seek(&[BindValue::Int(3)], false);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_sort_signature() {
+        let sort = Sort::parse("-priority_level,created_at", FIELDS).unwrap();
+
+        assert_eq!(sort.signature(), "-priority_level,created_at,id");
+        assert_eq!(
+            sort.reversed().signature(),
+            "priority_level,-created_at,-id"
+        );
+    }
+}
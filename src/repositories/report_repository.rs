@@ -0,0 +1,203 @@
+use crate::database::{self, Backend, DbPool, DbRow};
+use crate::errors::ApiError;
+use crate::models::report::*;
+use crate::models::task::{PriorityLevel, TaskStatus};
+use crate::repositories::query_builder::{BindValues, Filter};
+use chrono::Utc;
+
+#[derive(Clone)]
+pub struct ReportRepository {
+    pool: DbPool,
+}
+
+impl ReportRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Task counts, throughput and cycle times over live tasks, overall and
+    /// per group.
+    pub async fn task_report(&self, query: &TaskReportQuery) -> Result<TaskReport, ApiError> {
+        let date_field = query.date_field.unwrap_or_default();
+        let date_column = match date_field {
+            ReportDateField::Created => "created_at",
+            ReportDateField::Completed => "completed_at",
+        };
+
+        let mut filter = Filter::new()
+            .condition("deleted_at IS NULL", vec![])
+            .eq("store_id", query.store_id)
+            .eq("assigned_to", query.assigned_to)
+            .eq("priority_level", query.priority_level.map(|p| p.as_str()))
+            .eq("status", query.status.map(|s| s.as_str()));
+        if date_field == ReportDateField::Completed {
+            filter = filter.condition("completed_at IS NOT NULL", vec![]);
+        }
+        if let Some(from) = query.from {
+            filter = filter.condition(format!("{} >= ?", date_column), vec![from.into()]);
+        }
+        if let Some(to) = query.to {
+            filter = filter.condition(format!("{} < ?", date_column), vec![to.into()]);
+        }
+
+        let backend = self.pool.backend();
+        let group = match (query.group_by, backend) {
+            (None, _) => "NULL".to_string(),
+            (Some(ReportGrouping::Status), _) => "status".to_string(),
+            (Some(ReportGrouping::Priority), _) => "priority_level".to_string(),
+            (Some(ReportGrouping::Assignee), _) => "assigned_to".to_string(),
+            (Some(ReportGrouping::Store), _) => "store_id".to_string(),
+            (Some(ReportGrouping::Day), Backend::Sqlite) => format!("date({})", date_column),
+            (Some(ReportGrouping::Day), Backend::Postgres) => {
+                format!("to_char({}, 'YYYY-MM-DD')", date_column)
+            }
+            (Some(ReportGrouping::Week), Backend::Sqlite) => {
+                format!("date({}, 'weekday 0', '-6 days')", date_column)
+            }
+            (Some(ReportGrouping::Week), Backend::Postgres) => {
+                format!("to_char(date_trunc('week', {}), 'YYYY-MM-DD')", date_column)
+            }
+            (Some(ReportGrouping::Month), Backend::Sqlite) => {
+                format!("strftime('%Y-%m', {})", date_column)
+            }
+            (Some(ReportGrouping::Month), Backend::Postgres) => {
+                format!("to_char({}, 'YYYY-MM')", date_column)
+            }
+        };
+
+        let totals = self
+            .report_rows("NULL", &filter, None)
+            .await?
+            .pop()
+            .unwrap_or_else(empty_row);
+        let groups = match query.group_by {
+            Some(grouping) => self.report_rows(&group, &filter, Some(grouping)).await?,
+            None => Vec::new(),
+        };
+
+        Ok(TaskReport {
+            group_by: query.group_by,
+            date_field,
+            from: query.from,
+            to: query.to,
+            totals,
+            groups,
+        })
+    }
+
+    /// One row per distinct value of `group`. The p90 cycle time takes the
+    /// nearest rank, the `ceil(0.9 * n)`th smallest, within each group.
+    async fn report_rows(
+        &self,
+        group: &str,
+        filter: &Filter,
+        grouping: Option<ReportGrouping>,
+    ) -> Result<Vec<TaskReportRow>, ApiError> {
+        let cycle_hours = match self.pool.backend() {
+            Backend::Sqlite => "(julianday(completed_at) - julianday(created_at)) * 24",
+            Backend::Postgres => {
+                "CAST(EXTRACT(EPOCH FROM completed_at - created_at) / 3600 AS DOUBLE PRECISION)"
+            }
+        };
+        let sql = format!(
+            r#"
+            WITH filtered AS (
+                SELECT {group} AS group_key, status AS status_key, due_at,
+                       CASE WHEN status = 'Done' AND completed_at IS NOT NULL
+                            THEN {cycle_hours}
+                       END AS cycle_hours
+                FROM tasks{where_clause}
+            ),
+            ranked AS (
+                SELECT group_key, cycle_hours,
+                       ROW_NUMBER() OVER (PARTITION BY group_key ORDER BY cycle_hours) AS cycle_rank,
+                       COUNT(*) OVER (PARTITION BY group_key) AS cycle_count
+                FROM filtered
+                WHERE cycle_hours IS NOT NULL
+            ),
+            p90 AS (
+                SELECT group_key, cycle_hours
+                FROM ranked
+                WHERE cycle_rank = (cycle_count * 9 + 9) / 10
+            )
+            SELECT f.group_key,
+                   COUNT(*) AS total,
+                   COUNT(*) FILTER (WHERE f.status_key NOT IN ('Done', 'Cancelled')) AS open,
+                   COUNT(*) FILTER (WHERE f.status_key = 'Done') AS completed,
+                   COUNT(*) FILTER (WHERE f.status_key = 'Cancelled') AS cancelled,
+                   COUNT(*) FILTER (WHERE f.status_key NOT IN ('Done', 'Cancelled') AND f.due_at < ?) AS overdue,
+                   AVG(f.cycle_hours) AS mean_cycle_time_hours,
+                   (SELECT p.cycle_hours FROM p90 p WHERE p.group_key IS NOT DISTINCT FROM f.group_key) AS p90_cycle_time_hours
+            FROM filtered f
+            GROUP BY f.group_key
+            ORDER BY f.group_key IS NULL, f.group_key
+            "#,
+            where_clause = filter.where_clause()
+        );
+
+        let rows = database::query(&sql)
+            .bind_values(filter.binds())
+            .bind(Utc::now())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(ApiError::from)?;
+
+        let mut rows = rows
+            .iter()
+            .map(|row| report_row(row, grouping))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ApiError::InternalServerError(format!("Invalid task data: {}", e)))?;
+        if grouping == Some(ReportGrouping::Priority) {
+            rows.sort_by_key(|row| match row.key {
+                Some(ReportKey::Priority(PriorityLevel::High)) => 0,
+                Some(ReportKey::Priority(PriorityLevel::Medium)) => 1,
+                Some(ReportKey::Priority(PriorityLevel::Low)) => 2,
+                _ => 3,
+            });
+        }
+
+        Ok(rows)
+    }
+}
+
+fn report_row(row: &DbRow, grouping: Option<ReportGrouping>) -> Result<TaskReportRow, sqlx::Error> {
+    let key = match grouping {
+        None => None,
+        Some(ReportGrouping::Status) => row
+            .try_get::<Option<TaskStatus>, _>("group_key")?
+            .map(ReportKey::Status),
+        Some(ReportGrouping::Priority) => row
+            .try_get::<Option<PriorityLevel>, _>("group_key")?
+            .map(ReportKey::Priority),
+        Some(ReportGrouping::Assignee | ReportGrouping::Store) => row
+            .try_get::<Option<i32>, _>("group_key")?
+            .map(ReportKey::Id),
+        Some(ReportGrouping::Day | ReportGrouping::Week | ReportGrouping::Month) => row
+            .try_get::<Option<String>, _>("group_key")?
+            .map(ReportKey::Period),
+    };
+
+    Ok(TaskReportRow {
+        key,
+        total: row.get("total"),
+        open: row.get("open"),
+        completed: row.get("completed"),
+        cancelled: row.get("cancelled"),
+        overdue: row.get("overdue"),
+        mean_cycle_time_hours: row.get("mean_cycle_time_hours"),
+        p90_cycle_time_hours: row.get("p90_cycle_time_hours"),
+    })
+}
+
+fn empty_row() -> TaskReportRow {
+    TaskReportRow {
+        key: None,
+        total: 0,
+        open: 0,
+        completed: 0,
+        cancelled: 0,
+        overdue: 0,
+        mean_cycle_time_hours: None,
+        p90_cycle_time_hours: None,
+    }
+}
@@ -0,0 +1,40 @@
+use crate::database::{self, DbPool, DbRow};
+use crate::errors::ApiError;
+use crate::repositories::query_builder::{BindValues, SelectQuery};
+use futures_util::{Stream, StreamExt, stream};
+use tokio::sync::mpsc;
+
+const ROW_BUFFER: usize = 64;
+
+/// Runs `select` on a background task and yields mapped rows as the database
+/// produces them. The bounded channel applies backpressure, so a slow reader only ever
+/// holds a handful of rows in memory, and dropping the stream stops the query.
+pub fn stream_rows<T, F>(
+    pool: DbPool,
+    select: SelectQuery,
+    map: F,
+) -> impl Stream<Item = Result<T, ApiError>> + 'static
+where
+    T: Send + 'static,
+    F: Fn(DbRow) -> Result<T, sqlx::Error> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel(ROW_BUFFER);
+
+    tokio::spawn(async move {
+        let sql = select.sql();
+        let mut query = database::query(&sql).bind_values(select.binds());
+        let mut rows = query.fetch(&pool);
+        while let Some(row) = rows.next().await {
+            let item = row.and_then(&map).map_err(ApiError::from);
+            let failed = item.is_err();
+
+            if sender.send(item).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|item| (item, receiver))
+    })
+}
@@ -1,224 +1,2254 @@
+use crate::database::{self, DbExecutor, DbPool, DbRow, DbTransaction};
 use crate::errors::ApiError;
+use crate::models::auth::Actor;
+use crate::models::tag::{MAX_TAGS_PER_TASK, TagMatch, TagUsage};
 use crate::models::task::*;
-use chrono::Utc;
-use sqlx::{Row, SqlitePool};
+use crate::models::task_board::{Board, BoardQuery, LaneKey, MoveTaskRequest, Swimlane};
+use crate::models::task_bulk::{BulkAction, BulkItemResult, BulkTaskReport};
+use crate::models::task_dependency::{TaskDependencies, TaskNode};
+use crate::models::task_event::{FieldChange, TaskChanges, TaskEvent, TaskEventType, diff_tasks};
+use crate::repositories::AssignmentRepository;
+use crate::repositories::keyset::{PageRequest, fetch_page};
+use crate::repositories::query_builder::{
+    BindValue, BindValues, Filter, SelectQuery, Sort, SortField,
+};
+use crate::repositories::row_stream::stream_rows;
+use crate::utils::pagination::Page;
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use sqlx::types::Json;
+use std::collections::HashMap;
+
+const DEFAULT_TASK_SORT: &str = "-created_at";
+
+static TASK_SORT_FIELDS: &[SortField] = &[
+    SortField {
+        name: "id",
+        expression: "id",
+    },
+    SortField {
+        name: "title",
+        expression: "title",
+    },
+    SortField {
+        name: "priority_level",
+        expression: "CASE priority_level WHEN 'Low' THEN 1 WHEN 'Medium' THEN 2 WHEN 'High' THEN 3 ELSE 0 END",
+    },
+    SortField {
+        name: "status",
+        expression: "status",
+    },
+    SortField {
+        name: "assigned_to",
+        expression: "COALESCE(assigned_to, 0)",
+    },
+    SortField {
+        name: "store_id",
+        expression: "COALESCE(store_id, 0)",
+    },
+    SortField {
+        name: "completed_at",
+        expression: "COALESCE(completed_at, '0001-01-01')",
+    },
+    SortField {
+        name: "start_at",
+        expression: "COALESCE(start_at, '0001-01-01')",
+    },
+    SortField {
+        name: "due_at",
+        expression: "COALESCE(due_at, '9999-12-31')",
+    },
+    SortField {
+        name: "created_at",
+        expression: "created_at",
+    },
+    SortField {
+        name: "updated_at",
+        expression: "updated_at",
+    },
+];
+
+const TASK_COLUMNS: &str = "id, title, description, priority_level, status, assigned_to, store_id, parent_id, status_reason, start_at, due_at, completed_at, created_at, updated_at, deleted_at";
 
 #[derive(Clone)]
 pub struct TaskRepository {
-    pool: SqlitePool,
+    pool: DbPool,
 }
 
 impl TaskRepository {
-    pub fn new(pool: SqlitePool) -> Self {
+    pub fn new(pool: DbPool) -> Self {
         Self { pool }
     }
 
-    pub async fn create(&self, req: CreateTaskRequest) -> Result<Task, ApiError> {
+    /// A task with a store but no assignee goes to whoever the store's
+    /// strategy picks. The pick shares the insert's transaction, so a failed
+    /// create leaves the round-robin rotation where it was.
+    pub async fn create(&self, req: CreateTaskRequest, actor: &Actor) -> Result<Task, ApiError> {
+        let mut req = req;
+        let mut tx = self.begin().await?;
+        if req.assigned_to.is_none()
+            && let Some(store_id) = req.store_id
+        {
+            req.assigned_to = AssignmentRepository::pick(&mut tx, store_id).await?;
+        }
+        let task = Self::insert(&mut tx, req, actor).await?;
+
+        Self::commit(tx).await?;
+        Ok(task)
+    }
+
+    /// Inserts a task and its `Created` event on an existing connection, so
+    /// callers can make task creation part of a larger transaction.
+    pub(crate) async fn insert(
+        conn: &mut DbTransaction,
+        req: CreateTaskRequest,
+        actor: &Actor,
+    ) -> Result<Task, ApiError> {
         let now = Utc::now();
+        let completed_at = (req.status == Some(TaskStatus::Done)).then_some(now);
+        if let Some(parent_id) = req.parent_id {
+            Self::ensure_exists(conn, parent_id, "Parent task").await?;
+        }
+        if let Some(employee_id) = req.assigned_to {
+            Self::ensure_assignable(conn, employee_id).await?;
+        }
 
-        let result = sqlx::query(
+        let id: i32 = database::query_scalar(
             r#"
-            INSERT INTO tasks (title, description, priority_level, status, assigned_to, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO tasks (title, description, priority_level, status, assigned_to, store_id, parent_id, start_at, due_at, completed_at, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING id
             "#,
         )
         .bind(&req.title)
         .bind(&req.description)
-        .bind(req.priority_level.map(|p| p.to_string()))
-        .bind(req.status.map(|s| s.to_string()).unwrap_or("ToDo".to_string()))
+        .bind(req.priority_level)
+        .bind(req.status.unwrap_or(TaskStatus::ToDo))
         .bind(req.assigned_to)
+        .bind(req.store_id)
+        .bind(req.parent_id)
+        .bind(req.start_at)
+        .bind(req.due_at)
+        .bind(completed_at)
         .bind(now)
         .bind(now)
-        .execute(&self.pool)
+        .fetch_one(&mut *conn)
         .await
-        .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?;
+        .map_err(ApiError::from)?;
 
-        let id = result.last_insert_rowid() as i32;
-        Ok(Task::new(
-            id,
-            req.title,
-            req.description,
-            req.priority_level,
-            Some(req.status.clone().unwrap_or(TaskStatus::ToDo)),
-            req.assigned_to,
-        ))
+        Self::append_to_column(conn, id, req.status.unwrap_or(TaskStatus::ToDo)).await?;
+
+        let task = Self::load(&mut *conn, id).await?;
+        Self::record_event(
+            conn,
+            task.id,
+            TaskEventType::Created,
+            actor,
+            diff_tasks(None, &task),
+        )
+        .await?;
+
+        Ok(task)
     }
 
     pub async fn find_by_id(&self, id: i32) -> Result<Task, ApiError> {
-        let row = sqlx::query(
+        Self::load(&self.pool, id).await
+    }
+
+    pub async fn find_by_id_including_deleted(&self, id: i32) -> Result<Task, ApiError> {
+        Self::load_any(&self.pool, id).await
+    }
+
+    /// Loads a task that has not been deleted; deleted tasks are reported as
+    /// missing.
+    async fn load<'c>(executor: impl Into<DbExecutor<'c>>, id: i32) -> Result<Task, ApiError> {
+        let task = Self::load_any(executor, id).await?;
+        if task.deleted_at.is_some() {
+            return Err(ApiError::NotFound("Task not found".to_string()));
+        }
+
+        Ok(task)
+    }
+
+    async fn load_any<'c>(executor: impl Into<DbExecutor<'c>>, id: i32) -> Result<Task, ApiError> {
+        let row = database::query(&format!("SELECT {} FROM tasks WHERE id = ?", TASK_COLUMNS))
+            .bind(id)
+            .fetch_optional(executor)
+            .await
+            .map_err(ApiError::from)?
+            .ok_or_else(|| ApiError::NotFound("Task not found".to_string()))?;
+
+        Self::task_from_row(&row)
+    }
+
+    /// Decodes a task row. Statuses and priorities must be stored as their
+    /// variant names; anything else is an error rather than a missing value.
+    fn task_from_row(row: &DbRow) -> Result<Task, ApiError> {
+        row.decode::<Task>().map_err(|e| {
+            ApiError::InternalServerError(format!(
+                "Invalid data in task {}: {}",
+                row.get::<i32, _>("id"),
+                e
+            ))
+        })
+    }
+
+    pub async fn find_all(&self, query: TaskQuery) -> Result<Page<Task>, ApiError> {
+        let select = Self::select(&query)?;
+        let page = PageRequest::new(query.limit, query.offset, query.cursor, query.include_total)?;
+
+        fetch_page(&self.pool, select, page).await
+    }
+
+    /// Streams every task matching the query's filters. The default page size
+    /// does not apply; `limit`/`offset` are honoured only when given.
+    pub fn export(
+        &self,
+        query: TaskQuery,
+    ) -> Result<impl Stream<Item = Result<Task, ApiError>> + 'static, ApiError> {
+        let select = Self::select(&query)?
+            .limit(query.limit)
+            .offset(query.offset.unwrap_or(0));
+
+        Ok(stream_rows(self.pool.clone(), select, |row| {
+            row.decode::<Task>()
+        }))
+    }
+
+    /// Ids of the tasks matching a query's filters, in its sort order, up to
+    /// `limit`. Paging fields in the query are ignored.
+    pub async fn find_ids(&self, query: &TaskQuery, limit: usize) -> Result<Vec<i32>, ApiError> {
+        let select = Self::select_columns(query, "id")?.limit(Some(limit));
+        let sql = select.sql();
+
+        database::query_scalar::<i32>(&sql)
+            .bind_values(select.binds())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(ApiError::from)
+    }
+
+    fn select(query: &TaskQuery) -> Result<SelectQuery, ApiError> {
+        Self::select_columns(query, TASK_COLUMNS)
+    }
+
+    fn select_columns(query: &TaskQuery, columns: &'static str) -> Result<SelectQuery, ApiError> {
+        let filter = Self::filter(query)?;
+        let sort_spec = query.sort_spec().map_err(ApiError::ValidationError)?;
+        let sort = Sort::parse(
+            sort_spec.as_deref().unwrap_or(DEFAULT_TASK_SORT),
+            TASK_SORT_FIELDS,
+        )
+        .map_err(ApiError::ValidationError)?;
+
+        Ok(SelectQuery::new("tasks", columns)
+            .filter(filter)
+            .order_by(sort))
+    }
+
+    /// The conditions a query's filter fields put on task rows.
+    fn filter(query: &TaskQuery) -> Result<Filter, ApiError> {
+        let filter = Filter::new()
+            .contains("title", query.title.as_deref())
+            .eq("status", query.status.map(|s| s.as_str()))
+            .eq("priority_level", query.priority_level.map(|p| p.as_str()))
+            .eq("assigned_to", query.assigned_to)
+            .eq("store_id", query.store_id)
+            .eq("parent_id", query.parent_id)
+            .lt("due_at", query.due_before)
+            .gt("due_at", query.due_after);
+
+        let filter = if query.include_deleted.unwrap_or(false) {
+            filter
+        } else {
+            filter.condition("deleted_at IS NULL", vec![])
+        };
+
+        let filter = match query.overdue {
+            Some(true) => filter.condition(
+                "due_at < ? AND status NOT IN ('Done', 'Cancelled')",
+                vec![Utc::now().into()],
+            ),
+            Some(false) => filter.condition(
+                "(due_at IS NULL OR due_at >= ? OR status IN ('Done', 'Cancelled'))",
+                vec![Utc::now().into()],
+            ),
+            None => filter,
+        };
+
+        let tags = query.tag_filter().map_err(ApiError::ValidationError)?;
+        let filter = if tags.is_empty() {
+            filter
+        } else {
+            let placeholders = vec!["?"; tags.len()].join(", ");
+            let count = tags.len() as i64;
+            let mut binds: Vec<BindValue> = tags.into_iter().map(BindValue::from).collect();
+            match query.tag_match.unwrap_or_default() {
+                TagMatch::Any => filter.condition(
+                    format!(
+                        "id IN (SELECT task_id FROM task_tags WHERE tag IN ({}))",
+                        placeholders
+                    ),
+                    binds,
+                ),
+                TagMatch::All => {
+                    binds.push(count.into());
+                    filter.condition(
+                        format!(
+                            "id IN (SELECT task_id FROM task_tags WHERE tag IN ({}) GROUP BY task_id HAVING COUNT(*) = ?)",
+                            placeholders
+                        ),
+                        binds,
+                    )
+                }
+            }
+        };
+
+        Ok(filter)
+    }
+
+    pub async fn update(
+        &self,
+        id: i32,
+        req: UpdateTaskRequest,
+        actor: &Actor,
+    ) -> Result<Task, ApiError> {
+        let mut tx = self.begin().await?;
+        let existing_task = Self::load(&mut tx, id).await?;
+        let updated_title = req.title.unwrap_or_else(|| existing_task.title.clone());
+        let updated_description = req.description.or(existing_task.description.clone());
+        let updated_priority_level = req.priority_level.or(existing_task.priority_level);
+        let updated_assigned_to = req.assigned_to.or(existing_task.assigned_to);
+        if let Some(employee_id) = req.assigned_to
+            && Some(employee_id) != existing_task.assigned_to
+        {
+            Self::ensure_assignable(&mut tx, employee_id).await?;
+        }
+        let updated_start_at = req.start_at.or(existing_task.start_at);
+        let updated_due_at = req.due_at.or(existing_task.due_at);
+        validate_schedule(updated_start_at, updated_due_at).map_err(ApiError::ValidationError)?;
+        let updated_parent_id = match req.parent_id {
+            Some(parent_id) if Some(parent_id) != existing_task.parent_id => {
+                Self::check_parent(&mut tx, id, parent_id).await?;
+                Some(parent_id)
+            }
+            _ => existing_task.parent_id,
+        };
+        let current_status = existing_task.status.unwrap_or(TaskStatus::ToDo);
+        let now = Utc::now();
+
+        let (updated_status, updated_status_reason, updated_completed_at) = match req.status {
+            Some(status) if status != current_status => {
+                current_status
+                    .check_transition(status, None)
+                    .map_err(ApiError::ValidationError)?;
+                Self::ensure_unblocked(&mut tx, id, status).await?;
+                (
+                    status,
+                    None,
+                    completed_at_after(current_status, status, existing_task.completed_at, now),
+                )
+            }
+            _ => (
+                current_status,
+                existing_task.status_reason.clone(),
+                existing_task.completed_at,
+            ),
+        };
+
+        database::query(
             r#"
-            SELECT id, title, description, priority_level, status, assigned_to, completed_at, created_at, updated_at, deleted_at
-            FROM tasks
+            UPDATE tasks
+            SET title = ?, description = ?, priority_level = ?, status = ?, assigned_to = ?, parent_id = ?, status_reason = ?, start_at = ?, due_at = ?, completed_at = ?, updated_at = ?
             WHERE id = ?
             "#,
         )
+        .bind(&updated_title)
+        .bind(&updated_description)
+        .bind(updated_priority_level)
+        .bind(updated_status)
+        .bind(updated_assigned_to)
+        .bind(updated_parent_id)
+        .bind(updated_status_reason)
+        .bind(updated_start_at)
+        .bind(updated_due_at)
+        .bind(updated_completed_at)
+        .bind(now)
         .bind(id)
-        .fetch_optional(&self.pool)
-        .await
-        .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?
-        .ok_or_else(|| ApiError::NotFound("Task not found".to_string()))?;
-
-        let task = Task {
-            id: row.get("id"),
-            title: row.get("title"),
-            description: row.get("description"),
-            priority_level: row
-                .get::<Option<String>, _>("priority_level")
-                .and_then(|s| match s.as_str() {
-                    "Low" => Some(PriorityLevel::Low),
-                    "Medium" => Some(PriorityLevel::Medium),
-                    "High" => Some(PriorityLevel::High),
-                    _ => None,
-                }),
-            status: row
-                .get::<Option<String>, _>("status")
-                .and_then(|s| match s.as_str() {
-                    "ToDo" => Some(TaskStatus::ToDo),
-                    "InProgress" => Some(TaskStatus::InProgress),
-                    "Done" => Some(TaskStatus::Done),
-                    _ => None,
-                }),
-            assigned_to: row.get("assigned_to"),
-            completed_at: row.get("completed_at"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-            deleted_at: row.get("deleted_at"),
-        };
+        .execute(&mut tx)
+        .await
+        .map_err(ApiError::from)?;
+        if updated_status != current_status {
+            Self::append_to_column(&mut tx, id, updated_status).await?;
+        }
+
+        let task = Self::load(&mut tx, id).await?;
+        Self::record_event(
+            &mut tx,
+            id,
+            TaskEventType::Updated,
+            actor,
+            diff_tasks(Some(&existing_task), &task),
+        )
+        .await?;
 
+        Self::commit(tx).await?;
         Ok(task)
     }
 
-    pub async fn find_all(&self, query: TaskQuery) -> Result<(Vec<Task>, usize), ApiError> {
-        let mut sql = String::from(
-            "SELECT id, title, description, priority_level, status, assigned_to, completed_at, created_at, updated_at, deleted_at FROM tasks",
-        );
-        let mut count_sql = String::from("SELECT COUNT(*) FROM tasks");
-        let mut conditions = Vec::new();
-        let mut params: Vec<String> = Vec::new();
+    /// Moves a task to `req.status` if the transition table allows it,
+    /// recording the reason alongside the new status.
+    pub async fn transition(
+        &self,
+        id: i32,
+        req: TransitionTaskRequest,
+        actor: &Actor,
+    ) -> Result<Task, ApiError> {
+        let mut tx = self.begin().await?;
+        let task = Self::apply_transition(&mut tx, id, req, actor).await?;
 
-        if let Some(title) = &query.title {
-            conditions.push("title LIKE ?");
-            params.push(format!("%{}%", title));
-        }
+        Self::commit(tx).await?;
+        Ok(task)
+    }
 
-        if let Some(status) = &query.status {
-            conditions.push("status = ?");
-            params.push(status.to_string());
-        }
+    async fn apply_transition(
+        conn: &mut DbTransaction,
+        id: i32,
+        req: TransitionTaskRequest,
+        actor: &Actor,
+    ) -> Result<Task, ApiError> {
+        let existing_task = Self::load(&mut *conn, id).await?;
+        let current_status = existing_task.status.unwrap_or(TaskStatus::ToDo);
+        let reason = req
+            .reason
+            .map(|reason| reason.trim().to_string())
+            .filter(|reason| !reason.is_empty());
 
-        if let Some(priority_level) = &query.priority_level {
-            conditions.push("priority_level = ?");
-            params.push(priority_level.to_string());
-        }
+        current_status
+            .check_transition(req.status, reason.as_deref())
+            .map_err(ApiError::ValidationError)?;
+        Self::ensure_unblocked(conn, id, req.status).await?;
 
-        if let Some(assigned_to) = query.assigned_to {
-            conditions.push("assigned_to = ?");
-            params.push(assigned_to.to_string());
-        }
+        let now = Utc::now();
+        database::query(
+            r#"
+            UPDATE tasks
+            SET status = ?, status_reason = ?, completed_at = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(req.status)
+        .bind(&reason)
+        .bind(completed_at_after(
+            current_status,
+            req.status,
+            existing_task.completed_at,
+            now,
+        ))
+        .bind(now)
+        .bind(id)
+        .execute(&mut *conn)
+        .await
+        .map_err(ApiError::from)?;
+        Self::append_to_column(conn, id, req.status).await?;
+
+        let task = Self::load(&mut *conn, id).await?;
+        Self::record_event(
+            conn,
+            id,
+            TaskEventType::StatusChanged,
+            actor,
+            diff_tasks(Some(&existing_task), &task),
+        )
+        .await?;
 
-        if !conditions.is_empty() {
-            let where_clause = conditions.join(" AND ");
-            sql.push_str(&format!(" WHERE {}", where_clause));
-            count_sql.push_str(&format!(" WHERE {}", where_clause));
+        Ok(task)
+    }
+
+    pub async fn delete(&self, id: i32, actor: &Actor) -> Result<(), ApiError> {
+        let mut tx = self.begin().await?;
+        Self::soft_delete(&mut tx, id, actor).await?;
+
+        Self::commit(tx).await
+    }
+
+    async fn soft_delete(conn: &mut DbTransaction, id: i32, actor: &Actor) -> Result<(), ApiError> {
+        let existing_task = Self::load(&mut *conn, id).await?;
+        let now = Utc::now();
+
+        database::query("UPDATE tasks SET deleted_at = ?, updated_at = ? WHERE id = ?")
+            .bind(now)
+            .bind(now)
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(ApiError::from)?;
+
+        let task = Self::load_any(&mut *conn, id).await?;
+        Self::record_event(
+            conn,
+            id,
+            TaskEventType::Deleted,
+            actor,
+            diff_tasks(Some(&existing_task), &task),
+        )
+        .await
+    }
+
+    /// Applies `action` to each task in a single transaction, reporting the
+    /// outcome per task. The changes are kept only if every task succeeded
+    /// and this is not a dry run.
+    pub async fn bulk(
+        &self,
+        ids: &[i32],
+        action: &BulkAction,
+        dry_run: bool,
+        actor: &Actor,
+    ) -> Result<BulkTaskReport, ApiError> {
+        let mut tx = self.begin().await?;
+        let mut results = Vec::with_capacity(ids.len());
+
+        for &id in ids {
+            let outcome = match action {
+                BulkAction::UpdateStatus { status, reason } => {
+                    let req = TransitionTaskRequest {
+                        status: *status,
+                        reason: reason.clone(),
+                    };
+                    Self::apply_transition(&mut tx, id, req, actor)
+                        .await
+                        .map(Some)
+                }
+                BulkAction::Assign { employee_id } => {
+                    Self::apply_assignee(&mut tx, id, *employee_id, actor)
+                        .await
+                        .map(Some)
+                }
+                BulkAction::Tag { add, remove } => {
+                    Self::retag(&mut tx, id, add, remove, actor).await.map(Some)
+                }
+                BulkAction::Delete => Self::soft_delete(&mut tx, id, actor).await.map(|_| None),
+            };
+
+            results.push(match outcome {
+                Ok(task) => BulkItemResult {
+                    id,
+                    success: true,
+                    error: None,
+                    task,
+                },
+                Err(e) => BulkItemResult {
+                    id,
+                    success: false,
+                    error: Some(e.to_string()),
+                    task: None,
+                },
+            });
         }
 
-        if let Some(sort_by) = &query.sort_by {
-            let direction = query.sort_direction.as_deref().unwrap_or("asc");
-            sql.push_str(&format!(" ORDER BY {} {}", sort_by, direction));
+        let failed = results.iter().filter(|result| !result.success).count();
+        let applied = failed == 0 && !dry_run;
+        if applied {
+            Self::commit(tx).await?;
         } else {
-            sql.push_str(" ORDER BY created_at DESC");
+            tx.rollback()
+                .await
+                .map_err(|e| ApiError::InternalServerError(format!("Transaction error: {}", e)))?;
         }
 
-        if let Some(limit) = query.limit {
-            sql.push_str(&format!(" LIMIT {}", limit));
-        } else {
-            sql.push_str(" LIMIT 10");
+        Ok(BulkTaskReport {
+            dry_run,
+            applied,
+            succeeded: results.len() - failed,
+            failed,
+            results,
+        })
+    }
+
+    pub async fn restore(&self, id: i32, actor: &Actor) -> Result<Task, ApiError> {
+        let mut tx = self.begin().await?;
+        let existing_task = Self::load_any(&mut tx, id).await?;
+        if existing_task.deleted_at.is_none() {
+            return Err(ApiError::ValidationError("Task is not deleted".to_string()));
         }
 
-        if let Some(offset) = query.offset {
-            sql.push_str(&format!(" OFFSET {}", offset));
-        } else {
-            sql.push_str(" OFFSET 0");
+        database::query("UPDATE tasks SET deleted_at = NULL, updated_at = ? WHERE id = ?")
+            .bind(Utc::now())
+            .bind(id)
+            .execute(&mut tx)
+            .await
+            .map_err(ApiError::from)?;
+
+        let task = Self::load(&mut tx, id).await?;
+        Self::record_event(
+            &mut tx,
+            id,
+            TaskEventType::Restored,
+            actor,
+            diff_tasks(Some(&existing_task), &task),
+        )
+        .await?;
+
+        Self::commit(tx).await?;
+        Ok(task)
+    }
+
+    /// Permanently removes tasks deleted before `deleted_before`, along with
+    /// their history, comments and dependencies. Live subtasks of a purged
+    /// task are detached rather than removed, and template occurrences stay
+    /// claimed so the scheduler does not recreate them. Attachment contents
+    /// are left in storage; the purge reports their hashes so the caller can
+    /// remove them once committed.
+    pub async fn purge_deleted(
+        &self,
+        deleted_before: DateTime<Utc>,
+    ) -> Result<PurgedTasks, ApiError> {
+        // Each statement takes the purged ids as its `{}` list.
+        const STATEMENTS: &[&str] = &[
+            "DELETE FROM task_comment_revisions WHERE comment_id IN (SELECT id FROM task_comments WHERE task_id IN ({}))",
+            "DELETE FROM task_comment_mentions WHERE comment_id IN (SELECT id FROM task_comments WHERE task_id IN ({}))",
+            "DELETE FROM task_comments WHERE task_id IN ({})",
+            "DELETE FROM task_events WHERE task_id IN ({})",
+            "DELETE FROM task_dependencies WHERE task_id IN ({})",
+            "DELETE FROM task_dependencies WHERE depends_on_id IN ({})",
+            "DELETE FROM task_tags WHERE task_id IN ({})",
+            "DELETE FROM task_attachments WHERE task_id IN ({})",
+            "UPDATE tasks SET parent_id = NULL WHERE parent_id IN ({})",
+            "UPDATE task_template_occurrences SET task_id = NULL WHERE task_id IN ({})",
+            "DELETE FROM tasks WHERE id IN ({})",
+        ];
+
+        let mut tx = self.begin().await?;
+        let ids = database::query_scalar::<i32>(
+            "SELECT id FROM tasks WHERE deleted_at IS NOT NULL AND deleted_at < ?",
+        )
+        .bind(deleted_before)
+        .fetch_all(&mut tx)
+        .await
+        .map_err(ApiError::from)?;
+
+        let mut attachment_sha256s = Vec::new();
+        for chunk in ids.chunks(500) {
+            let placeholders = vec!["?"; chunk.len()].join(", ");
+            let sql = format!(
+                "SELECT DISTINCT sha256 FROM task_attachments WHERE task_id IN ({})",
+                placeholders
+            );
+            let mut query = database::query_scalar::<String>(&sql);
+            for id in chunk {
+                query = query.bind(id);
+            }
+            attachment_sha256s.extend(query.fetch_all(&mut tx).await.map_err(ApiError::from)?);
+
+            for statement in STATEMENTS {
+                let sql = statement.replace("{}", &placeholders);
+                let mut query = database::query(&sql);
+                for id in chunk {
+                    query = query.bind(id);
+                }
+                query.execute(&mut tx).await.map_err(ApiError::from)?;
+            }
         }
 
-        let mut query = sqlx::query_as::<_, Task>(&sql);
-        for param in &params {
-            query = query.bind(param);
+        Self::commit(tx).await?;
+
+        attachment_sha256s.sort();
+        attachment_sha256s.dedup();
+        Ok(PurgedTasks {
+            count: ids.len() as u64,
+            attachment_sha256s,
+        })
+    }
+
+    pub async fn assign(
+        &self,
+        task_id: i32,
+        employee_id: i32,
+        actor: &Actor,
+    ) -> Result<Task, ApiError> {
+        self.set_assignee(task_id, Some(employee_id), actor).await
+    }
+
+    pub async fn unassign(&self, task_id: i32, actor: &Actor) -> Result<Task, ApiError> {
+        self.set_assignee(task_id, None, actor).await
+    }
+
+    /// Reassigns the task using its store's strategy, picking and assigning
+    /// in one transaction.
+    pub async fn auto_assign(&self, task_id: i32, actor: &Actor) -> Result<Task, ApiError> {
+        let mut tx = self.begin().await?;
+        let store_id = Self::load(&mut tx, task_id)
+            .await?
+            .store_id
+            .ok_or_else(|| {
+                ApiError::ValidationError("Task has no store to assign from".to_string())
+            })?;
+        let employee_id = AssignmentRepository::pick(&mut tx, store_id)
+            .await?
+            .ok_or_else(|| {
+                ApiError::ValidationError(format!(
+                    "Store {} has no assignment strategy or no active employees",
+                    store_id
+                ))
+            })?;
+        let task = Self::apply_assignee(&mut tx, task_id, Some(employee_id), actor).await?;
+
+        Self::commit(tx).await?;
+        Ok(task)
+    }
+
+    async fn set_assignee(
+        &self,
+        task_id: i32,
+        employee_id: Option<i32>,
+        actor: &Actor,
+    ) -> Result<Task, ApiError> {
+        let mut tx = self.begin().await?;
+        let task = Self::apply_assignee(&mut tx, task_id, employee_id, actor).await?;
+
+        Self::commit(tx).await?;
+        Ok(task)
+    }
+
+    async fn apply_assignee(
+        conn: &mut DbTransaction,
+        task_id: i32,
+        employee_id: Option<i32>,
+        actor: &Actor,
+    ) -> Result<Task, ApiError> {
+        let existing_task = Self::load(&mut *conn, task_id).await?;
+        if let Some(employee_id) = employee_id {
+            Self::ensure_assignable(conn, employee_id).await?;
         }
 
-        let tasks = query
-            .fetch_all(&self.pool)
+        database::query("UPDATE tasks SET assigned_to = ?, updated_at = ? WHERE id = ?")
+            .bind(employee_id)
+            .bind(Utc::now())
+            .bind(task_id)
+            .execute(&mut *conn)
             .await
-            .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?;
+            .map_err(ApiError::from)?;
+
+        let task = Self::load(&mut *conn, task_id).await?;
+        Self::record_event(
+            conn,
+            task_id,
+            TaskEventType::Assigned,
+            actor,
+            diff_tasks(Some(&existing_task), &task),
+        )
+        .await?;
+
+        Ok(task)
+    }
+
+    pub async fn dependencies(&self, task_id: i32) -> Result<TaskDependencies, ApiError> {
+        let blocked_by = self
+            .fetch_tasks(
+                "id IN (SELECT depends_on_id FROM task_dependencies WHERE task_id = ?)",
+                task_id,
+            )
+            .await?;
+        let blocking = self
+            .fetch_tasks(
+                "id IN (SELECT task_id FROM task_dependencies WHERE depends_on_id = ?)",
+                task_id,
+            )
+            .await?;
 
-        let mut count_query = sqlx::query_scalar::<_, i64>(&count_sql);
-        for param in &params {
-            count_query = count_query.bind(param);
+        Ok(TaskDependencies {
+            blocked_by,
+            blocking,
+        })
+    }
+
+    /// Makes `task_id` wait for `depends_on`, refusing any dependency that
+    /// would close a cycle.
+    pub async fn add_dependency(
+        &self,
+        task_id: i32,
+        depends_on: i32,
+        actor: &Actor,
+    ) -> Result<TaskDependencies, ApiError> {
+        let mut tx = self.begin().await?;
+        Self::load(&mut tx, task_id).await?;
+        Self::ensure_exists(&mut tx, depends_on, "Dependency").await?;
+
+        if task_id == depends_on {
+            return Err(ApiError::ValidationError(
+                "A task cannot depend on itself".to_string(),
+            ));
         }
 
-        let total = count_query
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?;
+        // Walk everything `depends_on` already waits for; finding `task_id`
+        // there means the new edge would close a loop.
+        let cycle = database::query_scalar::<i32>(
+            r#"
+            WITH RECURSIVE upstream(id) AS (
+                SELECT depends_on_id FROM task_dependencies WHERE task_id = ?
+                UNION
+                SELECT d.depends_on_id FROM task_dependencies d JOIN upstream u ON d.task_id = u.id
+            )
+            SELECT id FROM upstream WHERE id = ?
+            "#,
+        )
+        .bind(depends_on)
+        .bind(task_id)
+        .fetch_optional(&mut tx)
+        .await
+        .map_err(ApiError::from)?;
 
-        Ok((tasks, total as usize))
+        if cycle.is_some() {
+            return Err(ApiError::ValidationError(format!(
+                "Task {} already depends on task {}; adding this dependency would create a cycle",
+                depends_on, task_id
+            )));
+        }
+
+        let before = Self::dependency_ids(&mut tx, task_id).await?;
+        database::query(
+            r#"
+            INSERT INTO task_dependencies (task_id, depends_on_id, created_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(task_id)
+        .bind(depends_on)
+        .bind(Utc::now())
+        .execute(&mut tx)
+        .await
+        .map_err(ApiError::from)?;
+
+        Self::record_dependency_change(&mut tx, task_id, before, actor).await?;
+        Self::commit(tx).await?;
+        self.dependencies(task_id).await
     }
 
-    pub async fn update(&self, id: i32, req: UpdateTaskRequest) -> Result<Task, ApiError> {
-        let existing_task = self.find_by_id(id).await?;
-        let updated_title = req.title.unwrap_or(existing_task.title);
-        let updated_description = req.description.or(existing_task.description);
-        let updated_priority_level = req.priority_level.or(existing_task.priority_level);
-        let updated_status = req.status.or(existing_task.status);
-        let updated_assigned_to = req.assigned_to.or(existing_task.assigned_to);
-        let updated_completed_at = if let Some(TaskStatus::Done) = updated_status {
-            Some(Utc::now())
+    pub async fn remove_dependency(
+        &self,
+        task_id: i32,
+        depends_on: i32,
+        actor: &Actor,
+    ) -> Result<TaskDependencies, ApiError> {
+        let mut tx = self.begin().await?;
+        let before = Self::dependency_ids(&mut tx, task_id).await?;
+
+        let result = database::query(
+            "DELETE FROM task_dependencies WHERE task_id = ? AND depends_on_id = ?",
+        )
+        .bind(task_id)
+        .bind(depends_on)
+        .execute(&mut tx)
+        .await
+        .map_err(ApiError::from)?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApiError::NotFound("Dependency not found".to_string()));
+        }
+
+        Self::record_dependency_change(&mut tx, task_id, before, actor).await?;
+        Self::commit(tx).await?;
+        self.dependencies(task_id).await
+    }
+
+    /// The task with all of its live subtasks, nested, and the open tasks
+    /// each of them is waiting on.
+    pub async fn tree(&self, id: i32, include_deleted: bool) -> Result<TaskNode, ApiError> {
+        let root = if include_deleted {
+            self.find_by_id_including_deleted(id).await?
         } else {
-            None
+            self.find_by_id(id).await?
         };
-        let now = Utc::now();
-        sqlx::query(
+        let descendants = self
+            .fetch_tasks(
+                r#"id IN (
+                    WITH RECURSIVE subtree(id) AS (
+                        SELECT id FROM tasks WHERE parent_id = ?
+                        UNION
+                        SELECT t.id FROM tasks t JOIN subtree s ON t.parent_id = s.id
+                    )
+                    SELECT id FROM subtree
+                )"#,
+                id,
+            )
+            .await?;
+
+        let ids: Vec<i32> = std::iter::once(id)
+            .chain(descendants.iter().map(|task| task.id))
+            .collect();
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        let sql = format!(
             r#"
-            UPDATE tasks
-            SET title = ?, description = ?, priority_level = ?, status = ?, assigned_to = ?, completed_at = ?, updated_at = ?
-            WHERE id = ?
+            SELECT d.task_id, d.depends_on_id
+            FROM task_dependencies d
+            JOIN tasks t ON t.id = d.depends_on_id
+            WHERE d.task_id IN ({}) AND t.deleted_at IS NULL AND t.status NOT IN ('Done', 'Cancelled')
+            ORDER BY d.task_id, d.depends_on_id
             "#,
-        )   .bind(&updated_title)
-            .bind(&updated_description)
-            .bind(updated_priority_level.map(|p| p.to_string()))
-            .bind(updated_status.map(|s| s.to_string()))
-            .bind(updated_assigned_to)
-            .bind(updated_completed_at)
-            .bind(now)
-            .bind(id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?;
-        self.find_by_id(id).await
+            placeholders
+        );
+        let mut query = database::query_as::<(i32, i32)>(&sql);
+        for id in &ids {
+            query = query.bind(id);
+        }
+        let blockers = query.fetch_all(&self.pool).await.map_err(ApiError::from)?;
+
+        Ok(TaskNode::build(root, descendants, &blockers))
     }
 
-    pub async fn delete(&self, id: i32) -> Result<(), ApiError> {
-        self.find_by_id(id).await?;
-        let now = Utc::now();
-        sqlx::query("UPDATE tasks SET deleted_at = ? WHERE id = ?")
+    /// Live tasks matching `condition`, which takes a single `?` bound to
+    /// `id`.
+    async fn fetch_tasks(&self, condition: &str, id: i32) -> Result<Vec<Task>, ApiError> {
+        let sql = format!(
+            "SELECT {} FROM tasks WHERE deleted_at IS NULL AND {} ORDER BY id",
+            TASK_COLUMNS, condition
+        );
+        let rows = database::query(&sql)
             .bind(id)
-            .bind(now)
-            .execute(&self.pool)
+            .fetch_all(&self.pool)
             .await
-            .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?;
+            .map_err(ApiError::from)?;
+
+        rows.iter().map(Self::task_from_row).collect()
+    }
+
+    async fn ensure_exists(conn: &mut DbTransaction, id: i32, what: &str) -> Result<(), ApiError> {
+        let exists = database::query_scalar::<i32>(
+            "SELECT id FROM tasks WHERE id = ? AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(ApiError::from)?;
+
+        match exists {
+            Some(_) => Ok(()),
+            None => Err(ApiError::ValidationError(format!(
+                "{} {} does not exist",
+                what, id
+            ))),
+        }
+    }
+
+    /// Tasks can only be given to employees that exist and are active.
+    async fn ensure_assignable(conn: &mut DbTransaction, employee_id: i32) -> Result<(), ApiError> {
+        let employee = database::query_scalar::<Option<DateTime<Utc>>>(
+            "SELECT deactivated_at FROM employees WHERE id = ?",
+        )
+        .bind(employee_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(ApiError::from)?;
+
+        match employee {
+            None => Err(ApiError::ValidationError(format!(
+                "Employee {} does not exist",
+                employee_id
+            ))),
+            Some(Some(_)) => Err(ApiError::ValidationError(format!(
+                "Employee {} is deactivated",
+                employee_id
+            ))),
+            Some(None) => Ok(()),
+        }
+    }
+
+    /// A task cannot become its own ancestor.
+    async fn check_parent(
+        conn: &mut DbTransaction,
+        id: i32,
+        parent_id: i32,
+    ) -> Result<(), ApiError> {
+        Self::ensure_exists(conn, parent_id, "Parent task").await?;
+
+        let cycle = database::query_scalar::<i32>(
+            r#"
+            WITH RECURSIVE ancestors(id) AS (
+                SELECT ?
+                UNION
+                SELECT t.parent_id FROM tasks t JOIN ancestors a ON t.id = a.id
+                WHERE t.parent_id IS NOT NULL
+            )
+            SELECT id FROM ancestors WHERE id = ?
+            "#,
+        )
+        .bind(parent_id)
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(ApiError::from)?;
+
+        if cycle.is_some() {
+            return Err(ApiError::ValidationError(
+                "A task cannot be moved under itself or one of its subtasks".to_string(),
+            ));
+        }
 
         Ok(())
     }
 
-    pub async fn assign(&self, task_id: i32, employee_id: i32) -> Result<Task, ApiError> {
-        sqlx::query("UPDATE tasks SET assigned_to = ?, updated_at = ? WHERE id = ?")
-            .bind(employee_id)
-            .bind(Utc::now())
-            .bind(task_id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| ApiError::InternalServerError(format!("Database error: {}", e)))?;
+    /// Starting or finishing a task requires every task it depends on to be
+    /// Done or Cancelled.
+    async fn ensure_unblocked(
+        conn: &mut DbTransaction,
+        id: i32,
+        next: TaskStatus,
+    ) -> Result<(), ApiError> {
+        if !matches!(next, TaskStatus::InProgress | TaskStatus::Done) {
+            return Ok(());
+        }
+
+        let open = database::query_scalar::<i32>(
+            r#"
+            SELECT t.id
+            FROM task_dependencies d
+            JOIN tasks t ON t.id = d.depends_on_id
+            WHERE d.task_id = ? AND t.deleted_at IS NULL AND t.status NOT IN ('Done', 'Cancelled')
+            ORDER BY t.id
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(ApiError::from)?;
+
+        if open.is_empty() {
+            return Ok(());
+        }
+
+        let ids: Vec<String> = open.iter().map(i32::to_string).collect();
+        Err(ApiError::ValidationError(format!(
+            "Task is waiting on open tasks: {}",
+            ids.join(", ")
+        )))
+    }
+
+    async fn dependency_ids(conn: &mut DbTransaction, task_id: i32) -> Result<Vec<i32>, ApiError> {
+        database::query_scalar(
+            "SELECT depends_on_id FROM task_dependencies WHERE task_id = ? ORDER BY depends_on_id",
+        )
+        .bind(task_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(ApiError::from)
+    }
+
+    async fn record_dependency_change(
+        conn: &mut DbTransaction,
+        task_id: i32,
+        before: Vec<i32>,
+        actor: &Actor,
+    ) -> Result<(), ApiError> {
+        let after = Self::dependency_ids(conn, task_id).await?;
+        let mut changes = TaskChanges::new();
+        if before != after {
+            changes.insert(
+                "depends_on".to_string(),
+                FieldChange {
+                    from: before.into(),
+                    to: after.into(),
+                },
+            );
+        }
+
+        Self::record_event(conn, task_id, TaskEventType::Updated, actor, changes).await
+    }
+
+    /// Replaces a task's tags, recording the change in its history.
+    pub async fn set_tags(
+        &self,
+        task_id: i32,
+        tags: Vec<String>,
+        actor: &Actor,
+    ) -> Result<Task, ApiError> {
+        let mut tx = self.begin().await?;
+        let task = Self::replace_tags(&mut tx, task_id, tags, actor).await?;
+
+        Self::commit(tx).await?;
+        Ok(task)
+    }
+
+    async fn replace_tags(
+        conn: &mut DbTransaction,
+        task_id: i32,
+        tags: Vec<String>,
+        actor: &Actor,
+    ) -> Result<Task, ApiError> {
+        let mut task = Self::load(&mut *conn, task_id).await?;
+        let before = Self::tag_names(conn, task_id).await?;
+
+        if before != tags {
+            database::query("DELETE FROM task_tags WHERE task_id = ?")
+                .bind(task_id)
+                .execute(&mut *conn)
+                .await
+                .map_err(ApiError::from)?;
 
-        self.find_by_id(task_id).await
+            for tag in &tags {
+                database::query("INSERT INTO task_tags (task_id, tag) VALUES (?, ?)")
+                    .bind(task_id)
+                    .bind(tag)
+                    .execute(&mut *conn)
+                    .await
+                    .map_err(ApiError::from)?;
+            }
+
+            let mut changes = TaskChanges::new();
+            changes.insert(
+                "tags".to_string(),
+                FieldChange {
+                    from: before.into(),
+                    to: tags.clone().into(),
+                },
+            );
+            Self::record_event(conn, task_id, TaskEventType::Updated, actor, changes).await?;
+        }
+
+        task.tags = Some(tags);
+        Ok(task)
+    }
+
+    /// Adds and removes tags, keeping the task's other tags.
+    async fn retag(
+        conn: &mut DbTransaction,
+        task_id: i32,
+        add: &[String],
+        remove: &[String],
+        actor: &Actor,
+    ) -> Result<Task, ApiError> {
+        let mut tags = Self::tag_names(conn, task_id).await?;
+        tags.extend(add.iter().cloned());
+        tags.retain(|tag| !remove.contains(tag));
+        tags.sort();
+        tags.dedup();
+        if tags.len() > MAX_TAGS_PER_TASK {
+            return Err(ApiError::ValidationError(format!(
+                "A task cannot have more than {} tags",
+                MAX_TAGS_PER_TASK
+            )));
+        }
+
+        Self::replace_tags(conn, task_id, tags, actor).await
+    }
+
+    /// The tags on each of `task_ids`, sorted. Tasks without tags are left
+    /// out of the map.
+    pub async fn tags_for(&self, task_ids: &[i32]) -> Result<HashMap<i32, Vec<String>>, ApiError> {
+        let mut tags: HashMap<i32, Vec<String>> = HashMap::new();
+        for chunk in task_ids.chunks(500) {
+            let sql = format!(
+                "SELECT task_id, tag FROM task_tags WHERE task_id IN ({}) ORDER BY task_id, tag",
+                vec!["?"; chunk.len()].join(", ")
+            );
+            let mut query = database::query_as::<(i32, String)>(&sql);
+            for id in chunk {
+                query = query.bind(id);
+            }
+            let rows = query.fetch_all(&self.pool).await.map_err(ApiError::from)?;
+
+            for (task_id, tag) in rows {
+                tags.entry(task_id).or_default().push(tag);
+            }
+        }
+
+        Ok(tags)
+    }
+
+    /// Every tag in use on a live task, most used first.
+    pub async fn tag_usage(&self) -> Result<Vec<TagUsage>, ApiError> {
+        database::query_as::<TagUsage>(
+            r#"
+            SELECT tt.tag, COUNT(*) AS count
+            FROM task_tags tt
+            JOIN tasks t ON t.id = tt.task_id
+            WHERE t.deleted_at IS NULL
+            GROUP BY tt.tag
+            ORDER BY count DESC, tt.tag
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(ApiError::from)
+    }
+
+    async fn tag_names(conn: &mut DbTransaction, task_id: i32) -> Result<Vec<String>, ApiError> {
+        database::query_scalar("SELECT tag FROM task_tags WHERE task_id = ? ORDER BY tag")
+            .bind(task_id)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(ApiError::from)
+    }
+
+    /// Tasks grouped into status columns, and into lanes when the query asks
+    /// for swimlanes. Each column holds one page of its tasks in board order
+    /// along with its total.
+    pub async fn board(&self, query: &BoardQuery) -> Result<Board, ApiError> {
+        let (limit, offset) = query.page().map_err(ApiError::ValidationError)?;
+        let filter =
+            Self::filter(&query.task_query())?.eq("status", query.status.map(|s| s.as_str()));
+        let lane = match query.swimlane {
+            None => "NULL",
+            Some(Swimlane::Assignee) => "assigned_to",
+            Some(Swimlane::Priority) => "priority_level",
+        };
+
+        let totals_sql = format!(
+            "SELECT {lane} AS lane, status, COUNT(*) AS total FROM tasks{} GROUP BY lane, status",
+            filter.where_clause()
+        );
+        let totals = database::query(&totals_sql)
+            .bind_values(filter.binds())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(ApiError::from)?
+            .iter()
+            .map(|row| {
+                Ok((
+                    lane_key(row, query.swimlane)?,
+                    row.try_get("status").map_err(|e| {
+                        ApiError::InternalServerError(format!("Invalid task status: {}", e))
+                    })?,
+                    row.get("total"),
+                ))
+            })
+            .collect::<Result<_, ApiError>>()?;
+
+        let cards_sql = format!(
+            "SELECT * FROM (SELECT {TASK_COLUMNS}, {lane} AS lane, ROW_NUMBER() OVER (PARTITION BY {lane}, status ORDER BY position IS NULL, position, id) AS board_rank FROM tasks{}) ranked WHERE board_rank > ? AND board_rank <= ? ORDER BY board_rank",
+            filter.where_clause()
+        );
+        let cards = database::query(&cards_sql)
+            .bind_values(filter.binds())
+            .bind(offset as i64)
+            .bind((offset + limit) as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(ApiError::from)?
+            .iter()
+            .map(|row| Ok((lane_key(row, query.swimlane)?, Self::task_from_row(row)?)))
+            .collect::<Result<_, ApiError>>()?;
+
+        let columns = match query.status {
+            Some(status) => vec![status],
+            None => TaskStatus::ALL.to_vec(),
+        };
+        Ok(Board::build(query.swimlane, &columns, totals, cards))
+    }
+
+    /// Moves a task on the board, changing its status first when the request
+    /// names a different column. A status change goes through the transition
+    /// table like any other; reordering within a column is not recorded in
+    /// the task's history.
+    pub async fn move_task(
+        &self,
+        id: i32,
+        req: MoveTaskRequest,
+        actor: &Actor,
+    ) -> Result<Task, ApiError> {
+        let mut tx = self.begin().await?;
+        let existing_task = Self::load(&mut tx, id).await?;
+        let task = match req.status {
+            Some(status) if Some(status) != existing_task.status => {
+                Self::apply_transition(
+                    &mut tx,
+                    id,
+                    TransitionTaskRequest {
+                        status,
+                        reason: req.reason,
+                    },
+                    actor,
+                )
+                .await?
+            }
+            _ => existing_task,
+        };
+
+        let status = task.status.unwrap_or(TaskStatus::ToDo);
+        let position =
+            Self::column_position(&mut tx, id, status, req.after_id, req.before_id).await?;
+        database::query("UPDATE tasks SET position = ? WHERE id = ?")
+            .bind(position)
+            .bind(id)
+            .execute(&mut tx)
+            .await
+            .map_err(ApiError::from)?;
+
+        Self::commit(tx).await?;
+        Ok(task)
+    }
+
+    /// Puts a task at the bottom of `status`'s column.
+    async fn append_to_column(
+        conn: &mut DbTransaction,
+        id: i32,
+        status: TaskStatus,
+    ) -> Result<(), ApiError> {
+        database::query(
+            "UPDATE tasks SET position = (SELECT COALESCE(MAX(position), 0) + 1 FROM tasks WHERE status = ? AND id != ?) WHERE id = ?",
+        )
+        .bind(status)
+        .bind(id)
+        .bind(id)
+        .execute(&mut *conn)
+        .await
+        .map_err(ApiError::from)?;
+
+        Ok(())
+    }
+
+    /// Picks a position in `status`'s column for task `id`: just after
+    /// `after_id`, just before `before_id`, or at the top. The column is
+    /// renumbered when some of its tasks have no position yet or there is no
+    /// room left between the neighbours.
+    async fn column_position(
+        conn: &mut DbTransaction,
+        id: i32,
+        status: TaskStatus,
+        after_id: Option<i32>,
+        before_id: Option<i32>,
+    ) -> Result<f64, ApiError> {
+        let column = database::query_as::<(i32, Option<f64>)>(
+            "SELECT id, position FROM tasks WHERE deleted_at IS NULL AND status = ? AND id != ? ORDER BY position IS NULL, position, id",
+        )
+        .bind(status)
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(ApiError::from)?;
+
+        let slot = match after_id.or(before_id) {
+            Some(neighbour_id) => {
+                let index = column
+                    .iter()
+                    .position(|(task_id, _)| *task_id == neighbour_id)
+                    .ok_or_else(|| {
+                        ApiError::ValidationError(format!(
+                            "Task {} is not in the {} column",
+                            neighbour_id, status
+                        ))
+                    })?;
+                if after_id.is_some() { index + 1 } else { index }
+            }
+            None => 0,
+        };
+
+        let positions: Option<Vec<f64>> = column.iter().map(|(_, position)| *position).collect();
+        if let Some(positions) = positions
+            && let Some(position) = slot_position(&positions, slot)
+        {
+            return Ok(position);
+        }
+
+        for (index, (task_id, _)) in column.iter().enumerate() {
+            database::query("UPDATE tasks SET position = ? WHERE id = ?")
+                .bind((index + 1) as f64)
+                .bind(task_id)
+                .execute(&mut *conn)
+                .await
+                .map_err(ApiError::from)?;
+        }
+        let positions: Vec<f64> = (1..=column.len()).map(|position| position as f64).collect();
+        slot_position(&positions, slot).ok_or_else(|| {
+            ApiError::InternalServerError("Could not place task in its column".to_string())
+        })
+    }
+
+    pub async fn history(&self, task_id: i32) -> Result<Vec<TaskEvent>, ApiError> {
+        database::query_as::<TaskEvent>(
+            r#"
+            SELECT id, task_id, event_type, actor, changes, created_at
+            FROM task_events
+            WHERE task_id = ?
+            ORDER BY created_at, id
+            "#,
+        )
+        .bind(task_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(ApiError::from)
+    }
+
+    /// Appends an audit entry inside the caller's transaction, so the entry
+    /// exists if and only if the change it describes was committed. Writes
+    /// that changed nothing are not recorded.
+    async fn record_event(
+        conn: &mut DbTransaction,
+        task_id: i32,
+        event_type: TaskEventType,
+        actor: &Actor,
+        changes: TaskChanges,
+    ) -> Result<(), ApiError> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        database::query(
+            r#"
+            INSERT INTO task_events (task_id, event_type, actor, changes, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(task_id)
+        .bind(event_type)
+        .bind(&actor.username)
+        .bind(Json(changes))
+        .bind(Utc::now())
+        .execute(&mut *conn)
+        .await
+        .map_err(ApiError::from)?;
+
+        Ok(())
+    }
+
+    async fn begin(&self) -> Result<DbTransaction, ApiError> {
+        self.pool
+            .begin()
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Transaction error: {}", e)))
+    }
+
+    async fn commit(tx: DbTransaction) -> Result<(), ApiError> {
+        tx.commit()
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Transaction commit error: {}", e)))
+    }
+}
+
+/// `completed_at` is stamped only on the move into Done and cleared when a
+/// task leaves Done; any other change keeps the existing value.
+fn completed_at_after(
+    from: TaskStatus,
+    to: TaskStatus,
+    completed_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    match (from, to) {
+        (TaskStatus::Done, TaskStatus::Done) => completed_at,
+        (_, TaskStatus::Done) => Some(now),
+        (TaskStatus::Done, _) => None,
+        _ => completed_at,
+    }
+}
+
+/// The board lane of a row selected with its lane value as `lane`.
+fn lane_key(row: &DbRow, swimlane: Option<Swimlane>) -> Result<LaneKey, ApiError> {
+    match swimlane {
+        None => Ok(LaneKey::All),
+        Some(Swimlane::Assignee) => Ok(LaneKey::Assignee(row.get("lane"))),
+        Some(Swimlane::Priority) => row
+            .try_get("lane")
+            .map(LaneKey::Priority)
+            .map_err(|e| ApiError::InternalServerError(format!("Invalid task priority: {}", e))),
+    }
+}
+
+/// A position between the tasks either side of `slot` in a column, or
+/// `None` when they are too close together to fit another between them.
+fn slot_position(positions: &[f64], slot: usize) -> Option<f64> {
+    let before = slot.checked_sub(1).map(|index| positions[index]);
+    let after = positions.get(slot).copied();
+    let position = match (before, after) {
+        (Some(before), Some(after)) => before + (after - before) / 2.0,
+        (Some(before), None) => before + 1.0,
+        (None, Some(after)) => after - 1.0,
+        (None, None) => 1.0,
+    };
+
+    let fits =
+        before.is_none_or(|before| position > before) && after.is_none_or(|after| position < after);
+    fits.then_some(position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::run_migrations;
+    use chrono::Duration;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn repository_with_task() -> (TaskRepository, Task) {
+        let pool: DbPool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+            .into();
+        run_migrations(&pool).await.unwrap();
+
+        let repository = TaskRepository::new(pool);
+        let task = repository
+            .create(
+                CreateTaskRequest {
+                    title: "Restock shelves".to_string(),
+                    description: None,
+                    priority_level: Some(PriorityLevel::High),
+                    status: None,
+                    assigned_to: None,
+                    store_id: None,
+                    parent_id: None,
+                    start_at: None,
+                    due_at: None,
+                },
+                &actor(),
+            )
+            .await
+            .unwrap();
+
+        (repository, task)
+    }
+
+    fn actor() -> Actor {
+        Actor {
+            username: "manager".to_string(),
+            role: "admin".to_string(),
+        }
+    }
+
+    async fn add_employee(
+        repository: &TaskRepository,
+        external_id: &str,
+        deactivated: bool,
+    ) -> i32 {
+        database::query_scalar(
+            "INSERT INTO employees (external_id, first_name, last_name, store_id, deactivated_at) VALUES (?, 'Test', 'Employee', 1, ?) RETURNING id",
+        )
+        .bind(external_id)
+        .bind(deactivated.then(Utc::now))
+        .fetch_one(&repository.pool)
+        .await
+        .unwrap()
+    }
+
+    fn transition(status: TaskStatus, reason: Option<&str>) -> TransitionTaskRequest {
+        TransitionTaskRequest {
+            status,
+            reason: reason.map(str::to_string),
+        }
+    }
+
+    fn rename(title: &str) -> UpdateTaskRequest {
+        UpdateTaskRequest {
+            title: Some(title.to_string()),
+            description: None,
+            priority_level: None,
+            status: None,
+            assigned_to: None,
+            parent_id: None,
+            start_at: None,
+            due_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_completed_at_is_stamped_only_entering_done() {
+        let (repository, task) = repository_with_task().await;
+
+        let task = repository
+            .transition(task.id, transition(TaskStatus::InProgress, None), &actor())
+            .await
+            .unwrap();
+        assert_eq!(task.status, Some(TaskStatus::InProgress));
+        assert!(task.completed_at.is_none());
+
+        let done = repository
+            .transition(task.id, transition(TaskStatus::Done, None), &actor())
+            .await
+            .unwrap();
+        assert_eq!(done.status, Some(TaskStatus::Done));
+        assert!(done.completed_at.is_some());
+
+        let renamed = repository
+            .update(task.id, rename("Restock aisle 4"), &actor())
+            .await
+            .unwrap();
+        assert_eq!(renamed.completed_at, done.completed_at);
+    }
+
+    #[tokio::test]
+    async fn test_reopen_requires_reason_and_clears_completed_at() {
+        let (repository, task) = repository_with_task().await;
+        repository
+            .transition(task.id, transition(TaskStatus::InProgress, None), &actor())
+            .await
+            .unwrap();
+        repository
+            .transition(task.id, transition(TaskStatus::Done, None), &actor())
+            .await
+            .unwrap();
+
+        let result = repository
+            .transition(task.id, transition(TaskStatus::ToDo, None), &actor())
+            .await;
+        assert!(matches!(result, Err(ApiError::ValidationError(_))));
+
+        let reopened = repository
+            .transition(
+                task.id,
+                transition(TaskStatus::ToDo, Some("Shelf still empty")),
+                &actor(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(reopened.status, Some(TaskStatus::ToDo));
+        assert_eq!(reopened.status_reason.as_deref(), Some("Shelf still empty"));
+        assert!(reopened.completed_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_update_enforces_transition_table() {
+        let (repository, task) = repository_with_task().await;
+
+        let mut skip_ahead = rename("Restock shelves");
+        skip_ahead.status = Some(TaskStatus::Done);
+        let result = repository.update(task.id, skip_ahead, &actor()).await;
+
+        assert!(matches!(result, Err(ApiError::ValidationError(_))));
+        let unchanged = repository.find_by_id(task.id).await.unwrap();
+        assert_eq!(unchanged.status, Some(TaskStatus::ToDo));
+    }
+
+    #[tokio::test]
+    async fn test_history_records_each_change_with_actor() {
+        let (repository, task) = repository_with_task().await;
+        repository
+            .transition(task.id, transition(TaskStatus::InProgress, None), &actor())
+            .await
+            .unwrap();
+        let employee_id = add_employee(&repository, "EMP007", false).await;
+        repository
+            .assign(task.id, employee_id, &actor())
+            .await
+            .unwrap();
+        repository
+            .update(task.id, rename("Restock shelves"), &actor())
+            .await
+            .unwrap();
+        repository.delete(task.id, &actor()).await.unwrap();
+
+        let history = repository.history(task.id).await.unwrap();
+        let types: Vec<TaskEventType> = history.iter().map(|event| event.event_type).collect();
+
+        assert_eq!(
+            types,
+            [
+                TaskEventType::Created,
+                TaskEventType::StatusChanged,
+                TaskEventType::Assigned,
+                TaskEventType::Deleted,
+            ]
+        );
+        assert!(history.iter().all(|event| event.actor == "manager"));
+        assert_eq!(
+            history[2].changes["assigned_to"].from,
+            serde_json::Value::Null
+        );
+        assert_eq!(
+            history[2].changes["assigned_to"].to,
+            serde_json::json!(employee_id)
+        );
+        assert!(history[3].changes.contains_key("deleted_at"));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_change_leaves_no_history() {
+        let (repository, task) = repository_with_task().await;
+
+        let result = repository
+            .transition(task.id, transition(TaskStatus::Done, None), &actor())
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(repository.history(task.id).await.unwrap().len(), 1);
+    }
+
+    fn task_query() -> TaskQuery {
+        TaskQuery {
+            limit: Some(100),
+            offset: None,
+            id: None,
+            title: None,
+            status: None,
+            priority_level: None,
+            assigned_to: None,
+            store_id: None,
+            parent_id: None,
+            overdue: None,
+            due_before: None,
+            due_after: None,
+            sort: Some("id".to_string()),
+            sort_by: None,
+            sort_direction: None,
+            cursor: None,
+            include_total: None,
+            include_deleted: None,
+            tags: None,
+            tag_match: None,
+        }
+    }
+
+    async fn titles(repository: &TaskRepository, query: TaskQuery) -> Vec<String> {
+        let page = repository.find_all(query).await.unwrap();
+        page.items.into_iter().map(|task| task.title).collect()
+    }
+
+    #[tokio::test]
+    async fn test_due_date_filters() {
+        let (repository, _) = repository_with_task().await;
+        let now = Utc::now();
+
+        for (title, due_at) in [
+            ("Late", now - Duration::days(2)),
+            ("Finished late", now - Duration::days(1)),
+            ("Upcoming", now + Duration::days(3)),
+        ] {
+            let request = CreateTaskRequest {
+                title: title.to_string(),
+                description: None,
+                priority_level: None,
+                status: None,
+                assigned_to: None,
+                store_id: None,
+                parent_id: None,
+                start_at: None,
+                due_at: Some(due_at),
+            };
+            repository.create(request, &actor()).await.unwrap();
+        }
+        repository
+            .transition(3, transition(TaskStatus::InProgress, None), &actor())
+            .await
+            .unwrap();
+        repository
+            .transition(3, transition(TaskStatus::Done, None), &actor())
+            .await
+            .unwrap();
+
+        let mut overdue = task_query();
+        overdue.overdue = Some(true);
+        assert_eq!(titles(&repository, overdue).await, ["Late"]);
+
+        let mut not_overdue = task_query();
+        not_overdue.overdue = Some(false);
+        assert_eq!(
+            titles(&repository, not_overdue).await,
+            ["Restock shelves", "Finished late", "Upcoming"]
+        );
+
+        let mut window = task_query();
+        window.due_after = Some(now - Duration::days(3));
+        window.due_before = Some(now);
+        assert_eq!(titles(&repository, window).await, ["Late", "Finished late"]);
+    }
+
+    #[tokio::test]
+    async fn test_update_rejects_due_before_start() {
+        let (repository, task) = repository_with_task().await;
+        let now = Utc::now();
+
+        let mut schedule = rename("Restock shelves");
+        schedule.start_at = Some(now);
+        repository
+            .update(task.id, schedule, &actor())
+            .await
+            .unwrap();
+
+        let mut due = rename("Restock shelves");
+        due.due_at = Some(now - Duration::hours(1));
+        let result = repository.update(task.id, due, &actor()).await;
+
+        assert!(matches!(result, Err(ApiError::ValidationError(_))));
+    }
+
+    async fn create_task(repository: &TaskRepository, title: &str, parent_id: Option<i32>) -> Task {
+        let request = CreateTaskRequest {
+            title: title.to_string(),
+            description: None,
+            priority_level: None,
+            status: None,
+            assigned_to: None,
+            store_id: None,
+            parent_id,
+            start_at: None,
+            due_at: None,
+        };
+        repository.create(request, &actor()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_open_dependency_blocks_start_and_completion() {
+        let (repository, delivery) = repository_with_task().await;
+        let display = create_task(&repository, "Build display", None).await;
+        repository
+            .add_dependency(display.id, delivery.id, &actor())
+            .await
+            .unwrap();
+
+        let start = repository
+            .transition(
+                display.id,
+                transition(TaskStatus::InProgress, None),
+                &actor(),
+            )
+            .await;
+        assert!(matches!(start, Err(ApiError::ValidationError(_))));
+
+        let mut update = rename("Build display");
+        update.status = Some(TaskStatus::InProgress);
+        let result = repository.update(display.id, update, &actor()).await;
+        assert!(matches!(result, Err(ApiError::ValidationError(_))));
+
+        repository
+            .transition(display.id, transition(TaskStatus::Blocked, None), &actor())
+            .await
+            .unwrap();
+        for status in [TaskStatus::InProgress, TaskStatus::Done] {
+            repository
+                .transition(delivery.id, transition(status, None), &actor())
+                .await
+                .unwrap();
+        }
+        repository
+            .transition(
+                display.id,
+                transition(TaskStatus::InProgress, None),
+                &actor(),
+            )
+            .await
+            .unwrap();
+
+        let dependencies = repository.dependencies(delivery.id).await.unwrap();
+        assert!(dependencies.blocked_by.is_empty());
+        assert_eq!(dependencies.blocking[0].id, display.id);
+    }
+
+    #[tokio::test]
+    async fn test_dependency_cycles_are_rejected() {
+        let (repository, first) = repository_with_task().await;
+        let second = create_task(&repository, "Second", None).await;
+        let third = create_task(&repository, "Third", None).await;
+
+        repository
+            .add_dependency(second.id, first.id, &actor())
+            .await
+            .unwrap();
+        repository
+            .add_dependency(third.id, second.id, &actor())
+            .await
+            .unwrap();
+
+        for (task_id, depends_on) in [(first.id, third.id), (first.id, first.id)] {
+            let result = repository
+                .add_dependency(task_id, depends_on, &actor())
+                .await;
+            assert!(matches!(result, Err(ApiError::ValidationError(_))));
+        }
+
+        repository
+            .remove_dependency(second.id, first.id, &actor())
+            .await
+            .unwrap();
+        repository
+            .add_dependency(first.id, third.id, &actor())
+            .await
+            .unwrap();
+
+        let events = repository.history(first.id).await.unwrap();
+        let change = &events.last().unwrap().changes["depends_on"];
+        assert_eq!(change.to, serde_json::json!([third.id]));
+    }
+
+    #[tokio::test]
+    async fn test_tree_rolls_up_subtask_progress() {
+        let (repository, parent) = repository_with_task().await;
+        let aisle = create_task(&repository, "Aisle 1", Some(parent.id)).await;
+        let fridge = create_task(&repository, "Fridges", Some(parent.id)).await;
+        let shelf = create_task(&repository, "Top shelf", Some(aisle.id)).await;
+        create_task(&repository, "Bottom shelf", Some(aisle.id)).await;
+        for status in [TaskStatus::InProgress, TaskStatus::Done] {
+            repository
+                .transition(shelf.id, transition(status, None), &actor())
+                .await
+                .unwrap();
+        }
+        repository
+            .transition(fridge.id, transition(TaskStatus::Cancelled, None), &actor())
+            .await
+            .unwrap();
+
+        let tree = repository.tree(parent.id, false).await.unwrap();
+
+        let progress = tree.task.progress.unwrap();
+        assert_eq!(
+            (progress.completed, progress.total, progress.percent),
+            (1, 2, 50)
+        );
+        assert_eq!(tree.subtasks.len(), 2);
+        assert_eq!(tree.subtasks[0].subtasks.len(), 2);
+
+        let mut reparent = rename("Restock shelves");
+        reparent.parent_id = Some(shelf.id);
+        let result = repository.update(parent.id, reparent, &actor()).await;
+        assert!(matches!(result, Err(ApiError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_assignment_requires_an_active_employee() {
+        let (repository, task) = repository_with_task().await;
+        let active = add_employee(&repository, "EMP001", false).await;
+        let deactivated = add_employee(&repository, "EMP002", true).await;
+
+        for employee_id in [deactivated, 999] {
+            let result = repository.assign(task.id, employee_id, &actor()).await;
+            assert!(matches!(result, Err(ApiError::ValidationError(_))));
+
+            let mut update = rename("Restock shelves");
+            update.assigned_to = Some(employee_id);
+            let result = repository.update(task.id, update, &actor()).await;
+            assert!(matches!(result, Err(ApiError::ValidationError(_))));
+        }
+
+        let missing = repository.assign(999, active, &actor()).await;
+        assert!(matches!(missing, Err(ApiError::NotFound(_))));
+
+        let assigned = repository.assign(task.id, active, &actor()).await.unwrap();
+        assert_eq!(assigned.assigned_to, Some(active));
+
+        let unassigned = repository.unassign(task.id, &actor()).await.unwrap();
+        assert_eq!(unassigned.assigned_to, None);
+        let history = repository.history(task.id).await.unwrap();
+        assert_eq!(history.last().unwrap().event_type, TaskEventType::Assigned);
+    }
+
+    #[tokio::test]
+    async fn test_deleted_tasks_are_hidden_until_restored() {
+        let (repository, task) = repository_with_task().await;
+        let other = create_task(&repository, "Clean fridges", None).await;
+
+        repository.delete(task.id, &actor()).await.unwrap();
+
+        let result = repository.find_by_id(task.id).await;
+        assert!(matches!(result, Err(ApiError::NotFound(_))));
+        assert_eq!(titles(&repository, task_query()).await, ["Clean fridges"]);
+        assert!(
+            repository
+                .find_by_id(other.id)
+                .await
+                .unwrap()
+                .deleted_at
+                .is_none()
+        );
+
+        let mut with_deleted = task_query();
+        with_deleted.include_deleted = Some(true);
+        assert_eq!(
+            titles(&repository, with_deleted).await,
+            ["Restock shelves", "Clean fridges"]
+        );
+
+        let deleted = repository.delete(task.id, &actor()).await;
+        assert!(matches!(deleted, Err(ApiError::NotFound(_))));
+
+        let restored = repository.restore(task.id, &actor()).await.unwrap();
+        assert!(restored.deleted_at.is_none());
+        assert_eq!(
+            titles(&repository, task_query()).await,
+            ["Restock shelves", "Clean fridges"]
+        );
+        let history = repository.history(task.id).await.unwrap();
+        assert_eq!(history.last().unwrap().event_type, TaskEventType::Restored);
+
+        let again = repository.restore(task.id, &actor()).await;
+        assert!(matches!(again, Err(ApiError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_purge_removes_only_tasks_past_retention() {
+        let (repository, parent) = repository_with_task().await;
+        let child = create_task(&repository, "Aisle 1", Some(parent.id)).await;
+        let recent = create_task(&repository, "Clean fridges", None).await;
+
+        repository.delete(parent.id, &actor()).await.unwrap();
+        repository.delete(recent.id, &actor()).await.unwrap();
+        database::query("UPDATE tasks SET deleted_at = ? WHERE id = ?")
+            .bind(Utc::now() - Duration::days(40))
+            .bind(parent.id)
+            .execute(&repository.pool)
+            .await
+            .unwrap();
+
+        let purged = repository
+            .purge_deleted(Utc::now() - Duration::days(30))
+            .await
+            .unwrap();
+        assert_eq!(purged.count, 1);
+
+        let result = repository.find_by_id_including_deleted(parent.id).await;
+        assert!(matches!(result, Err(ApiError::NotFound(_))));
+        assert!(repository.history(parent.id).await.unwrap().is_empty());
+        assert!(
+            repository
+                .find_by_id(child.id)
+                .await
+                .unwrap()
+                .parent_id
+                .is_none()
+        );
+        assert!(
+            repository
+                .find_by_id_including_deleted(recent.id)
+                .await
+                .unwrap()
+                .deleted_at
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tag_filters_match_any_or_all() {
+        let (repository, task) = repository_with_task().await;
+        let fridge = create_task(&repository, "Clean fridges", None).await;
+        let deleted = create_task(&repository, "Old task", None).await;
+        let tags = |tags: &[&str]| tags.iter().map(|tag| tag.to_string()).collect::<Vec<_>>();
+        repository
+            .set_tags(task.id, tags(&["aisle", "urgent"]), &actor())
+            .await
+            .unwrap();
+        repository
+            .set_tags(fridge.id, tags(&["urgent"]), &actor())
+            .await
+            .unwrap();
+        repository
+            .set_tags(deleted.id, tags(&["aisle"]), &actor())
+            .await
+            .unwrap();
+        repository.delete(deleted.id, &actor()).await.unwrap();
+
+        let mut any = task_query();
+        any.tags = Some("Urgent, aisle".to_string());
+        assert_eq!(
+            titles(&repository, any).await,
+            ["Restock shelves", "Clean fridges"]
+        );
+
+        let mut all = task_query();
+        all.tags = Some("urgent,aisle".to_string());
+        all.tag_match = Some(TagMatch::All);
+        assert_eq!(titles(&repository, all).await, ["Restock shelves"]);
+
+        let usage = repository.tag_usage().await.unwrap();
+        let usage: Vec<(&str, i64)> = usage.iter().map(|u| (u.tag.as_str(), u.count)).collect();
+        assert_eq!(usage, [("urgent", 2), ("aisle", 1)]);
+
+        let history = repository.history(task.id).await.unwrap();
+        let change = &history.last().unwrap().changes["tags"];
+        assert_eq!(change.to, serde_json::json!(["aisle", "urgent"]));
+    }
+
+    #[tokio::test]
+    async fn test_bulk_actions_are_all_or_nothing() {
+        let (repository, first) = repository_with_task().await;
+        let second = create_task(&repository, "Clean fridges", None).await;
+        let status = |task: &Task| task.status;
+        let start = BulkAction::UpdateStatus {
+            status: TaskStatus::InProgress,
+            reason: None,
+        };
+
+        let report = repository
+            .bulk(&[first.id, 999, second.id], &start, false, &actor())
+            .await
+            .unwrap();
+        assert!(!report.applied);
+        assert_eq!((report.succeeded, report.failed), (2, 1));
+        assert!(
+            report.results[1]
+                .error
+                .as_deref()
+                .unwrap()
+                .contains("not found")
+        );
+        let unchanged = repository.find_by_id(first.id).await.unwrap();
+        assert_eq!(status(&unchanged), Some(TaskStatus::ToDo));
+
+        let report = repository
+            .bulk(&[first.id, second.id], &start, true, &actor())
+            .await
+            .unwrap();
+        assert!(!report.applied && report.failed == 0);
+        assert_eq!(
+            status(report.results[0].task.as_ref().unwrap()),
+            Some(TaskStatus::InProgress)
+        );
+        let unchanged = repository.find_by_id(second.id).await.unwrap();
+        assert_eq!(status(&unchanged), Some(TaskStatus::ToDo));
+
+        let tag = BulkAction::Tag {
+            add: vec!["weekly".to_string()],
+            remove: vec![],
+        };
+        let report = repository
+            .bulk(&[first.id, second.id], &tag, false, &actor())
+            .await
+            .unwrap();
+        assert!(report.applied);
+
+        let mut weekly = task_query();
+        weekly.tags = Some("weekly".to_string());
+        let ids = repository.find_ids(&weekly, 10).await.unwrap();
+        assert_eq!(ids.len(), 2);
+
+        let report = repository
+            .bulk(&ids, &BulkAction::Delete, false, &actor())
+            .await
+            .unwrap();
+        assert!(report.applied);
+        assert!(titles(&repository, task_query()).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_board_orders_columns_and_moves_tasks() {
+        let (repository, first) = repository_with_task().await;
+        let second = create_task(&repository, "Face aisles", None).await;
+        let third = create_task(&repository, "Count till", None).await;
+        let fourth = create_task(&repository, "Clean fridges", None).await;
+        let board_query =
+            |query: serde_json::Value| -> BoardQuery { serde_json::from_value(query).unwrap() };
+        let column = |board: &Board, status: TaskStatus| {
+            let column = board.lanes[0]
+                .columns
+                .iter()
+                .find(|column| column.status == status)
+                .unwrap();
+            let titles: Vec<String> = column.tasks.iter().map(|t| t.title.clone()).collect();
+            (column.total, titles)
+        };
+        let move_to =
+            |status: Option<TaskStatus>, after_id: Option<i32>, before_id: Option<i32>| {
+                MoveTaskRequest {
+                    status,
+                    reason: None,
+                    after_id,
+                    before_id,
+                }
+            };
+
+        repository
+            .move_task(fourth.id, move_to(None, None, Some(second.id)), &actor())
+            .await
+            .unwrap();
+        repository
+            .move_task(
+                first.id,
+                move_to(Some(TaskStatus::InProgress), None, None),
+                &actor(),
+            )
+            .await
+            .unwrap();
+        repository
+            .transition(third.id, transition(TaskStatus::InProgress, None), &actor())
+            .await
+            .unwrap();
+
+        let board = repository
+            .board(&board_query(serde_json::json!({"limit": 2})))
+            .await
+            .unwrap();
+        assert_eq!(board.lanes.len(), 1);
+        assert_eq!(board.lanes[0].columns.len(), TaskStatus::ALL.len());
+        assert_eq!(
+            column(&board, TaskStatus::ToDo),
+            (
+                2,
+                vec!["Clean fridges".to_string(), "Face aisles".to_string()]
+            )
+        );
+        assert_eq!(
+            column(&board, TaskStatus::InProgress),
+            (
+                2,
+                vec!["Restock shelves".to_string(), "Count till".to_string()]
+            )
+        );
+
+        repository
+            .move_task(first.id, move_to(None, Some(third.id), None), &actor())
+            .await
+            .unwrap();
+        let in_progress = repository
+            .board(&board_query(
+                serde_json::json!({"status": "InProgress", "offset": 1}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(in_progress.lanes[0].columns.len(), 1);
+        assert_eq!(
+            column(&in_progress, TaskStatus::InProgress),
+            (2, vec!["Restock shelves".to_string()])
+        );
+
+        let outside_column = repository
+            .move_task(second.id, move_to(None, Some(first.id), None), &actor())
+            .await;
+        assert!(matches!(outside_column, Err(ApiError::ValidationError(_))));
+        let not_allowed = repository
+            .move_task(
+                second.id,
+                move_to(Some(TaskStatus::Done), None, None),
+                &actor(),
+            )
+            .await;
+        assert!(not_allowed.is_err());
+        assert_eq!(
+            repository.find_by_id(second.id).await.unwrap().status,
+            Some(TaskStatus::ToDo)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_statuses_read_back_as_written_and_bad_rows_fail() {
+        let (repository, task) = repository_with_task().await;
+        let mut request = CreateTaskRequest {
+            title: "Face aisles".to_string(),
+            description: None,
+            priority_level: Some(PriorityLevel::High),
+            status: Some(TaskStatus::InProgress),
+            assigned_to: None,
+            store_id: None,
+            parent_id: None,
+            start_at: None,
+            due_at: None,
+        };
+        let in_progress = repository.create(request.clone(), &actor()).await.unwrap();
+        request.status = Some(TaskStatus::InReview);
+        repository.create(request, &actor()).await.unwrap();
+
+        let mut query = task_query();
+        query.status = Some(TaskStatus::InProgress);
+        let page = repository.find_all(query).await.unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].status, Some(TaskStatus::InProgress));
+        assert_eq!(page.items[0].priority_level, Some(PriorityLevel::High));
+        let stored: String = database::query_scalar("SELECT status FROM tasks WHERE id = ?")
+            .bind(in_progress.id)
+            .fetch_one(&repository.pool)
+            .await
+            .unwrap();
+        assert_eq!(stored, "InProgress");
+
+        database::query("PRAGMA ignore_check_constraints = ON")
+            .execute(&repository.pool)
+            .await
+            .unwrap();
+        database::query("UPDATE tasks SET status = 'Someday' WHERE id = ?")
+            .bind(task.id)
+            .execute(&repository.pool)
+            .await
+            .unwrap();
+        let error = repository.find_by_id(task.id).await.unwrap_err();
+        assert!(
+            matches!(error, ApiError::InternalServerError(ref message) if message.contains("Someday"))
+        );
+        assert!(repository.find_all(task_query()).await.is_err());
     }
 }
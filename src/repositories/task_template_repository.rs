@@ -0,0 +1,319 @@
+use crate::database::{self, DbPool};
+use crate::errors::ApiError;
+use crate::models::auth::Actor;
+use crate::models::task::CreateTaskRequest;
+use crate::models::task_template::*;
+use crate::repositories::{AssignmentRepository, TaskRepository};
+use chrono::{Duration, NaiveDate, NaiveTime, Utc};
+use sqlx::types::Json;
+
+const TEMPLATE_COLUMNS: &str = "id, title, description, priority_level, assigned_to, store_ids, rrule, starts_on, due_in_hours, active, created_at, updated_at";
+
+#[derive(Clone)]
+pub struct TaskTemplateRepository {
+    pool: DbPool,
+}
+
+impl TaskTemplateRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find_all(&self) -> Result<Vec<TaskTemplate>, ApiError> {
+        database::query_as(&format!(
+            "SELECT {} FROM task_templates ORDER BY id",
+            TEMPLATE_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(ApiError::from)
+    }
+
+    pub async fn find_active(&self) -> Result<Vec<TaskTemplate>, ApiError> {
+        database::query_as(&format!(
+            "SELECT {} FROM task_templates WHERE active = TRUE ORDER BY id",
+            TEMPLATE_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(ApiError::from)
+    }
+
+    pub async fn find_by_id(&self, id: i32) -> Result<TaskTemplate, ApiError> {
+        database::query_as(&format!(
+            "SELECT {} FROM task_templates WHERE id = ?",
+            TEMPLATE_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::NotFound("Task template not found".to_string()))
+    }
+
+    pub async fn create(&self, req: CreateTaskTemplateRequest) -> Result<TaskTemplate, ApiError> {
+        let now = Utc::now();
+
+        let id: i32 = database::query_scalar(
+            r#"
+            INSERT INTO task_templates (title, description, priority_level, assigned_to, store_ids, rrule, starts_on, due_in_hours, active, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, TRUE, ?, ?)
+            RETURNING id
+            "#,
+        )
+        .bind(&req.title)
+        .bind(&req.description)
+        .bind(req.priority_level)
+        .bind(req.assigned_to)
+        .bind(Json(req.store_ids.unwrap_or_default()))
+        .bind(canonical_rrule(&req.rrule)?)
+        .bind(req.starts_on.unwrap_or_else(|| now.date_naive()))
+        .bind(req.due_in_hours)
+        .bind(now)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(ApiError::from)?;
+
+        self.find_by_id(id).await
+    }
+
+    pub async fn update(
+        &self,
+        id: i32,
+        req: UpdateTaskTemplateRequest,
+    ) -> Result<TaskTemplate, ApiError> {
+        let template = self.find_by_id(id).await?;
+        let rrule = match &req.rrule {
+            Some(rrule) => canonical_rrule(rrule)?,
+            None => template.rrule,
+        };
+
+        database::query(
+            r#"
+            UPDATE task_templates
+            SET title = ?, description = ?, priority_level = ?, assigned_to = ?, store_ids = ?,
+                rrule = ?, starts_on = ?, due_in_hours = ?, active = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(req.title.unwrap_or(template.title))
+        .bind(req.description.or(template.description))
+        .bind(req.priority_level.or(template.priority_level))
+        .bind(req.assigned_to.or(template.assigned_to))
+        .bind(req.store_ids.map(Json).unwrap_or(template.store_ids))
+        .bind(rrule)
+        .bind(req.starts_on.unwrap_or(template.starts_on))
+        .bind(req.due_in_hours.or(template.due_in_hours))
+        .bind(req.active.unwrap_or(template.active))
+        .bind(Utc::now())
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(ApiError::from)?;
+
+        self.find_by_id(id).await
+    }
+
+    /// Deletes the template. Tasks it already created are left alone.
+    pub async fn delete(&self, id: i32) -> Result<(), ApiError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Transaction error: {}", e)))?;
+
+        let result = database::query("DELETE FROM task_templates WHERE id = ?")
+            .bind(id)
+            .execute(&mut tx)
+            .await
+            .map_err(ApiError::from)?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApiError::NotFound("Task template not found".to_string()));
+        }
+
+        database::query("DELETE FROM task_template_occurrences WHERE template_id = ?")
+            .bind(id)
+            .execute(&mut tx)
+            .await
+            .map_err(ApiError::from)?;
+
+        tx.commit()
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Transaction commit error: {}", e)))
+    }
+
+    /// Creates a task for every store and date that does not have one yet.
+    /// Each (template, store, date) is claimed in `task_template_occurrences`
+    /// inside the same transaction as the task, so repeated or concurrent runs
+    /// never create duplicates.
+    pub async fn materialize(
+        &self,
+        template: &TaskTemplate,
+        dates: &[NaiveDate],
+    ) -> Result<MaterializeReport, ApiError> {
+        let mut report = MaterializeReport {
+            templates: 1,
+            ..Default::default()
+        };
+        let actor = Actor::system("scheduler");
+        let now = Utc::now();
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ApiError::InternalServerError(format!("Transaction error: {}", e)))?;
+
+        for date in dates {
+            for store_id in template.target_stores() {
+                let claimed = database::query(
+                    r#"
+                    INSERT INTO task_template_occurrences (template_id, store_key, occurs_on, created_at)
+                    VALUES (?, ?, ?, ?)
+                    ON CONFLICT DO NOTHING
+                    "#,
+                )
+                .bind(template.id)
+                .bind(store_id.unwrap_or(0))
+                .bind(date)
+                .bind(now)
+                .execute(&mut tx)
+                .await
+                .map_err(ApiError::from)?;
+
+                if claimed.rows_affected() == 0 {
+                    report.existing += 1;
+                    continue;
+                }
+
+                let assigned_to = match (template.assigned_to, store_id) {
+                    (None, Some(store_id)) => AssignmentRepository::pick(&mut tx, store_id).await?,
+                    (assigned_to, _) => assigned_to,
+                };
+                let start_at = date.and_time(NaiveTime::MIN).and_utc();
+                let request = CreateTaskRequest {
+                    title: template.title.clone(),
+                    description: template.description.clone(),
+                    priority_level: template.priority_level,
+                    status: None,
+                    assigned_to,
+                    store_id,
+                    parent_id: None,
+                    start_at: Some(start_at),
+                    due_at: template
+                        .due_in_hours
+                        .map(|hours| start_at + Duration::hours(hours)),
+                };
+                let task = TaskRepository::insert(&mut tx, request, &actor).await?;
+
+                database::query(
+                    r#"
+                    UPDATE task_template_occurrences SET task_id = ?
+                    WHERE template_id = ? AND store_key = ? AND occurs_on = ?
+                    "#,
+                )
+                .bind(task.id)
+                .bind(template.id)
+                .bind(store_id.unwrap_or(0))
+                .bind(date)
+                .execute(&mut tx)
+                .await
+                .map_err(ApiError::from)?;
+
+                report.created_task_ids.push(task.id);
+            }
+        }
+
+        tx.commit().await.map_err(|e| {
+            ApiError::InternalServerError(format!("Transaction commit error: {}", e))
+        })?;
+
+        Ok(report)
+    }
+}
+
+fn canonical_rrule(rrule: &str) -> Result<String, ApiError> {
+    rrule
+        .parse::<Recurrence>()
+        .map(|recurrence| recurrence.to_string())
+        .map_err(ApiError::ValidationError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::run_migrations;
+    use chrono::DateTime;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn repository() -> TaskTemplateRepository {
+        let pool: DbPool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+            .into();
+        run_migrations(&pool).await.unwrap();
+
+        TaskTemplateRepository::new(pool)
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn template_request(store_ids: Vec<i32>) -> CreateTaskTemplateRequest {
+        CreateTaskTemplateRequest {
+            title: "Check fridges".to_string(),
+            description: None,
+            priority_level: None,
+            assigned_to: None,
+            store_ids: Some(store_ids),
+            rrule: "RRULE:FREQ=daily".to_string(),
+            starts_on: Some(date("2026-03-01")),
+            due_in_hours: Some(8),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_stores_canonical_rrule() {
+        let repository = repository().await;
+
+        let template = repository.create(template_request(vec![])).await.unwrap();
+
+        assert_eq!(template.rrule, "FREQ=DAILY");
+        assert!(template.active);
+    }
+
+    #[tokio::test]
+    async fn test_materialize_per_store_is_idempotent() {
+        let repository = repository().await;
+        let template = repository
+            .create(template_request(vec![1, 2]))
+            .await
+            .unwrap();
+        let dates = [date("2026-03-01"), date("2026-03-02")];
+
+        let first = repository.materialize(&template, &dates).await.unwrap();
+        assert_eq!(first.created_task_ids.len(), 4);
+        assert_eq!(first.existing, 0);
+
+        let second = repository.materialize(&template, &dates).await.unwrap();
+        assert!(second.created_task_ids.is_empty());
+        assert_eq!(second.existing, 4);
+
+        let (store_id, due_at): (Option<i32>, Option<DateTime<Utc>>) =
+            database::query_as("SELECT store_id, due_at FROM tasks WHERE id = ?")
+                .bind(first.created_task_ids[1])
+                .fetch_one(&repository.pool)
+                .await
+                .unwrap();
+        assert_eq!(store_id, Some(2));
+        assert_eq!(
+            due_at,
+            Some(date("2026-03-01").and_hms_opt(8, 0, 0).unwrap().and_utc())
+        );
+    }
+}
@@ -0,0 +1,34 @@
+use crate::errors::ApiError;
+use crate::models::assignment::*;
+use crate::repositories::AssignmentRepository;
+
+#[derive(Clone)]
+pub struct AssignmentService {
+    repository: AssignmentRepository,
+}
+
+impl AssignmentService {
+    pub fn new(repository: AssignmentRepository) -> Self {
+        Self { repository }
+    }
+
+    pub async fn get_all_settings(&self) -> Result<Vec<StoreAssignmentSettings>, ApiError> {
+        self.repository.find_all().await
+    }
+
+    pub async fn get_settings(&self, store_id: i32) -> Result<StoreAssignmentSettings, ApiError> {
+        self.repository.find_by_store(store_id).await
+    }
+
+    pub async fn save_settings(
+        &self,
+        store_id: i32,
+        req: AssignmentSettingsRequest,
+    ) -> Result<StoreAssignmentSettings, ApiError> {
+        self.repository.save(store_id, req.strategy).await
+    }
+
+    pub async fn delete_settings(&self, store_id: i32) -> Result<(), ApiError> {
+        self.repository.delete(store_id).await
+    }
+}
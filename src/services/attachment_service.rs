@@ -0,0 +1,126 @@
+use crate::errors::ApiError;
+use crate::models::attachment::*;
+use crate::models::auth::Actor;
+use crate::repositories::{AttachmentRepository, TaskRepository};
+use crate::storage::{BlobLocks, BlobStorage, LocalStorage, content_key};
+use actix_web::web::Bytes;
+
+#[derive(Clone)]
+pub struct AttachmentService<S: BlobStorage = LocalStorage> {
+    repository: AttachmentRepository,
+    tasks: TaskRepository,
+    storage: S,
+    locks: BlobLocks,
+    limits: AttachmentLimits,
+}
+
+impl<S: BlobStorage> AttachmentService<S> {
+    pub fn new(
+        repository: AttachmentRepository,
+        tasks: TaskRepository,
+        storage: S,
+        limits: AttachmentLimits,
+    ) -> Self {
+        Self {
+            repository,
+            tasks,
+            storage,
+            locks: BlobLocks::default(),
+            limits,
+        }
+    }
+
+    /// The largest upload accepted, so handlers can stop reading early.
+    pub fn max_bytes(&self) -> usize {
+        self.limits.max_bytes
+    }
+
+    pub async fn get_attachments(&self, task_id: i32) -> Result<Vec<TaskAttachment>, ApiError> {
+        self.tasks.find_by_id(task_id).await?;
+
+        self.repository.find_by_task(task_id).await
+    }
+
+    pub async fn get_attachment(
+        &self,
+        task_id: i32,
+        attachment_id: i32,
+    ) -> Result<TaskAttachment, ApiError> {
+        self.tasks.find_by_id(task_id).await?;
+
+        self.repository.find_by_id(task_id, attachment_id).await
+    }
+
+    pub async fn create_attachment(
+        &self,
+        task_id: i32,
+        file_name: Option<&str>,
+        data: Bytes,
+        actor: &Actor,
+    ) -> Result<TaskAttachment, ApiError> {
+        let content_type = self
+            .limits
+            .check(&data)
+            .map_err(ApiError::ValidationError)?;
+        self.tasks.find_by_id(task_id).await?;
+
+        let size_bytes = data.len() as i64;
+        // Held until the attachment row exists, so the contents cannot be
+        // removed as unreferenced in between.
+        let _lock = self.locks.lock(&content_key(&data)).await;
+        let sha256 = self.storage.put(data).await?;
+        self.repository
+            .create(
+                task_id,
+                &sanitize_file_name(file_name),
+                content_type,
+                size_bytes,
+                &sha256,
+                &actor.username,
+            )
+            .await
+    }
+
+    /// Reads bytes `start..=end` of an attachment's contents.
+    pub async fn read_attachment(
+        &self,
+        attachment: &TaskAttachment,
+        start: u64,
+        end: u64,
+    ) -> Result<Bytes, ApiError> {
+        self.storage.read(&attachment.sha256, start, end).await
+    }
+
+    /// Removes the stored contents among `sha256s` that no attachment refers
+    /// to any more, such as those left behind by a task purge.
+    pub async fn remove_unreferenced_contents(&self, sha256s: &[String]) -> Result<(), ApiError> {
+        for sha256 in sha256s {
+            let _lock = self.locks.lock(sha256).await;
+            if !self.repository.is_referenced(sha256).await? {
+                self.storage.delete(sha256).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Only the uploader, or an admin, may remove an attachment. The stored
+    /// contents go once no attachment refers to them.
+    pub async fn delete_attachment(
+        &self,
+        task_id: i32,
+        attachment_id: i32,
+        actor: &Actor,
+    ) -> Result<(), ApiError> {
+        let attachment = self.get_attachment(task_id, attachment_id).await?;
+        if attachment.uploaded_by != actor.username && !actor.is_admin() {
+            return Err(ApiError::Forbidden(
+                "Only the uploader can delete this attachment".to_string(),
+            ));
+        }
+
+        self.repository.delete(&attachment).await?;
+        self.remove_unreferenced_contents(std::slice::from_ref(&attachment.sha256))
+            .await
+    }
+}
@@ -12,6 +12,12 @@ pub struct AuthService {
     refresh_token_duration: Duration,
 }
 
+impl Default for AuthService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl AuthService {
     pub fn new() -> Self {
         let mut users = HashMap::new();
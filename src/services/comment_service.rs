@@ -0,0 +1,131 @@
+use crate::errors::ApiError;
+use crate::models::auth::Actor;
+use crate::models::comment::*;
+use crate::repositories::{CommentRepository, EmployeeRepository, TaskRepository};
+
+#[derive(Clone)]
+pub struct CommentService {
+    repository: CommentRepository,
+    tasks: TaskRepository,
+    employees: EmployeeRepository,
+}
+
+impl CommentService {
+    pub fn new(
+        repository: CommentRepository,
+        tasks: TaskRepository,
+        employees: EmployeeRepository,
+    ) -> Self {
+        Self {
+            repository,
+            tasks,
+            employees,
+        }
+    }
+
+    pub async fn get_comments(&self, task_id: i32) -> Result<Vec<TaskComment>, ApiError> {
+        self.tasks.find_by_id(task_id).await?;
+
+        self.repository.find_by_task(task_id).await
+    }
+
+    pub async fn get_comment(
+        &self,
+        task_id: i32,
+        comment_id: i32,
+    ) -> Result<TaskComment, ApiError> {
+        self.tasks.find_by_id(task_id).await?;
+
+        self.repository.find_by_id(task_id, comment_id).await
+    }
+
+    pub async fn create_comment(
+        &self,
+        task_id: i32,
+        req: CommentRequest,
+        actor: &Actor,
+    ) -> Result<TaskComment, ApiError> {
+        req.validate().map_err(ApiError::ValidationError)?;
+        self.tasks.find_by_id(task_id).await?;
+
+        self.repository
+            .create(task_id, &actor.username, &req.body)
+            .await
+    }
+
+    pub async fn update_comment(
+        &self,
+        task_id: i32,
+        comment_id: i32,
+        req: CommentRequest,
+        actor: &Actor,
+    ) -> Result<TaskComment, ApiError> {
+        req.validate().map_err(ApiError::ValidationError)?;
+        let comment = self.editable_comment(task_id, comment_id, actor).await?;
+
+        if comment.body == req.body {
+            return Ok(comment);
+        }
+
+        self.repository
+            .update(&comment, &req.body, &actor.username)
+            .await
+    }
+
+    pub async fn delete_comment(
+        &self,
+        task_id: i32,
+        comment_id: i32,
+        actor: &Actor,
+    ) -> Result<(), ApiError> {
+        let comment = self.editable_comment(task_id, comment_id, actor).await?;
+
+        self.repository.delete(comment.id).await
+    }
+
+    pub async fn get_comment_revisions(
+        &self,
+        task_id: i32,
+        comment_id: i32,
+    ) -> Result<Vec<CommentRevision>, ApiError> {
+        let comment = self.get_comment(task_id, comment_id).await?;
+
+        self.repository.revisions(comment.id).await
+    }
+
+    pub async fn get_task_mentions(
+        &self,
+        task_id: i32,
+    ) -> Result<Vec<MentionedEmployee>, ApiError> {
+        self.tasks.find_by_id(task_id).await?;
+
+        self.repository.mentioned_employees(task_id).await
+    }
+
+    pub async fn get_employee_mentions(
+        &self,
+        employee_id: i32,
+    ) -> Result<Vec<TaskComment>, ApiError> {
+        self.employees.find_by_id(employee_id).await?;
+
+        self.repository.find_mentioning(employee_id).await
+    }
+
+    /// Only a comment's author, or an admin, may change or remove it.
+    async fn editable_comment(
+        &self,
+        task_id: i32,
+        comment_id: i32,
+        actor: &Actor,
+    ) -> Result<TaskComment, ApiError> {
+        let comment = self.get_comment(task_id, comment_id).await?;
+
+        if comment.author != actor.username && !actor.is_admin() {
+            return Err(ApiError::Forbidden(
+                "Only the author can change this comment".to_string(),
+            ));
+        }
+
+        Ok(comment)
+    }
+}
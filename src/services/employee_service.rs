@@ -1,37 +1,74 @@
 use crate::errors::ApiError;
 use crate::models::employee::*;
 use crate::repositories::EmployeeRepository;
+use crate::utils::pagination::Page;
+use crate::utils::tabular::{self, CsvRow};
+use chrono::{Duration, Utc};
+use futures_util::Stream;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Imports with more rows than this run as background jobs unless the
+/// caller explicitly asks otherwise.
+const BACKGROUND_IMPORT_THRESHOLD: usize = 500;
+const IMPORT_JOB_RETENTION_HOURS: i64 = 24;
+const REQUIRED_IMPORT_COLUMNS: &[&str] = &["external_id", "first_name", "last_name"];
+/// Columns an import only overwrites on existing employees when the CSV
+/// actually contains them.
+const OPTIONAL_IMPORT_COLUMNS: &[&str] = &["store_id", "email"];
+
+pub enum ImportOutcome {
+    Completed(EmployeeImportReport),
+    Queued(ImportJob),
+}
 
 #[derive(Clone)]
 pub struct EmployeeService {
     repository: EmployeeRepository,
+    import_jobs: Arc<Mutex<HashMap<Uuid, ImportJob>>>,
 }
 
 impl EmployeeService {
     pub fn new(repository: EmployeeRepository) -> Self {
-        Self { repository }
+        Self {
+            repository,
+            import_jobs: Arc::new(Mutex::new(HashMap::new())),
+        }
     }
 
     pub async fn get_employee(&self, id: i32) -> Result<Employee, ApiError> {
         self.repository.find_by_id(id).await
     }
 
-    pub async fn get_employees(&self, query: EmployeeQuery) -> Result<serde_json::Value, ApiError> {
-        let (employees, total) = self.repository.find_all(query.clone()).await?;
+    pub async fn get_employees(&self, query: EmployeeQuery) -> Result<Page<Employee>, ApiError> {
+        self.repository.find_all(query).await
+    }
+
+    pub fn export_employees(
+        &self,
+        query: EmployeeQuery,
+    ) -> Result<impl Stream<Item = Result<Employee, ApiError>> + 'static, ApiError> {
+        self.repository.export(query)
+    }
+
+    pub async fn deactivate_employee(&self, id: i32) -> Result<Employee, ApiError> {
+        self.repository.deactivate(id).await
+    }
+
+    pub async fn reactivate_employee(&self, id: i32) -> Result<Employee, ApiError> {
+        self.repository.reactivate(id).await
+    }
 
-        Ok(serde_json::json!({
-            "employees": employees,
-            "total": total,
-            "offset": query.offset.unwrap_or(0),
-            "limit": query.limit.unwrap_or(10)
-        }))
+    pub async fn get_workload(&self, query: WorkloadQuery) -> Result<Workload, ApiError> {
+        self.repository.workload(query.store_id).await
     }
 
     pub async fn get_employees_by_store(
         &self,
         store_id: i32,
         query: EmployeeQuery,
-    ) -> Result<serde_json::Value, ApiError> {
+    ) -> Result<Page<Employee>, ApiError> {
         let mut store_query = query.clone();
         store_query.store_id = Some(store_id);
 
@@ -50,4 +87,188 @@ impl EmployeeService {
             .create_bulk(employee_request.employees)
             .await
     }
+
+    pub async fn import_employees(
+        &self,
+        data: &[u8],
+        query: EmployeeImportQuery,
+    ) -> Result<ImportOutcome, ApiError> {
+        let delimiter = tabular::parse_delimiter(query.delimiter.as_deref())
+            .map_err(ApiError::ValidationError)?;
+        let rows =
+            tabular::read_records::<CreateEmployee>(data, delimiter, REQUIRED_IMPORT_COLUMNS)
+                .map_err(ApiError::ValidationError)?;
+        let headers = tabular::read_headers(data, delimiter).map_err(ApiError::ValidationError)?;
+        let columns: Vec<&'static str> = OPTIONAL_IMPORT_COLUMNS
+            .iter()
+            .copied()
+            .filter(|column| headers.iter().any(|header| header == column))
+            .collect();
+
+        if rows.is_empty() {
+            return Err(ApiError::ValidationError(
+                "CSV file contains no rows".to_string(),
+            ));
+        }
+
+        let dry_run = query.dry_run.unwrap_or(false);
+        let background = query
+            .background
+            .unwrap_or(rows.len() > BACKGROUND_IMPORT_THRESHOLD);
+
+        if !background {
+            let report = self.run_import(rows, &columns, dry_run).await?;
+            return Ok(ImportOutcome::Completed(report));
+        }
+
+        let job = ImportJob::new(dry_run, rows.len());
+        self.store_import_job(job.clone());
+
+        let service = self.clone();
+        let job_id = job.id;
+        tokio::spawn(async move {
+            service
+                .process_import_job(job_id, rows, &columns, dry_run)
+                .await;
+        });
+
+        Ok(ImportOutcome::Queued(job))
+    }
+
+    pub fn get_import_job(&self, job_id: Uuid) -> Result<ImportJob, ApiError> {
+        self.import_jobs
+            .lock()
+            .unwrap()
+            .get(&job_id)
+            .cloned()
+            .ok_or_else(|| ApiError::NotFound("Import job not found".to_string()))
+    }
+
+    async fn process_import_job(
+        &self,
+        job_id: Uuid,
+        rows: Vec<CsvRow<CreateEmployee>>,
+        columns: &[&str],
+        dry_run: bool,
+    ) {
+        self.update_import_job(job_id, |job| job.status = ImportJobStatus::Running);
+
+        let result = self.run_import(rows, columns, dry_run).await;
+
+        self.update_import_job(job_id, |job| {
+            match result {
+                Ok(report) => {
+                    job.status = ImportJobStatus::Completed;
+                    job.report = Some(report);
+                }
+                Err(e) => {
+                    job.status = ImportJobStatus::Failed;
+                    job.error = Some(e.to_string());
+                }
+            }
+            job.finished_at = Some(Utc::now());
+        });
+    }
+
+    async fn run_import(
+        &self,
+        rows: Vec<CsvRow<CreateEmployee>>,
+        columns: &[&str],
+        dry_run: bool,
+    ) -> Result<EmployeeImportReport, ApiError> {
+        let mut report = EmployeeImportReport {
+            dry_run,
+            total_rows: rows.len(),
+            ..Default::default()
+        };
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        let mut accepted = Vec::new();
+
+        for row in rows {
+            let employee = match row.record {
+                Ok(employee) => employee,
+                Err(error) => {
+                    report.rejected.push(ImportRejection {
+                        line: row.line,
+                        external_id: None,
+                        error,
+                    });
+                    continue;
+                }
+            };
+
+            let validation =
+                employee
+                    .validate_fields()
+                    .and_then(|_| match seen.get(&employee.external_id) {
+                        Some(first_line) => Err(format!(
+                            "Duplicate external ID (first seen on line {})",
+                            first_line
+                        )),
+                        None => Ok(()),
+                    });
+
+            if let Err(error) = validation {
+                report.rejected.push(ImportRejection {
+                    line: row.line,
+                    external_id: Some(employee.external_id),
+                    error,
+                });
+                continue;
+            }
+
+            seen.insert(employee.external_id.clone(), row.line);
+            accepted.push(employee);
+        }
+
+        let external_ids: Vec<String> = accepted.iter().map(|e| e.external_id.clone()).collect();
+        let existing = self
+            .repository
+            .find_existing_external_ids(&external_ids)
+            .await?;
+
+        // Existing employees keep their store when the CSV has no store_id
+        // column; everyone else needs one.
+        let store_id_given = columns.contains(&"store_id");
+        let mut creates = Vec::new();
+        let mut updates = Vec::new();
+        for employee in accepted {
+            let exists = existing.contains(&employee.external_id);
+            if employee.store_id.is_none() && (store_id_given || !exists) {
+                report.rejected.push(ImportRejection {
+                    line: seen[&employee.external_id],
+                    external_id: Some(employee.external_id),
+                    error: "Store ID is required".to_string(),
+                });
+            } else if exists {
+                updates.push(employee);
+            } else {
+                creates.push(employee);
+            }
+        }
+        report.rejected.sort_by_key(|rejection| rejection.line);
+
+        report.created = creates.iter().map(|e| e.external_id.clone()).collect();
+        report.updated = updates.iter().map(|e| e.external_id.clone()).collect();
+
+        if !dry_run {
+            self.repository.import(creates, updates, columns).await?;
+        }
+
+        Ok(report)
+    }
+
+    fn store_import_job(&self, job: ImportJob) {
+        let cutoff = Utc::now() - Duration::hours(IMPORT_JOB_RETENTION_HOURS);
+        let mut jobs = self.import_jobs.lock().unwrap();
+
+        jobs.retain(|_, job| job.finished_at.is_none_or(|finished| finished > cutoff));
+        jobs.insert(job.id, job);
+    }
+
+    fn update_import_job(&self, job_id: Uuid, update: impl FnOnce(&mut ImportJob)) {
+        if let Some(job) = self.import_jobs.lock().unwrap().get_mut(&job_id) {
+            update(job);
+        }
+    }
 }
@@ -1,10 +1,20 @@
+pub mod assignment_service;
+pub mod attachment_service;
+pub mod comment_service;
 pub mod employee_service;
 pub mod object_service;
+pub mod report_service;
 pub mod task_service;
+pub mod task_template_service;
 
+pub use assignment_service::*;
+pub use attachment_service::*;
+pub use comment_service::*;
 pub use employee_service::*;
 pub use object_service::*;
+pub use report_service::*;
 pub use task_service::*;
+pub use task_template_service::*;
 
 pub mod auth_service;
 
@@ -1,6 +1,8 @@
 use crate::errors::ApiError;
 use crate::models::object::*;
 use crate::repositories::ObjectRepository;
+use crate::utils::pagination::Page;
+use futures_util::Stream;
 
 #[derive(Clone)]
 pub struct ObjectService {
@@ -22,15 +24,15 @@ impl ObjectService {
         self.repository.find_by_id(id).await
     }
 
-    pub async fn get_objects(&self, query: ObjectQuery) -> Result<serde_json::Value, ApiError> {
-        let (objects, total) = self.repository.find_all(query.clone()).await?;
+    pub async fn get_objects(&self, query: ObjectQuery) -> Result<Page<Object>, ApiError> {
+        self.repository.find_all(query).await
+    }
 
-        Ok(serde_json::json!({
-            "objects": objects,
-            "total": total,
-            "offset": query.offset.unwrap_or(0),
-            "limit": query.limit.unwrap_or(10)
-        }))
+    pub fn export_objects(
+        &self,
+        query: ObjectQuery,
+    ) -> Result<impl Stream<Item = Result<Object, ApiError>> + 'static, ApiError> {
+        self.repository.export(query)
     }
 
     pub async fn update_object(
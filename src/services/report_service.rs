@@ -0,0 +1,20 @@
+use crate::errors::ApiError;
+use crate::models::report::*;
+use crate::repositories::ReportRepository;
+
+#[derive(Clone)]
+pub struct ReportService {
+    repository: ReportRepository,
+}
+
+impl ReportService {
+    pub fn new(repository: ReportRepository) -> Self {
+        Self { repository }
+    }
+
+    pub async fn get_task_report(&self, query: TaskReportQuery) -> Result<TaskReport, ApiError> {
+        query.validate().map_err(ApiError::ValidationError)?;
+
+        self.repository.task_report(&query).await
+    }
+}
@@ -1,47 +1,376 @@
 use crate::errors::ApiError;
+use crate::models::auth::Actor;
+use crate::models::tag::{SetTagsRequest, TagUsage};
 use crate::models::task::*;
-use crate::repositories::TaskRepository;
+use crate::models::task_board::{Board, BoardQuery, MoveTaskRequest};
+use crate::models::task_bulk::{BulkTaskReport, BulkTaskRequest, MAX_BULK_TASKS};
+use crate::models::task_dependency::{AddDependencyRequest, TaskDependencies, TaskNode};
+use crate::models::task_event::TaskEvent;
+use crate::repositories::{EmployeeRepository, TaskRepository};
+use crate::services::AttachmentService;
+use crate::storage::BlobStorage;
+use crate::utils::pagination::Page;
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
 
 #[derive(Clone)]
 pub struct TaskService {
     repository: TaskRepository,
+    employees: EmployeeRepository,
+    sla_policy: SlaPolicy,
 }
 
 impl TaskService {
-    pub fn new(repository: TaskRepository) -> Self {
-        Self { repository }
+    pub fn new(
+        repository: TaskRepository,
+        employees: EmployeeRepository,
+        sla_policy: SlaPolicy,
+    ) -> Self {
+        Self {
+            repository,
+            employees,
+            sla_policy,
+        }
     }
 
-    pub async fn create_task(&self, req: CreateTaskRequest) -> Result<Task, ApiError> {
+    pub async fn create_task(
+        &self,
+        req: CreateTaskRequest,
+        actor: &Actor,
+    ) -> Result<Task, ApiError> {
         req.validate().map_err(ApiError::ValidationError)?;
 
-        self.repository.create(req).await
+        self.repository.create(req, actor).await
     }
 
-    pub async fn get_task(&self, id: i32) -> Result<Task, ApiError> {
-        self.repository.find_by_id(id).await
+    pub async fn get_task(
+        &self,
+        id: i32,
+        query: TaskLookupQuery,
+        actor: &Actor,
+    ) -> Result<Task, ApiError> {
+        let include_deleted = include_deleted(query.include_deleted, actor)?;
+        // Loaded through the tree so the task carries its subtask progress.
+        let mut task = self.repository.tree(id, include_deleted).await?.task;
+        self.apply_sla(&mut task, Utc::now());
+        self.attach_tags(std::slice::from_mut(&mut task)).await?;
+
+        Ok(task)
+    }
+
+    pub async fn get_tasks(&self, query: TaskQuery, actor: &Actor) -> Result<Page<Task>, ApiError> {
+        include_deleted(query.include_deleted, actor)?;
+
+        let mut page = self.repository.find_all(query).await?;
+        let now = Utc::now();
+        for task in &mut page.items {
+            self.apply_sla(task, now);
+        }
+        self.attach_tags(&mut page.items).await?;
+
+        Ok(page)
     }
 
-    pub async fn get_tasks(&self, query: TaskQuery) -> Result<serde_json::Value, ApiError> {
-        let (tasks, total) = self.repository.find_all(query.clone()).await?;
+    pub fn export_tasks(
+        &self,
+        query: TaskQuery,
+        actor: &Actor,
+    ) -> Result<impl Stream<Item = Result<Task, ApiError>> + 'static, ApiError> {
+        include_deleted(query.include_deleted, actor)?;
 
-        Ok(serde_json::json!({
-            "tasks": tasks,
-            "total": total,
-            "offset": query.offset.unwrap_or(0),
-            "limit": query.limit.unwrap_or(10)
-        }))
+        self.repository.export(query)
     }
-    pub async fn update_task(&self, id: i32, req: UpdateTaskRequest) -> Result<Task, ApiError> {
+
+    pub async fn update_task(
+        &self,
+        id: i32,
+        req: UpdateTaskRequest,
+        actor: &Actor,
+    ) -> Result<Task, ApiError> {
         req.validate().map_err(ApiError::ValidationError)?;
 
-        self.repository.update(id, req).await
+        self.repository.update(id, req, actor).await
     }
-    pub async fn delete_task(&self, id: i32) -> Result<(), ApiError> {
-        self.repository.delete(id).await
+
+    pub async fn bulk_update_tasks(
+        &self,
+        req: BulkTaskRequest,
+        actor: &Actor,
+    ) -> Result<BulkTaskReport, ApiError> {
+        req.validate().map_err(ApiError::ValidationError)?;
+        let action = req.action.normalized().map_err(ApiError::ValidationError)?;
+
+        let ids = match (req.ids, req.filter) {
+            (Some(mut ids), _) => {
+                let mut seen = std::collections::HashSet::new();
+                ids.retain(|id| seen.insert(*id));
+                ids
+            }
+            (None, Some(filter)) => {
+                include_deleted(filter.include_deleted, actor)?;
+                let ids = self
+                    .repository
+                    .find_ids(&filter, MAX_BULK_TASKS + 1)
+                    .await?;
+                if ids.len() > MAX_BULK_TASKS {
+                    return Err(ApiError::ValidationError(format!(
+                        "The filter matches more than {} tasks",
+                        MAX_BULK_TASKS
+                    )));
+                }
+                ids
+            }
+            (None, None) => Vec::new(),
+        };
+
+        let mut report = self
+            .repository
+            .bulk(&ids, &action, req.dry_run, actor)
+            .await?;
+        let now = Utc::now();
+        for task in report.results.iter_mut().filter_map(|r| r.task.as_mut()) {
+            self.apply_sla(task, now);
+        }
+
+        Ok(report)
     }
 
-    pub async fn assign_task(&self, task_id: i32, employee_id: i32) -> Result<Task, ApiError> {
-        self.repository.assign(task_id, employee_id).await
+    pub async fn get_board(&self, query: BoardQuery) -> Result<Board, ApiError> {
+        let mut board = self.repository.board(&query).await?;
+        let now = Utc::now();
+        let mut cards: Vec<&mut Task> = board
+            .lanes
+            .iter_mut()
+            .flat_map(|lane| lane.columns.iter_mut())
+            .flat_map(|column| column.tasks.iter_mut())
+            .collect();
+        for task in cards.iter_mut() {
+            self.apply_sla(task, now);
+        }
+        self.attach_tags(cards).await?;
+
+        Ok(board)
+    }
+
+    pub async fn move_task(
+        &self,
+        id: i32,
+        req: MoveTaskRequest,
+        actor: &Actor,
+    ) -> Result<Task, ApiError> {
+        req.validate(id).map_err(ApiError::ValidationError)?;
+
+        let mut task = self.repository.move_task(id, req, actor).await?;
+        self.apply_sla(&mut task, Utc::now());
+
+        Ok(task)
+    }
+
+    pub async fn get_task_transitions(&self, id: i32) -> Result<TaskTransitions, ApiError> {
+        let task = self.repository.find_by_id(id).await?;
+        let status = task.status.unwrap_or(TaskStatus::ToDo);
+
+        Ok(TaskTransitions {
+            status,
+            allowed: status.next_statuses().to_vec(),
+        })
+    }
+
+    pub async fn transition_task(
+        &self,
+        id: i32,
+        req: TransitionTaskRequest,
+        actor: &Actor,
+    ) -> Result<Task, ApiError> {
+        req.validate().map_err(ApiError::ValidationError)?;
+
+        self.repository.transition(id, req, actor).await
+    }
+
+    pub async fn delete_task(&self, id: i32, actor: &Actor) -> Result<(), ApiError> {
+        self.repository.delete(id, actor).await
+    }
+
+    pub async fn get_task_history(
+        &self,
+        id: i32,
+        actor: &Actor,
+    ) -> Result<Vec<TaskEvent>, ApiError> {
+        // Deleted tasks keep their history until they are purged, but only
+        // admins may read it.
+        let task = self.repository.find_by_id_including_deleted(id).await?;
+        if task.deleted_at.is_some() {
+            include_deleted(Some(true), actor)?;
+        }
+
+        self.repository.history(id).await
+    }
+
+    pub async fn restore_task(&self, id: i32, actor: &Actor) -> Result<Task, ApiError> {
+        include_deleted(Some(true), actor)?;
+
+        self.repository.restore(id, actor).await
+    }
+
+    /// Hard-deletes tasks soft-deleted before `deleted_before`, then the
+    /// attachment contents nothing refers to any more. Returns the number of
+    /// tasks purged.
+    pub async fn purge_deleted_tasks<S: BlobStorage>(
+        &self,
+        deleted_before: DateTime<Utc>,
+        attachments: &AttachmentService<S>,
+    ) -> Result<u64, ApiError> {
+        let purged = self.repository.purge_deleted(deleted_before).await?;
+        attachments
+            .remove_unreferenced_contents(&purged.attachment_sha256s)
+            .await?;
+
+        Ok(purged.count)
+    }
+
+    /// Hard-deletes tasks that have been soft-deleted for longer than
+    /// `retention`, every `interval`, for the lifetime of the process.
+    pub fn spawn_purge_job<S: BlobStorage>(
+        self,
+        attachments: AttachmentService<S>,
+        retention: chrono::Duration,
+        interval: std::time::Duration,
+    ) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                ticker.tick().await;
+                match self
+                    .purge_deleted_tasks(Utc::now() - retention, &attachments)
+                    .await
+                {
+                    Ok(0) => {}
+                    Ok(purged) => log::info!("Purged {} deleted tasks", purged),
+                    Err(e) => log::error!("Deleted task purge failed: {}", e),
+                }
+            }
+        });
     }
+
+    pub async fn get_task_tree(&self, id: i32) -> Result<TaskNode, ApiError> {
+        let mut tree = self.repository.tree(id, false).await?;
+        self.apply_sla_to_tree(&mut tree, Utc::now());
+
+        Ok(tree)
+    }
+
+    pub async fn get_task_dependencies(&self, id: i32) -> Result<TaskDependencies, ApiError> {
+        self.repository.find_by_id(id).await?;
+
+        self.repository.dependencies(id).await
+    }
+
+    pub async fn add_task_dependency(
+        &self,
+        id: i32,
+        req: AddDependencyRequest,
+        actor: &Actor,
+    ) -> Result<TaskDependencies, ApiError> {
+        self.repository
+            .add_dependency(id, req.depends_on, actor)
+            .await
+    }
+
+    pub async fn remove_task_dependency(
+        &self,
+        id: i32,
+        depends_on: i32,
+        actor: &Actor,
+    ) -> Result<TaskDependencies, ApiError> {
+        self.repository
+            .remove_dependency(id, depends_on, actor)
+            .await
+    }
+
+    pub async fn assign_task(
+        &self,
+        task_id: i32,
+        employee_id: i32,
+        actor: &Actor,
+    ) -> Result<Task, ApiError> {
+        self.repository.assign(task_id, employee_id, actor).await
+    }
+
+    /// Reassigns the task using its store's assignment strategy.
+    pub async fn auto_assign_task(&self, task_id: i32, actor: &Actor) -> Result<Task, ApiError> {
+        self.repository.auto_assign(task_id, actor).await
+    }
+
+    pub async fn unassign_task(&self, task_id: i32, actor: &Actor) -> Result<Task, ApiError> {
+        self.repository.unassign(task_id, actor).await
+    }
+
+    pub async fn get_employee_tasks(
+        &self,
+        employee_id: i32,
+        query: TaskQuery,
+        actor: &Actor,
+    ) -> Result<Page<Task>, ApiError> {
+        self.employees.find_by_id(employee_id).await?;
+
+        let mut employee_query = query;
+        employee_query.assigned_to = Some(employee_id);
+        self.get_tasks(employee_query, actor).await
+    }
+
+    pub async fn set_task_tags(
+        &self,
+        id: i32,
+        req: SetTagsRequest,
+        actor: &Actor,
+    ) -> Result<Task, ApiError> {
+        let tags = req.normalized().map_err(ApiError::ValidationError)?;
+
+        let mut task = self.repository.set_tags(id, tags, actor).await?;
+        self.apply_sla(&mut task, Utc::now());
+
+        Ok(task)
+    }
+
+    pub async fn get_tags(&self) -> Result<Vec<TagUsage>, ApiError> {
+        self.repository.tag_usage().await
+    }
+
+    async fn attach_tags<'a>(
+        &self,
+        tasks: impl IntoIterator<Item = &'a mut Task>,
+    ) -> Result<(), ApiError> {
+        let tasks: Vec<&mut Task> = tasks.into_iter().collect();
+        let ids: Vec<i32> = tasks.iter().map(|task| task.id).collect();
+        let mut tags = self.repository.tags_for(&ids).await?;
+        for task in tasks {
+            task.tags = Some(tags.remove(&task.id).unwrap_or_default());
+        }
+
+        Ok(())
+    }
+
+    fn apply_sla(&self, task: &mut Task, now: DateTime<Utc>) {
+        task.sla = Some(self.sla_policy.evaluate(task, now));
+    }
+
+    fn apply_sla_to_tree(&self, node: &mut TaskNode, now: DateTime<Utc>) {
+        self.apply_sla(&mut node.task, now);
+        for subtask in &mut node.subtasks {
+            self.apply_sla_to_tree(subtask, now);
+        }
+    }
+}
+
+/// Deleted tasks are only visible to admins.
+fn include_deleted(requested: Option<bool>, actor: &Actor) -> Result<bool, ApiError> {
+    let include_deleted = requested.unwrap_or(false);
+    if include_deleted && !actor.is_admin() {
+        return Err(ApiError::Forbidden(
+            "Only admins can access deleted tasks".to_string(),
+        ));
+    }
+
+    Ok(include_deleted)
 }
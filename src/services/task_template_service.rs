@@ -0,0 +1,164 @@
+use crate::errors::ApiError;
+use crate::models::task_template::*;
+use crate::repositories::TaskTemplateRepository;
+use chrono::{Days, NaiveDate, Utc};
+use std::time::Duration;
+
+#[derive(Clone)]
+pub struct TaskTemplateService {
+    repository: TaskTemplateRepository,
+    lookahead_days: u64,
+}
+
+impl TaskTemplateService {
+    pub fn new(repository: TaskTemplateRepository, lookahead_days: u64) -> Self {
+        Self {
+            repository,
+            lookahead_days,
+        }
+    }
+
+    pub async fn get_templates(&self) -> Result<Vec<TaskTemplate>, ApiError> {
+        self.repository.find_all().await
+    }
+
+    pub async fn get_template(&self, id: i32) -> Result<TaskTemplate, ApiError> {
+        self.repository.find_by_id(id).await
+    }
+
+    pub async fn create_template(
+        &self,
+        req: CreateTaskTemplateRequest,
+    ) -> Result<TaskTemplate, ApiError> {
+        req.validate().map_err(ApiError::ValidationError)?;
+
+        self.repository.create(req).await
+    }
+
+    pub async fn update_template(
+        &self,
+        id: i32,
+        req: UpdateTaskTemplateRequest,
+    ) -> Result<TaskTemplate, ApiError> {
+        req.validate().map_err(ApiError::ValidationError)?;
+
+        self.repository.update(id, req).await
+    }
+
+    pub async fn delete_template(&self, id: i32) -> Result<(), ApiError> {
+        self.repository.delete(id).await
+    }
+
+    /// Dates the template fires on between `from` and `to`, defaulting to
+    /// the scheduler's lookahead window.
+    pub async fn get_occurrences(
+        &self,
+        id: i32,
+        query: OccurrenceQuery,
+    ) -> Result<Vec<NaiveDate>, ApiError> {
+        let template = self.repository.find_by_id(id).await?;
+        let (default_from, default_to) = self.window();
+        let from = query.from.unwrap_or(default_from);
+        let to = query.to.unwrap_or(default_to);
+
+        if to < from {
+            return Err(ApiError::ValidationError(
+                "'to' must not be before 'from'".to_string(),
+            ));
+        }
+        if (to - from).num_days() > MAX_OCCURRENCE_WINDOW_DAYS {
+            return Err(ApiError::ValidationError(format!(
+                "Occurrence window cannot exceed {} days",
+                MAX_OCCURRENCE_WINDOW_DAYS
+            )));
+        }
+
+        Self::occurrences(&template, from, to)
+    }
+
+    /// Creates the tasks for one template's occurrences inside the lookahead
+    /// window. Occurrences that already have tasks are skipped.
+    pub async fn materialize_template(&self, id: i32) -> Result<MaterializeReport, ApiError> {
+        let template = self.repository.find_by_id(id).await?;
+        if !template.active {
+            return Err(ApiError::ValidationError(
+                "Task template is inactive".to_string(),
+            ));
+        }
+
+        let (from, to) = self.window();
+        let dates = Self::occurrences(&template, from, to)?;
+
+        self.repository.materialize(&template, &dates).await
+    }
+
+    pub async fn materialize_all(&self) -> Result<MaterializeReport, ApiError> {
+        let (from, to) = self.window();
+        let mut report = MaterializeReport::default();
+
+        // One broken template (say, its assignee was deactivated) must not
+        // hold up the others.
+        for template in self.repository.find_active().await? {
+            let created = match Self::occurrences(&template, from, to) {
+                Ok(dates) => self.repository.materialize(&template, &dates).await,
+                Err(e) => Err(e),
+            };
+            let created = match created {
+                Ok(created) => created,
+                Err(e) => {
+                    log::warn!("Skipping task template {}: {}", template.id, e);
+                    continue;
+                }
+            };
+
+            report.templates += 1;
+            report.existing += created.existing;
+            report.created_task_ids.extend(created.created_task_ids);
+        }
+
+        Ok(report)
+    }
+
+    /// Runs `materialize_all` every `interval` for the lifetime of the process.
+    pub fn spawn_scheduler(self, interval: Duration) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                ticker.tick().await;
+                match self.materialize_all().await {
+                    Ok(report) if !report.created_task_ids.is_empty() => log::info!(
+                        "Task template scheduler created {} tasks",
+                        report.created_task_ids.len()
+                    ),
+                    Ok(_) => {}
+                    Err(e) => log::error!("Task template scheduler failed: {}", e),
+                }
+            }
+        });
+    }
+
+    fn window(&self) -> (NaiveDate, NaiveDate) {
+        let today = Utc::now().date_naive();
+        let to = today
+            .checked_add_days(Days::new(self.lookahead_days))
+            .unwrap_or(NaiveDate::MAX);
+
+        (today, to)
+    }
+
+    fn occurrences(
+        template: &TaskTemplate,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<NaiveDate>, ApiError> {
+        let recurrence = template.recurrence().map_err(|e| {
+            ApiError::InternalServerError(format!(
+                "Task template {} has an invalid rule: {}",
+                template.id, e
+            ))
+        })?;
+
+        Ok(recurrence.occurrences(template.starts_on, from, to))
+    }
+}
@@ -0,0 +1,111 @@
+use crate::errors::ApiError;
+use crate::storage::{BlobStorage, content_key};
+use actix_web::web::Bytes;
+use std::io::{ErrorKind, SeekFrom};
+use std::path::PathBuf;
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+/// Keeps blobs on the local disk under `root`, as `root/ab/abcdef...`.
+#[derive(Clone, Debug)]
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Keys are only ever hex digests; anything else could escape `root`.
+    fn path_for(&self, key: &str) -> Result<PathBuf, ApiError> {
+        if key.len() != 64 || !key.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+            return Err(ApiError::InternalServerError(format!(
+                "Invalid storage key: {}",
+                key
+            )));
+        }
+
+        Ok(self.root.join(&key[..2]).join(key))
+    }
+}
+
+impl BlobStorage for LocalStorage {
+    async fn put(&self, data: Bytes) -> Result<String, ApiError> {
+        let key = content_key(&data);
+        let path = self.path_for(&key)?;
+        if fs::try_exists(&path).await.map_err(storage_error)? {
+            return Ok(key);
+        }
+
+        // Written to a temporary file first so a blob is never visible
+        // half-written under its key.
+        let dir = path.parent().unwrap_or(&self.root);
+        fs::create_dir_all(dir).await.map_err(storage_error)?;
+        let temp = dir.join(format!(".{}.{}", key, uuid::Uuid::new_v4()));
+        fs::write(&temp, &data).await.map_err(storage_error)?;
+        if let Err(e) = fs::rename(&temp, &path).await {
+            fs::remove_file(&temp).await.ok();
+            return Err(storage_error(e));
+        }
+
+        Ok(key)
+    }
+
+    async fn read(&self, key: &str, start: u64, end: u64) -> Result<Bytes, ApiError> {
+        let path = self.path_for(key)?;
+        let mut file = fs::File::open(&path).await.map_err(|e| match e.kind() {
+            ErrorKind::NotFound => ApiError::NotFound("Attachment content not found".to_string()),
+            _ => storage_error(e),
+        })?;
+
+        let mut data = vec![0; (end - start + 1) as usize];
+        file.seek(SeekFrom::Start(start))
+            .await
+            .map_err(storage_error)?;
+        file.read_exact(&mut data).await.map_err(storage_error)?;
+
+        Ok(Bytes::from(data))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), ApiError> {
+        let path = self.path_for(key)?;
+        match fs::remove_file(&path).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(storage_error(e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn storage_error(e: std::io::Error) -> ApiError {
+    ApiError::InternalServerError(format!("Storage error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_blobs_are_stored_once_and_read_by_range() {
+        let root = std::env::temp_dir().join(format!("attachments-{}", uuid::Uuid::new_v4()));
+        let storage = LocalStorage::new(&root);
+
+        let key = storage.put(Bytes::from_static(b"%PDF-1.7")).await.unwrap();
+        let again = storage.put(Bytes::from_static(b"%PDF-1.7")).await.unwrap();
+        assert_eq!(key, again);
+        assert_eq!(std::fs::read_dir(root.join(&key[..2])).unwrap().count(), 1);
+
+        assert_eq!(storage.read(&key, 1, 3).await.unwrap(), "PDF");
+        assert_eq!(storage.read(&key, 0, 7).await.unwrap(), "%PDF-1.7");
+
+        storage.delete(&key).await.unwrap();
+        storage.delete(&key).await.unwrap();
+        let missing = storage.read(&key, 0, 0).await;
+        assert!(matches!(missing, Err(ApiError::NotFound(_))));
+
+        let escape = storage.read("../../etc/passwd", 0, 0).await;
+        assert!(matches!(escape, Err(ApiError::InternalServerError(_))));
+
+        std::fs::remove_dir_all(root).ok();
+    }
+}
@@ -0,0 +1,76 @@
+pub mod local;
+
+pub use local::*;
+
+use crate::errors::ApiError;
+use actix_web::web::Bytes;
+use sha2::{Digest, Sha256};
+use std::future::Future;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+use tokio::sync::{Mutex, MutexGuard};
+
+const LOCK_STRIPES: usize = 64;
+
+/// Where attachment contents are kept. Blobs are addressed by the hex SHA-256
+/// of their contents, so identical uploads share a single blob.
+pub trait BlobStorage: Clone + Send + Sync + 'static {
+    /// Stores `data` and returns its key. Storing contents that already
+    /// exist is a no-op.
+    fn put(&self, data: Bytes) -> impl Future<Output = Result<String, ApiError>> + Send;
+
+    /// Reads bytes `start..=end` of the blob stored under `key`.
+    fn read(
+        &self,
+        key: &str,
+        start: u64,
+        end: u64,
+    ) -> impl Future<Output = Result<Bytes, ApiError>> + Send;
+
+    /// Removes the blob stored under `key`, if there is one.
+    fn delete(&self, key: &str) -> impl Future<Output = Result<(), ApiError>> + Send;
+}
+
+/// The key `data` is stored under.
+pub fn content_key(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// Serializes work on a blob key within this process, so that checking
+/// whether contents are still referenced and deleting them cannot interleave
+/// with an upload of the same contents. Keys share a fixed set of locks.
+#[derive(Clone)]
+pub struct BlobLocks {
+    stripes: Arc<[Mutex<()>]>,
+}
+
+impl Default for BlobLocks {
+    fn default() -> Self {
+        Self {
+            stripes: (0..LOCK_STRIPES).map(|_| Mutex::new(())).collect(),
+        }
+    }
+}
+
+impl BlobLocks {
+    pub async fn lock(&self, key: &str) -> MutexGuard<'_, ()> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        let stripe = hasher.finish() as usize % self.stripes.len();
+
+        self.stripes[stripe].lock().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_key_is_the_sha256_hex_digest() {
+        assert_eq!(
+            content_key(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -1,3 +1,5 @@
+pub mod pagination;
 pub mod responses;
+pub mod tabular;
 
 pub use responses::*;
\ No newline at end of file
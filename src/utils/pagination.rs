@@ -0,0 +1,110 @@
+use crate::utils::ApiResponse;
+use actix_web::http::header::LINK;
+use actix_web::{HttpRequest, HttpResponse};
+use serde::Serialize;
+
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: Option<usize>,
+    pub limit: usize,
+    pub offset: Option<usize>,
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
+}
+
+impl<T: Serialize> Page<T> {
+    pub fn to_json(&self, key: &str) -> serde_json::Value {
+        let mut value = serde_json::json!({
+            "total": self.total,
+            "offset": self.offset,
+            "limit": self.limit,
+            "next_cursor": self.next_cursor,
+            "prev_cursor": self.prev_cursor
+        });
+        value[key] = serde_json::json!(self.items);
+        value
+    }
+}
+
+/// Wraps a page in the standard `ApiResponse` body and advertises its
+/// neighbours in a `Link` header.
+pub fn paginated_response<T: Serialize>(
+    req: &HttpRequest,
+    page: &Page<T>,
+    key: &str,
+    message: &str,
+) -> HttpResponse {
+    let mut response = HttpResponse::Ok();
+    if let Some(link) = link_header(req, page) {
+        response.insert_header((LINK, link));
+    }
+    response.json(ApiResponse::success(page.to_json(key), message))
+}
+
+fn link_header<T>(req: &HttpRequest, page: &Page<T>) -> Option<String> {
+    let params: Vec<&str> = req
+        .query_string()
+        .split('&')
+        .filter(|param| {
+            !param.is_empty() && !param.starts_with("cursor=") && !param.starts_with("offset=")
+        })
+        .collect();
+
+    let links: Vec<String> = [("next", &page.next_cursor), ("prev", &page.prev_cursor)]
+        .into_iter()
+        .filter_map(|(rel, cursor)| {
+            let cursor = cursor.as_ref()?;
+            let mut query = params.join("&");
+            if !query.is_empty() {
+                query.push('&');
+            }
+            query.push_str(&format!("cursor={}", cursor));
+            Some(format!("<{}?{}>; rel=\"{}\"", req.path(), query, rel))
+        })
+        .collect();
+
+    (!links.is_empty()).then(|| links.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_header_replaces_cursor_and_offset() {
+        let req = actix_web::test::TestRequest::get()
+            .uri("/tasks/?status=ToDo&offset=10&cursor=old&limit=5")
+            .to_http_request();
+        let page: Page<i32> = Page {
+            items: vec![1],
+            total: None,
+            limit: 5,
+            offset: None,
+            next_cursor: Some("abc".to_string()),
+            prev_cursor: Some("xyz".to_string()),
+        };
+
+        assert_eq!(
+            link_header(&req, &page).unwrap(),
+            "</tasks/?status=ToDo&limit=5&cursor=abc>; rel=\"next\", </tasks/?status=ToDo&limit=5&cursor=xyz>; rel=\"prev\""
+        );
+    }
+
+    #[test]
+    fn test_link_header_without_cursors() {
+        let req = actix_web::test::TestRequest::get()
+            .uri("/tasks/")
+            .to_http_request();
+        let page: Page<i32> = Page {
+            items: vec![],
+            total: Some(0),
+            limit: 10,
+            offset: Some(0),
+            next_cursor: None,
+            prev_cursor: None,
+        };
+
+        assert!(link_header(&req, &page).is_none());
+    }
+}
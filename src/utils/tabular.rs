@@ -0,0 +1,372 @@
+use crate::errors::ApiError;
+use actix_web::http::header::{ACCEPT, CONTENT_DISPOSITION};
+use actix_web::web::Bytes;
+use actix_web::{HttpRequest, HttpResponse};
+use futures_util::{Stream, StreamExt, stream};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ExportQuery {
+    pub format: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Tsv,
+    Ndjson,
+}
+
+/// A record that can be written as one row of a tabular export. `COLUMNS`
+/// must list the serialized field names in declaration order.
+pub trait Exportable: Serialize {
+    const COLUMNS: &'static [&'static str];
+}
+
+pub struct CsvRow<T> {
+    pub line: usize,
+    pub record: Result<T, String>,
+}
+
+/// Resolves the `delimiter` query parameter. Accepts a single ASCII character
+/// or the names `comma`, `semicolon`, `tab` and `pipe`.
+pub fn parse_delimiter(value: Option<&str>) -> Result<u8, String> {
+    let value = match value {
+        Some(value) => value,
+        None => return Ok(b','),
+    };
+
+    match value.to_ascii_lowercase().as_str() {
+        "comma" => Ok(b','),
+        "semicolon" => Ok(b';'),
+        "tab" | "\\t" => Ok(b'\t'),
+        "pipe" => Ok(b'|'),
+        _ if value.len() == 1 && value.is_ascii() => Ok(value.as_bytes()[0]),
+        _ => Err(format!("Invalid delimiter: {}", value)),
+    }
+}
+
+/// Normalizes a header cell so that "First Name", "first-name" and
+/// "FIRST_NAME" all map to the `first_name` field.
+pub fn normalize_header(header: &str) -> String {
+    header.trim().to_ascii_lowercase().replace([' ', '-'], "_")
+}
+
+fn reader(data: &[u8], delimiter: u8) -> csv::Reader<&[u8]> {
+    let data = data.strip_prefix(UTF8_BOM).unwrap_or(data);
+
+    csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(data)
+}
+
+fn normalized_headers(reader: &mut csv::Reader<&[u8]>) -> Result<csv::StringRecord, String> {
+    Ok(reader
+        .headers()
+        .map_err(|e| format!("Invalid CSV header: {}", e))?
+        .iter()
+        .map(normalize_header)
+        .collect())
+}
+
+/// Returns the normalized header row of a CSV document, so callers can tell
+/// a column that was left out from one whose cells are empty.
+pub fn read_headers(data: &[u8], delimiter: u8) -> Result<Vec<String>, String> {
+    let headers = normalized_headers(&mut reader(data, delimiter))?;
+    Ok(headers.iter().map(str::to_string).collect())
+}
+
+/// Reads every record of a CSV document into `T`, mapping columns by header
+/// name. Header problems fail the whole document; row problems are reported
+/// per row so callers can reject individual lines.
+pub fn read_records<T: DeserializeOwned>(
+    data: &[u8],
+    delimiter: u8,
+    required_headers: &[&str],
+) -> Result<Vec<CsvRow<T>>, String> {
+    let mut reader = reader(data, delimiter);
+    let headers = normalized_headers(&mut reader)?;
+
+    for required in required_headers {
+        if !headers.iter().any(|h| h == *required) {
+            return Err(format!("Missing required column: {}", required));
+        }
+    }
+
+    let mut rows = Vec::new();
+    for result in reader.records() {
+        let (line, record) = match result {
+            Ok(record) => {
+                let line = record.position().map(|p| p.line() as usize).unwrap_or(0);
+                let parsed = record
+                    .deserialize::<T>(Some(&headers))
+                    .map_err(|e| e.to_string());
+                (line, parsed)
+            }
+            Err(e) => {
+                let line = e.position().map(|p| p.line() as usize).unwrap_or(0);
+                (line, Err(e.to_string()))
+            }
+        };
+        rows.push(CsvRow { line, record });
+    }
+
+    Ok(rows)
+}
+
+impl ExportFormat {
+    /// Picks the response format from `?format=` first, then the `Accept`
+    /// header. Anything unrecognised in `Accept` falls back to JSON.
+    pub fn negotiate(format: Option<&str>, req: &HttpRequest) -> Result<Self, ApiError> {
+        if let Some(format) = format {
+            return match format.to_ascii_lowercase().as_str() {
+                "json" => Ok(ExportFormat::Json),
+                "csv" => Ok(ExportFormat::Csv),
+                "tsv" => Ok(ExportFormat::Tsv),
+                "ndjson" | "jsonl" => Ok(ExportFormat::Ndjson),
+                _ => Err(ApiError::ValidationError(format!(
+                    "Unsupported format: {}",
+                    format
+                ))),
+            };
+        }
+
+        let accept = req
+            .headers()
+            .get(ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("");
+
+        for media_type in accept.split(',') {
+            let media_type = media_type.split(';').next().unwrap_or("").trim();
+            match media_type {
+                "application/json" => return Ok(ExportFormat::Json),
+                "text/csv" => return Ok(ExportFormat::Csv),
+                "text/tab-separated-values" => return Ok(ExportFormat::Tsv),
+                "application/x-ndjson" | "application/ndjson" => return Ok(ExportFormat::Ndjson),
+                _ => {}
+            }
+        }
+
+        Ok(ExportFormat::Json)
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Tsv => "text/tab-separated-values; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Tsv => "tsv",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+
+    fn delimiter(&self) -> u8 {
+        match self {
+            ExportFormat::Tsv => b'\t',
+            _ => b',',
+        }
+    }
+
+    pub fn header_line<T: Exportable>(&self) -> Option<Bytes> {
+        match self {
+            ExportFormat::Csv | ExportFormat::Tsv => {
+                let mut writer = self.row_writer();
+                writer.write_record(T::COLUMNS).ok()?;
+                writer.into_inner().ok().map(Bytes::from)
+            }
+            ExportFormat::Json | ExportFormat::Ndjson => None,
+        }
+    }
+
+    pub fn record_line<T: Exportable>(&self, record: &T) -> Result<Bytes, String> {
+        match self {
+            ExportFormat::Csv | ExportFormat::Tsv => {
+                let mut writer = self.row_writer();
+                writer.serialize(record).map_err(|e| e.to_string())?;
+                writer
+                    .into_inner()
+                    .map(Bytes::from)
+                    .map_err(|e| e.to_string())
+            }
+            ExportFormat::Json | ExportFormat::Ndjson => {
+                let mut line = serde_json::to_vec(record).map_err(|e| e.to_string())?;
+                line.push(b'\n');
+                Ok(Bytes::from(line))
+            }
+        }
+    }
+
+    fn row_writer(&self) -> csv::Writer<Vec<u8>> {
+        csv::WriterBuilder::new()
+            .delimiter(self.delimiter())
+            .has_headers(false)
+            .from_writer(Vec::new())
+    }
+}
+
+/// Streams `records` to the client in `format`, one row per chunk, so large
+/// exports never have to be held in memory.
+pub fn export_response<T, S>(format: ExportFormat, name: &str, records: S) -> HttpResponse
+where
+    T: Exportable + 'static,
+    S: Stream<Item = Result<T, ApiError>> + 'static,
+{
+    let header = stream::iter(format.header_line::<T>().map(Ok));
+    let rows = records.map(move |record| {
+        record.and_then(|record| {
+            format
+                .record_line(&record)
+                .map_err(|e| ApiError::InternalServerError(format!("Export error: {}", e)))
+        })
+    });
+
+    HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header((
+            CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}.{}\"", name, format.extension()),
+        ))
+        .streaming(header.chain(rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Row {
+        external_id: String,
+        store_id: Option<i32>,
+    }
+
+    #[derive(Serialize)]
+    struct ExportRow {
+        id: i32,
+        name: String,
+        note: Option<String>,
+    }
+
+    impl Exportable for ExportRow {
+        const COLUMNS: &'static [&'static str] = &["id", "name", "note"];
+    }
+
+    #[test]
+    fn test_negotiate_format() {
+        let req = actix_web::test::TestRequest::default()
+            .insert_header((ACCEPT, "text/tab-separated-values, */*;q=0.8"))
+            .to_http_request();
+
+        assert_eq!(
+            ExportFormat::negotiate(None, &req).unwrap(),
+            ExportFormat::Tsv
+        );
+        assert_eq!(
+            ExportFormat::negotiate(Some("ndjson"), &req).unwrap(),
+            ExportFormat::Ndjson
+        );
+        assert!(ExportFormat::negotiate(Some("xlsx"), &req).is_err());
+
+        let req = actix_web::test::TestRequest::default().to_http_request();
+        assert_eq!(
+            ExportFormat::negotiate(None, &req).unwrap(),
+            ExportFormat::Json
+        );
+    }
+
+    #[test]
+    fn test_record_lines() {
+        let row = ExportRow {
+            id: 1,
+            name: "Smith, John".to_string(),
+            note: None,
+        };
+
+        assert_eq!(
+            ExportFormat::Csv.header_line::<ExportRow>().unwrap(),
+            "id,name,note\n"
+        );
+        assert_eq!(
+            ExportFormat::Csv.record_line(&row).unwrap(),
+            "1,\"Smith, John\",\n"
+        );
+        assert_eq!(
+            ExportFormat::Tsv.record_line(&row).unwrap(),
+            "1\tSmith, John\t\n"
+        );
+        assert_eq!(
+            ExportFormat::Ndjson.record_line(&row).unwrap(),
+            "{\"id\":1,\"name\":\"Smith, John\",\"note\":null}\n"
+        );
+        assert!(ExportFormat::Ndjson.header_line::<ExportRow>().is_none());
+    }
+
+    #[test]
+    fn test_parse_delimiter() {
+        assert_eq!(parse_delimiter(None).unwrap(), b',');
+        assert_eq!(parse_delimiter(Some(";")).unwrap(), b';');
+        assert_eq!(parse_delimiter(Some("tab")).unwrap(), b'\t');
+        assert!(parse_delimiter(Some(";;")).is_err());
+    }
+
+    #[test]
+    fn test_read_records_strips_bom_and_normalizes_headers() {
+        let data = "\u{FEFF}External ID;Store-ID\nEMP001;1\nEMP002;\n".as_bytes();
+        let rows = read_records::<Row>(data, b';', &["external_id"]).unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].line, 2);
+        assert_eq!(
+            rows[0].record.as_ref().unwrap(),
+            &Row {
+                external_id: "EMP001".to_string(),
+                store_id: Some(1)
+            }
+        );
+        assert_eq!(rows[1].record.as_ref().unwrap().store_id, None);
+    }
+
+    #[test]
+    fn test_read_headers() {
+        let data = "\u{FEFF}External ID,First Name\nEMP001,John\n".as_bytes();
+
+        assert_eq!(
+            read_headers(data, b',').unwrap(),
+            ["external_id", "first_name"]
+        );
+    }
+
+    #[test]
+    fn test_read_records_reports_bad_rows() {
+        let data = b"external_id,store_id\nEMP001,abc\n";
+        let rows = read_records::<Row>(data, b',', &[]).unwrap();
+
+        assert!(rows[0].record.is_err());
+    }
+
+    #[test]
+    fn test_read_records_missing_required_column() {
+        let data = b"first_name\nJohn\n";
+        let result = read_records::<Row>(data, b',', &["external_id"]);
+
+        assert_eq!(
+            result.err().unwrap(),
+            "Missing required column: external_id"
+        );
+    }
+}
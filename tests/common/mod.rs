@@ -1,108 +1,62 @@
-use sqlx::SqlitePool;
+#![allow(dead_code)]
 
-pub async fn create_test_pool() -> SqlitePool {
-    let test_db_url = "sqlite::memory:";
-    let pool = SqlitePool::connect(test_db_url).await.unwrap();
+use rust_api_framework::database::{self, DbPool, run_migrations};
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::sqlite::SqlitePoolOptions;
+use std::str::FromStr;
 
-    // Run migrations for objects table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS objects (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT NOT NULL,
-            email TEXT NOT NULL,
-            age INTEGER,
-            created_at DATETIME NOT NULL,
-            updated_at DATETIME NOT NULL
-        )
-        "#,
-    )
-    .execute(&pool)
-    .await
-    .unwrap();
+/// An in-memory database with the production schema. It has a single
+/// connection because each in-memory connection is a separate database.
+pub async fn create_test_pool() -> DbPool {
+    let pool: DbPool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap()
+        .into();
 
-    sqlx::query(
-        r#"
-        CREATE INDEX IF NOT EXISTS idx_objects_email ON objects(email)
-        "#,
-    )
-    .execute(&pool)
-    .await
-    .unwrap();
-
-    sqlx::query(
-        r#"
-        CREATE INDEX IF NOT EXISTS idx_objects_created_at ON objects(created_at)
-        "#,
-    )
-    .execute(&pool)
-    .await
-    .unwrap();
-
-    // Add employees table (needed for tasks foreign key)
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS employees (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            external_id TEXT,
-            first_name TEXT NOT NULL,
-            last_name TEXT NOT NULL,
-            store_id INTEGER NOT NULL
-        )
-        "#,
-    )
-    .execute(&pool)
-    .await
-    .unwrap();
-
-    // Create employees indexes
-    sqlx::query(
-        r#"
-        CREATE INDEX IF NOT EXISTS idx_employees_store_id ON employees(store_id)
-        "#,
-    )
-    .execute(&pool)
-    .await
-    .unwrap();
-
-    sqlx::query(
-        r#"
-        CREATE INDEX IF NOT EXISTS idx_employees_external_id ON employees(external_id)
-        "#,
-    )
-    .execute(&pool)
-    .await
-    .unwrap();
-
-    sqlx::query(
-        r#"
-        CREATE INDEX IF NOT EXISTS idx_employees_name ON employees(last_name, first_name)
-        "#,
-    )
-    .execute(&pool)
-    .await
-    .unwrap();
-
-    // Run migrations for tasks table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS tasks (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            title TEXT NOT NULL,
-            description TEXT,
-            priority_level TEXT,
-            status TEXT NOT NULL,
-            assigned_to INTEGER,
-            completed_at DATETIME,
-            created_at DATETIME NOT NULL,
-            updated_at DATETIME NOT NULL,
-            FOREIGN KEY (assigned_to) REFERENCES employees(id) ON DELETE SET NULL
-        )
-        "#,
-    )
-    .execute(&pool)
-    .await
-    .unwrap();
+    run_migrations(&pool).await.unwrap();
 
     pool
 }
+
+/// Runs `test` against a fresh in-memory SQLite database and, when
+/// `TEST_POSTGRES_URL` is set, against PostgreSQL as well. Each Postgres run
+/// gets its own schema, dropped afterwards, so tests can share one server.
+pub async fn for_each_backend(test: impl AsyncFn(DbPool)) {
+    test(create_test_pool().await).await;
+
+    let Ok(url) = std::env::var("TEST_POSTGRES_URL") else {
+        return;
+    };
+    let schema = format!("test_{}", uuid::Uuid::new_v4().simple());
+    let admin: DbPool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&url)
+        .await
+        .unwrap()
+        .into();
+    database::query(&format!("CREATE SCHEMA {}", schema))
+        .execute(&admin)
+        .await
+        .unwrap();
+
+    let options = PgConnectOptions::from_str(&url)
+        .unwrap()
+        .options([("search_path", schema.as_str()), ("TimeZone", "UTC")]);
+    let pool: DbPool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect_with(options)
+        .await
+        .unwrap()
+        .into();
+    run_migrations(&pool).await.unwrap();
+
+    test(pool.clone()).await;
+
+    pool.close().await;
+    database::query(&format!("DROP SCHEMA {} CASCADE", schema))
+        .execute(&admin)
+        .await
+        .unwrap();
+}
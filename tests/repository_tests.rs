@@ -0,0 +1,1123 @@
+mod common;
+
+use actix_web::web::Bytes;
+use chrono::{DateTime, Duration, Utc};
+use common::for_each_backend;
+use futures_util::StreamExt;
+use rust_api_framework::database::{self, DbPool};
+use rust_api_framework::errors::ApiError;
+use rust_api_framework::models::attachment::{AttachmentLimits, TaskAttachment};
+use rust_api_framework::models::auth::Actor;
+use rust_api_framework::models::report::ReportKey;
+use rust_api_framework::models::task::{PriorityLevel, SlaPolicy, TaskStatus};
+use rust_api_framework::models::task_template::CreateTaskTemplateRequest;
+use rust_api_framework::repositories::{
+    AttachmentRepository, CommentRepository, EmployeeRepository, ObjectRepository,
+    ReportRepository, TaskRepository, TaskTemplateRepository,
+};
+use rust_api_framework::services::{
+    AttachmentService, CommentService, EmployeeService, ImportOutcome, TaskService,
+};
+use rust_api_framework::storage::{BlobStorage, LocalStorage};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Builds a request or query model the way a handler would receive it.
+fn from_json<T: DeserializeOwned>(value: serde_json::Value) -> T {
+    serde_json::from_value(value).unwrap()
+}
+
+fn actor() -> Actor {
+    Actor::system("tests")
+}
+
+fn user(username: &str, role: &str) -> Actor {
+    Actor {
+        username: username.to_string(),
+        role: role.to_string(),
+    }
+}
+
+fn task_service(pool: &DbPool) -> TaskService {
+    TaskService::new(
+        TaskRepository::new(pool.clone()),
+        EmployeeRepository::new(pool.clone()),
+        SlaPolicy::default(),
+    )
+}
+
+fn at(value: &str) -> DateTime<Utc> {
+    value.parse().unwrap()
+}
+
+async fn add_employees(pool: &DbPool, count: usize) -> Vec<i32> {
+    let employees = (1..=count)
+        .map(|n| {
+            from_json(json!({
+                "external_id": format!("EMP{:03}", n),
+                "first_name": "Test",
+                "last_name": format!("Employee {}", n),
+                "store_id": 1,
+            }))
+        })
+        .collect();
+
+    EmployeeRepository::new(pool.clone())
+        .create_bulk(employees)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|employee| employee.id)
+        .collect()
+}
+
+#[actix_web::test]
+async fn test_objects_round_trip_and_page_by_cursor() {
+    for_each_backend(async |pool| {
+        let repository = ObjectRepository::new(pool);
+        for (name, age) in [("Alice", 30), ("alfred", 41), ("Bob", 25)] {
+            repository
+                .create(from_json(json!({
+                    "name": name,
+                    "email": format!("{}@example.com", name.to_lowercase()),
+                    "age": age,
+                })))
+                .await
+                .unwrap();
+        }
+
+        let first = repository
+            .find_all(from_json(
+                json!({ "name": "AL", "sort": "name", "limit": 1 }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(first.items[0].name, "Alice");
+        let second = repository
+            .find_all(from_json(json!({
+                "name": "AL",
+                "sort": "name",
+                "limit": 1,
+                "cursor": first.next_cursor.unwrap(),
+            })))
+            .await
+            .unwrap();
+        assert_eq!(second.items[0].name, "alfred");
+        assert_eq!(second.next_cursor, None);
+
+        let bob = repository
+            .update(3, from_json(json!({ "age": 26 })))
+            .await
+            .unwrap();
+        assert_eq!((bob.name.as_str(), bob.age), ("Bob", Some(26)));
+        repository.delete(bob.id).await.unwrap();
+        assert!(matches!(
+            repository.find_by_id(bob.id).await,
+            Err(ApiError::NotFound(_))
+        ));
+    })
+    .await;
+}
+
+#[actix_web::test]
+async fn test_object_name_filter_pages_by_cursor_on_every_sort() {
+    for_each_backend(async |pool| {
+        let repository = ObjectRepository::new(pool);
+        let names = ["Anna", "Hannah", "Johan", "Nadia", "Bob", "Dan"];
+        for (n, name) in names.iter().enumerate() {
+            repository
+                .create(from_json(json!({
+                    "name": name,
+                    "email": format!("{}@example.com", name.to_lowercase()),
+                    "age": 20 + (n * 7) % 5,
+                })))
+                .await
+                .unwrap();
+        }
+
+        for filter in [None, Some("an"), Some("AN"), Some("zz")] {
+            for sort in ["name", "-name", "age,-id", "-email"] {
+                let mut expected: Vec<(&str, usize, usize)> = names
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, name)| {
+                        filter.is_none_or(|f| name.to_lowercase().contains(&f.to_lowercase()))
+                    })
+                    .map(|(n, name)| (*name, 20 + (n * 7) % 5, n + 1))
+                    .collect();
+                match sort {
+                    "name" => expected.sort_by_key(|(name, _, _)| name.to_string()),
+                    "-name" => {
+                        expected.sort_by_key(|(name, _, _)| std::cmp::Reverse(name.to_string()))
+                    }
+                    "age,-id" => {
+                        expected.sort_by_key(|(_, age, id)| (*age, std::cmp::Reverse(*id)))
+                    }
+                    _ => {
+                        expected.sort_by_key(|(name, _, _)| std::cmp::Reverse(name.to_lowercase()))
+                    }
+                }
+                let expected: Vec<&str> = expected.into_iter().map(|(name, _, _)| name).collect();
+
+                let mut found = Vec::new();
+                let mut cursor: Option<String> = None;
+                loop {
+                    let page = repository
+                        .find_all(from_json(json!({
+                            "name": filter,
+                            "sort": sort,
+                            "limit": 2,
+                            "cursor": cursor,
+                        })))
+                        .await
+                        .unwrap();
+                    assert_eq!(page.total, Some(expected.len()), "{:?} {}", filter, sort);
+                    found.extend(page.items.into_iter().map(|object| object.name));
+                    match page.next_cursor {
+                        Some(next) => cursor = Some(next),
+                        None => break,
+                    }
+                }
+                assert_eq!(found, expected, "{:?} {}", filter, sort);
+            }
+        }
+    })
+    .await;
+}
+
+#[actix_web::test]
+async fn test_task_filter_combinations_page_by_cursor() {
+    for_each_backend(async |pool| {
+        let employees = add_employees(&pool, 1).await;
+        let repository = TaskRepository::new(pool);
+        let statuses = ["ToDo", "InProgress"];
+        let priorities = [Some("High"), Some("Low"), None];
+        let mut seeded = Vec::new();
+        for n in 0..12 {
+            let title = format!("{} {:02}", if n % 4 < 2 { "Fridge" } else { "Till" }, n);
+            let status = statuses[n % 2];
+            let priority = priorities[n % 3];
+            let store_id = 1 + (n / 6) as i32;
+            let assigned_to = (n % 5 != 0).then_some(employees[0]);
+            let task = repository
+                .create(
+                    from_json(json!({
+                        "title": title,
+                        "status": status,
+                        "priority_level": priority,
+                        "store_id": store_id,
+                        "assigned_to": assigned_to,
+                    })),
+                    &actor(),
+                )
+                .await
+                .unwrap();
+            seeded.push((task.id, title, status, priority, store_id, assigned_to));
+        }
+        let rank = |priority: Option<&str>| match priority {
+            Some("High") => 3,
+            Some("Medium") => 2,
+            Some("Low") => 1,
+            _ => 0,
+        };
+
+        for mask in 0..32u8 {
+            let title = (mask & 1 != 0).then_some("fridge");
+            let status = (mask & 2 != 0).then_some("ToDo");
+            let priority = (mask & 4 != 0).then_some("High");
+            let store_id = (mask & 8 != 0).then_some(1);
+            let assigned_to = (mask & 16 != 0).then_some(employees[0]);
+
+            let mut expected: Vec<_> = seeded
+                .iter()
+                .filter(|(_, t, s, p, store, assignee)| {
+                    title.is_none_or(|title| t.to_lowercase().contains(title))
+                        && status.is_none_or(|status| *s == status)
+                        && priority.is_none_or(|priority| *p == Some(priority))
+                        && store_id.is_none_or(|id| *store == id)
+                        && assigned_to.is_none_or(|id| *assignee == Some(id))
+                })
+                .collect();
+            expected
+                .sort_by_key(|(id, t, _, p, _, _)| (std::cmp::Reverse(rank(*p)), t.clone(), *id));
+            let expected: Vec<i32> = expected.iter().map(|(id, ..)| *id).collect();
+
+            let mut found = Vec::new();
+            let mut cursor: Option<String> = None;
+            loop {
+                let page = repository
+                    .find_all(from_json(json!({
+                        "title": title,
+                        "status": status,
+                        "priority_level": priority,
+                        "store_id": store_id,
+                        "assigned_to": assigned_to,
+                        "sort": "-priority_level,title",
+                        "limit": 2,
+                        "cursor": cursor,
+                    })))
+                    .await
+                    .unwrap();
+                assert_eq!(page.total, Some(expected.len()), "filter mask {:05b}", mask);
+                found.extend(page.items.iter().map(|task| task.id));
+                match page.next_cursor {
+                    Some(next) => cursor = Some(next),
+                    None => break,
+                }
+            }
+            assert_eq!(found, expected, "filter mask {:05b}", mask);
+        }
+    })
+    .await;
+}
+
+#[actix_web::test]
+async fn test_employee_workload_counts_open_tasks() {
+    for_each_backend(async |pool| {
+        let employees = add_employees(&pool, 2).await;
+        let tasks = TaskRepository::new(pool.clone());
+        for status in ["ToDo", "InProgress", "Done"] {
+            tasks
+                .create(
+                    from_json(json!({
+                        "title": status,
+                        "status": status,
+                        "assigned_to": employees[0],
+                        "store_id": 1,
+                    })),
+                    &actor(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let repository = EmployeeRepository::new(pool);
+        repository.deactivate(employees[1]).await.unwrap();
+        let workload = repository.workload(Some(1)).await.unwrap();
+
+        let busy = workload
+            .employees
+            .iter()
+            .find(|employee| employee.employee_id == employees[0])
+            .unwrap();
+        assert_eq!(busy.open_tasks, 2);
+        assert_eq!(workload.stores[0].open_tasks, 2);
+    })
+    .await;
+}
+
+#[actix_web::test]
+async fn test_employee_import_keeps_columns_missing_from_csv() {
+    for_each_backend(async |pool| {
+        let service = EmployeeService::new(EmployeeRepository::new(pool));
+        let import = async |csv: &str| {
+            service
+                .import_employees(csv.as_bytes(), from_json(json!({ "background": false })))
+                .await
+                .unwrap();
+        };
+        import("external_id,first_name,last_name,store_id,email\nEMP001,John,Smith,1,john@example.com\nEMP002,Sarah,Jones,2,\n").await;
+        import("external_id,first_name,last_name,store_id\nEMP001,Johnny,Smith,3\n").await;
+
+        let page = service
+            .get_employees(from_json(json!({ "sort": "external_id" })))
+            .await
+            .unwrap();
+        let employees: Vec<_> = page
+            .items
+            .iter()
+            .map(|e| (e.first_name.as_str(), e.store_id, e.email.as_deref()))
+            .collect();
+        assert_eq!(
+            employees,
+            [
+                ("Johnny", Some(3), Some("john@example.com")),
+                ("Sarah", Some(2), None),
+            ]
+        );
+    })
+    .await;
+}
+
+#[actix_web::test]
+async fn test_employee_import_without_store_ids_keeps_existing_stores() {
+    for_each_backend(async |pool| {
+        let service = EmployeeService::new(EmployeeRepository::new(pool));
+        let import = async |csv: &str| match service
+            .import_employees(csv.as_bytes(), from_json(json!({ "background": false })))
+            .await
+            .unwrap()
+        {
+            ImportOutcome::Completed(report) => report,
+            ImportOutcome::Queued(_) => panic!("import ran in the background"),
+        };
+        import("external_id,first_name,last_name,store_id\nEMP001,John,Smith,1\nEMP002,Sarah,Jones,2\n").await;
+
+        let report = import(
+            "external_id,first_name,last_name\nEMP001,Johnny,Smith\nEMP002,Sara,Jones\nEMP003,New,Hire\n",
+        )
+        .await;
+        assert_eq!(report.updated, ["EMP001", "EMP002"]);
+        assert!(report.created.is_empty());
+        assert_eq!(report.rejected.len(), 1);
+        assert_eq!(
+            (report.rejected[0].line, report.rejected[0].error.as_str()),
+            (4, "Store ID is required")
+        );
+
+        let report = import("external_id,first_name,last_name,store_id\nEMP001,John,Smith,\n").await;
+        assert_eq!(report.rejected[0].error, "Store ID is required");
+
+        let page = service
+            .get_employees(from_json(json!({ "sort": "external_id" })))
+            .await
+            .unwrap();
+        let employees: Vec<_> = page
+            .items
+            .iter()
+            .map(|e| (e.first_name.as_str(), e.store_id))
+            .collect();
+        assert_eq!(employees, [("Johnny", Some(1)), ("Sara", Some(2))]);
+    })
+    .await;
+}
+
+#[actix_web::test]
+async fn test_task_lifecycle() {
+    for_each_backend(async |pool| {
+        let employees = add_employees(&pool, 1).await;
+        let repository = TaskRepository::new(pool);
+        let parent = repository
+            .create(from_json(json!({ "title": "Parent" })), &actor())
+            .await
+            .unwrap();
+        let child = repository
+            .create(
+                from_json(json!({
+                    "title": "Child",
+                    "parent_id": parent.id,
+                    "assigned_to": employees[0],
+                    "due_at": "2026-03-02T09:00:00Z",
+                })),
+                &actor(),
+            )
+            .await
+            .unwrap();
+
+        repository
+            .add_dependency(parent.id, child.id, &actor())
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository
+                .add_dependency(child.id, parent.id, &actor())
+                .await,
+            Err(ApiError::ValidationError(_))
+        ));
+        repository
+            .set_tags(child.id, vec!["fridge".to_string()], &actor())
+            .await
+            .unwrap();
+
+        for status in ["InProgress", "Done"] {
+            repository
+                .transition(child.id, from_json(json!({ "status": status })), &actor())
+                .await
+                .unwrap();
+        }
+        let done = repository.find_by_id(child.id).await.unwrap();
+        assert!(done.completed_at.is_some());
+        let tree = repository.tree(parent.id, false).await.unwrap();
+        assert_eq!(tree.subtasks[0].task.id, child.id);
+        assert!(tree.blocked_by.is_empty());
+        assert_eq!(repository.history(child.id).await.unwrap().len(), 4);
+
+        let page = repository
+            .find_all(from_json(
+                json!({ "tags": "fridge", "sort": "-completed_at" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(page.items.len(), 1);
+        let first = repository
+            .find_all(from_json(json!({ "sort": "-completed_at", "limit": 1 })))
+            .await
+            .unwrap();
+        assert_eq!(first.items[0].id, child.id);
+        let second = repository
+            .find_all(from_json(json!({
+                "sort": "-completed_at",
+                "limit": 1,
+                "cursor": first.next_cursor.unwrap(),
+            })))
+            .await
+            .unwrap();
+        assert_eq!(second.items[0].id, parent.id);
+
+        let exported: Vec<i32> = repository
+            .export(from_json(json!({ "sort": "id" })))
+            .unwrap()
+            .map(|task| task.unwrap().id)
+            .collect()
+            .await;
+        assert_eq!(exported, [parent.id, child.id]);
+
+        repository.delete(child.id, &actor()).await.unwrap();
+        let purged = repository
+            .purge_deleted(Utc::now() + Duration::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(purged.count, 1);
+        assert!(
+            repository
+                .dependencies(parent.id)
+                .await
+                .unwrap()
+                .blocked_by
+                .is_empty()
+        );
+    })
+    .await;
+}
+
+fn attachment_service<S: BlobStorage>(pool: &DbPool, storage: S) -> AttachmentService<S> {
+    AttachmentService::new(
+        AttachmentRepository::new(pool.clone()),
+        TaskRepository::new(pool.clone()),
+        storage,
+        AttachmentLimits::default(),
+    )
+}
+
+fn temp_storage() -> LocalStorage {
+    LocalStorage::new(std::env::temp_dir().join(format!("attachments-{}", uuid::Uuid::new_v4())))
+}
+
+/// Local storage that announces a deletion and then waits before carrying it
+/// out, so a test can start an upload in between.
+#[derive(Clone)]
+struct SlowDeletes {
+    storage: LocalStorage,
+    deleting: Arc<Notify>,
+}
+
+impl BlobStorage for SlowDeletes {
+    async fn put(&self, data: Bytes) -> Result<String, ApiError> {
+        self.storage.put(data).await
+    }
+
+    async fn read(&self, key: &str, start: u64, end: u64) -> Result<Bytes, ApiError> {
+        self.storage.read(key, start, end).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), ApiError> {
+        self.deleting.notify_waiters();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        self.storage.delete(key).await
+    }
+}
+
+#[actix_web::test]
+async fn test_purge_removes_unreferenced_attachment_contents() {
+    for_each_backend(async |pool| {
+        let tasks = task_service(&pool);
+        let attachments = attachment_service(&pool, temp_storage());
+        let purged = tasks
+            .create_task(from_json(json!({ "title": "Purged" })), &actor())
+            .await
+            .unwrap();
+        let kept = tasks
+            .create_task(from_json(json!({ "title": "Kept" })), &actor())
+            .await
+            .unwrap();
+
+        let upload = async |task_id: i32, data: &'static [u8]| {
+            attachments
+                .create_attachment(task_id, None, Bytes::from_static(data), &actor())
+                .await
+                .unwrap()
+        };
+        let shared = upload(kept.id, b"%PDF-shared").await;
+        upload(purged.id, b"%PDF-shared").await;
+        let unique = upload(purged.id, b"%PDF-unique").await;
+
+        tasks.delete_task(purged.id, &actor()).await.unwrap();
+        let count = tasks
+            .purge_deleted_tasks(Utc::now() + Duration::seconds(1), &attachments)
+            .await
+            .unwrap();
+
+        assert_eq!(count, 1);
+        assert_eq!(
+            attachments.read_attachment(&shared, 0, 3).await.unwrap(),
+            "%PDF"
+        );
+        assert!(matches!(
+            attachments.read_attachment(&unique, 0, 3).await,
+            Err(ApiError::NotFound(_))
+        ));
+    })
+    .await;
+}
+
+#[actix_web::test]
+async fn test_shared_attachment_contents_outlive_each_reference() {
+    for_each_backend(async |pool| {
+        let tasks = TaskRepository::new(pool.clone());
+        let mut ids = Vec::new();
+        for title in ["First", "Second"] {
+            let task = tasks
+                .create(from_json(json!({ "title": title })), &actor())
+                .await
+                .unwrap();
+            ids.push(task.id);
+        }
+        let deleting = Arc::new(Notify::new());
+        let storage = SlowDeletes {
+            storage: temp_storage(),
+            deleting: deleting.clone(),
+        };
+        let attachments = attachment_service(&pool, storage);
+        let upload = async |task_id: i32| {
+            attachments
+                .create_attachment(task_id, None, Bytes::from_static(b"%PDF-1.7"), &actor())
+                .await
+                .unwrap()
+        };
+        let delete = async |attachment: &TaskAttachment| {
+            attachments
+                .delete_attachment(attachment.task_id, attachment.id, &actor())
+                .await
+                .unwrap()
+        };
+
+        let first = upload(ids[0]).await;
+        let second = upload(ids[1]).await;
+        delete(&first).await;
+        assert_eq!(
+            attachments.read_attachment(&second, 0, 3).await.unwrap(),
+            "%PDF"
+        );
+        delete(&second).await;
+        assert!(matches!(
+            attachments.read_attachment(&second, 0, 3).await,
+            Err(ApiError::NotFound(_))
+        ));
+
+        // An upload that starts while the last attachment with the same
+        // contents is being deleted must still find them stored.
+        let previous = upload(ids[0]).await;
+        let (_, current) = futures_util::join!(delete(&previous), async {
+            deleting.notified().await;
+            upload(ids[1]).await
+        });
+        assert_eq!(
+            attachments.read_attachment(&current, 0, 3).await.unwrap(),
+            "%PDF"
+        );
+    })
+    .await;
+}
+
+#[actix_web::test]
+async fn test_attachments_are_deleted_by_their_uploader_or_an_admin() {
+    for_each_backend(async |pool| {
+        let tasks = task_service(&pool);
+        let attachments = attachment_service(&pool, temp_storage());
+        let task = tasks
+            .create_task(from_json(json!({ "title": "Attached" })), &actor())
+            .await
+            .unwrap();
+        let uploader = user("uploader", "user");
+        let (admin, clerk) = (user("admin", "admin"), user("clerk", "user"));
+        let upload = async |data: &'static [u8]| {
+            attachments
+                .create_attachment(task.id, None, Bytes::from_static(data), &uploader)
+                .await
+                .unwrap()
+        };
+        let first = upload(b"%PDF-first").await;
+        let second = upload(b"%PDF-second").await;
+
+        assert!(matches!(
+            attachments
+                .delete_attachment(task.id, first.id, &clerk)
+                .await,
+            Err(ApiError::Forbidden(_))
+        ));
+        attachments.get_attachment(task.id, first.id).await.unwrap();
+
+        attachments
+            .delete_attachment(task.id, first.id, &uploader)
+            .await
+            .unwrap();
+        attachments
+            .delete_attachment(task.id, second.id, &admin)
+            .await
+            .unwrap();
+        assert!(
+            attachments
+                .get_attachments(task.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    })
+    .await;
+}
+
+#[actix_web::test]
+async fn test_deleted_task_history_is_for_admins() {
+    for_each_backend(async |pool| {
+        let tasks = task_service(&pool);
+        let task = tasks
+            .create_task(from_json(json!({ "title": "Deleted" })), &actor())
+            .await
+            .unwrap();
+        let (admin, clerk) = (user("admin", "admin"), user("clerk", "user"));
+        assert_eq!(
+            tasks.get_task_history(task.id, &clerk).await.unwrap().len(),
+            1
+        );
+
+        tasks.delete_task(task.id, &actor()).await.unwrap();
+
+        assert!(matches!(
+            tasks.get_task_history(task.id, &clerk).await,
+            Err(ApiError::Forbidden(_))
+        ));
+        assert_eq!(
+            tasks.get_task_history(task.id, &admin).await.unwrap().len(),
+            2
+        );
+    })
+    .await;
+}
+
+#[actix_web::test]
+async fn test_deleted_tasks_are_for_admins() {
+    for_each_backend(async |pool| {
+        let tasks = task_service(&pool);
+        let task = tasks
+            .create_task(from_json(json!({ "title": "Deleted" })), &actor())
+            .await
+            .unwrap();
+        tasks.delete_task(task.id, &actor()).await.unwrap();
+        let (admin, clerk) = (user("admin", "admin"), user("clerk", "user"));
+        let with_deleted = json!({ "include_deleted": true });
+
+        assert!(matches!(
+            tasks
+                .get_tasks(from_json(with_deleted.clone()), &clerk)
+                .await,
+            Err(ApiError::Forbidden(_))
+        ));
+        assert!(matches!(
+            tasks
+                .get_task(task.id, from_json(with_deleted.clone()), &clerk)
+                .await,
+            Err(ApiError::Forbidden(_))
+        ));
+        assert!(matches!(
+            tasks.restore_task(task.id, &clerk).await,
+            Err(ApiError::Forbidden(_))
+        ));
+
+        let page = tasks
+            .get_tasks(from_json(with_deleted.clone()), &admin)
+            .await
+            .unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert!(page.items[0].deleted_at.is_some());
+        tasks
+            .get_task(task.id, from_json(with_deleted), &admin)
+            .await
+            .unwrap();
+        let restored = tasks.restore_task(task.id, &admin).await.unwrap();
+        assert_eq!(restored.deleted_at, None);
+    })
+    .await;
+}
+
+fn comment_service(pool: &DbPool) -> CommentService {
+    CommentService::new(
+        CommentRepository::new(pool.clone()),
+        TaskRepository::new(pool.clone()),
+        EmployeeRepository::new(pool.clone()),
+    )
+}
+
+#[actix_web::test]
+async fn test_comments_on_deleted_tasks_are_not_found() {
+    for_each_backend(async |pool| {
+        let tasks = task_service(&pool);
+        let comments = comment_service(&pool);
+        let task = tasks
+            .create_task(from_json(json!({ "title": "Deleted" })), &actor())
+            .await
+            .unwrap();
+        let comment = comments
+            .create_comment(task.id, from_json(json!({ "body": "First" })), &actor())
+            .await
+            .unwrap();
+        comments
+            .update_comment(
+                task.id,
+                comment.id,
+                from_json(json!({ "body": "Second" })),
+                &actor(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            comments
+                .get_comment_revisions(task.id, comment.id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        tasks.delete_task(task.id, &actor()).await.unwrap();
+
+        assert!(matches!(
+            comments.get_comment(task.id, comment.id).await,
+            Err(ApiError::NotFound(_))
+        ));
+        assert!(matches!(
+            comments.get_comment_revisions(task.id, comment.id).await,
+            Err(ApiError::NotFound(_))
+        ));
+    })
+    .await;
+}
+
+#[actix_web::test]
+async fn test_comments_are_changed_by_their_author() {
+    for_each_backend(async |pool| {
+        let tasks = task_service(&pool);
+        let comments = comment_service(&pool);
+        let task = tasks
+            .create_task(from_json(json!({ "title": "Discussed" })), &actor())
+            .await
+            .unwrap();
+        let (author, clerk) = (user("author", "user"), user("clerk", "user"));
+        let comment = comments
+            .create_comment(task.id, from_json(json!({ "body": "First" })), &author)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            comments
+                .update_comment(
+                    task.id,
+                    comment.id,
+                    from_json(json!({ "body": "Hijacked" })),
+                    &clerk,
+                )
+                .await,
+            Err(ApiError::Forbidden(_))
+        ));
+        assert!(matches!(
+            comments.delete_comment(task.id, comment.id, &clerk).await,
+            Err(ApiError::Forbidden(_))
+        ));
+
+        let updated = comments
+            .update_comment(
+                task.id,
+                comment.id,
+                from_json(json!({ "body": "Second" })),
+                &author,
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.body, "Second");
+        comments
+            .delete_comment(task.id, comment.id, &author)
+            .await
+            .unwrap();
+        assert!(matches!(
+            comments.get_comment(task.id, comment.id).await,
+            Err(ApiError::NotFound(_))
+        ));
+    })
+    .await;
+}
+
+#[actix_web::test]
+async fn test_missing_employee_references_are_conflicts() {
+    for_each_backend(async |pool| {
+        let error = database::query(
+            "INSERT INTO tasks (title, status, assigned_to, created_at, updated_at) VALUES ('Orphan', 'ToDo', 42, ?, ?)",
+        )
+        .bind(Utc::now())
+        .bind(Utc::now())
+        .execute(&pool)
+        .await
+        .map_err(ApiError::from)
+        .unwrap_err();
+        assert!(matches!(error, ApiError::Conflict(_)), "{:?}", error);
+
+        let error = database::query(
+            "INSERT INTO employees (first_name, last_name, store_id, manager_id) VALUES ('Test', 'Employee', 1, 42)",
+        )
+        .execute(&pool)
+        .await
+        .map_err(ApiError::from)
+        .unwrap_err();
+        assert!(matches!(error, ApiError::Conflict(_)), "{:?}", error);
+    })
+    .await;
+}
+
+#[actix_web::test]
+async fn test_board_moves_tasks_between_columns() {
+    for_each_backend(async |pool| {
+        let repository = TaskRepository::new(pool);
+        let mut ids = Vec::new();
+        for title in ["First", "Second", "Third"] {
+            let task = repository
+                .create(from_json(json!({ "title": title })), &actor())
+                .await
+                .unwrap();
+            ids.push(task.id);
+        }
+
+        repository
+            .move_task(ids[2], from_json(json!({ "before_id": ids[0] })), &actor())
+            .await
+            .unwrap();
+        repository
+            .move_task(
+                ids[1],
+                from_json(json!({ "status": "InProgress" })),
+                &actor(),
+            )
+            .await
+            .unwrap();
+
+        let board = repository
+            .board(&from_json(json!({ "swimlane": "priority" })))
+            .await
+            .unwrap();
+        let columns = &board.lanes[0].columns;
+        let column = |status: TaskStatus| {
+            let column = columns.iter().find(|c| c.status == status).unwrap();
+            column.tasks.iter().map(|task| task.id).collect::<Vec<_>>()
+        };
+        assert_eq!(column(TaskStatus::ToDo), [ids[2], ids[0]]);
+        assert_eq!(column(TaskStatus::InProgress), [ids[1]]);
+    })
+    .await;
+}
+
+#[actix_web::test]
+async fn test_report_groups_by_period_and_measures_cycle_time() {
+    for_each_backend(async |pool| {
+        let tasks = TaskRepository::new(pool.clone());
+        for (created_at, hours) in [
+            ("2026-03-02T08:00:00Z", Some(2)),
+            ("2026-03-04T08:00:00Z", Some(4)),
+            ("2026-03-09T08:00:00Z", None),
+        ] {
+            let task = tasks
+                .create(from_json(json!({ "title": created_at })), &actor())
+                .await
+                .unwrap();
+            let created_at = at(created_at);
+            let (status, completed_at) = match hours {
+                Some(hours) => ("Done", Some(created_at + Duration::hours(hours))),
+                None => ("ToDo", None),
+            };
+            database::query(
+                "UPDATE tasks SET status = ?, created_at = ?, completed_at = ? WHERE id = ?",
+            )
+            .bind(status)
+            .bind(created_at)
+            .bind(completed_at)
+            .bind(task.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let repository = ReportRepository::new(pool);
+        let report = repository
+            .task_report(&from_json(json!({ "group_by": "week" })))
+            .await
+            .unwrap();
+        let weeks: Vec<_> = report
+            .groups
+            .iter()
+            .map(|row| (row.key.clone(), row.total, row.completed))
+            .collect();
+        assert_eq!(
+            weeks,
+            [
+                (Some(ReportKey::Period("2026-03-02".to_string())), 2, 2),
+                (Some(ReportKey::Period("2026-03-09".to_string())), 1, 0),
+            ]
+        );
+        let hours = |value: Option<f64>| (value.unwrap() * 1000.0).round() / 1000.0;
+        assert_eq!(hours(report.groups[0].mean_cycle_time_hours), 3.0);
+        assert_eq!(hours(report.groups[0].p90_cycle_time_hours), 4.0);
+        assert_eq!(report.totals.open, 1);
+
+        let months = repository
+            .task_report(&from_json(json!({ "group_by": "month" })))
+            .await
+            .unwrap();
+        assert_eq!(
+            months.groups[0].key,
+            Some(ReportKey::Period("2026-03".to_string()))
+        );
+    })
+    .await;
+}
+
+/// Inserts a task directly so its timestamps can be fixed. `cycle_hours`
+/// completes it that long after creation.
+async fn insert_report_task(
+    pool: &DbPool,
+    (store_id, priority, status): (i32, &str, &str),
+    created_at: &str,
+    cycle_hours: Option<i64>,
+) {
+    let created_at = at(created_at);
+    database::query(
+        r#"
+        INSERT INTO tasks (title, priority_level, status, store_id, due_at, completed_at, created_at, updated_at)
+        VALUES ('Task', ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(priority)
+    .bind(status)
+    .bind(store_id)
+    .bind(created_at + Duration::days(1))
+    .bind(cycle_hours.map(|hours| created_at + Duration::hours(hours)))
+    .bind(created_at)
+    .bind(created_at)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+#[actix_web::test]
+async fn test_task_report_counts_throughput_and_cycle_times() {
+    for_each_backend(async |pool| {
+        for (task, created_at, cycle_hours) in [
+            ((2, "High", "Done"), "2026-03-02T00:00:00Z", Some(10)),
+            ((2, "High", "Done"), "2026-03-03T00:00:00Z", Some(20)),
+            ((2, "Low", "Done"), "2026-03-04T00:00:00Z", Some(30)),
+            ((1, "High", "InProgress"), "2026-03-05T00:00:00Z", None),
+            ((2, "High", "Cancelled"), "2026-03-02T12:00:00Z", None),
+            ((2, "High", "Done"), "2026-03-10T00:00:00Z", Some(40)),
+            ((2, "High", "Done"), "2026-03-03T00:00:00Z", Some(1)),
+        ] {
+            insert_report_task(&pool, task, created_at, cycle_hours).await;
+        }
+        database::query("UPDATE tasks SET deleted_at = ? WHERE id = 7")
+            .bind(at("2026-03-04T00:00:00Z"))
+            .execute(&pool)
+            .await
+            .unwrap();
+        let repository = ReportRepository::new(pool);
+        let hours = |value: Option<f64>| (value.unwrap() * 1000.0).round() / 1000.0;
+
+        let report = repository
+            .task_report(&from_json(json!({
+                "store_id": 2,
+                "priority_level": "High",
+                "date_field": "completed",
+                "from": "2026-03-02T00:00:00Z",
+                "to": "2026-03-09T00:00:00Z",
+            })))
+            .await
+            .unwrap();
+        assert_eq!((report.totals.total, report.totals.completed), (2, 2));
+        assert_eq!(hours(report.totals.mean_cycle_time_hours), 15.0);
+        assert_eq!(hours(report.totals.p90_cycle_time_hours), 20.0);
+        assert!(report.groups.is_empty());
+
+        let report = repository
+            .task_report(&from_json(json!({ "group_by": "priority" })))
+            .await
+            .unwrap();
+        let totals = &report.totals;
+        assert_eq!(
+            (
+                totals.total,
+                totals.open,
+                totals.completed,
+                totals.cancelled,
+                totals.overdue
+            ),
+            (6, 1, 4, 1, 1)
+        );
+        assert_eq!(hours(totals.mean_cycle_time_hours), 25.0);
+        assert_eq!(hours(totals.p90_cycle_time_hours), 40.0);
+        let keys: Vec<_> = report.groups.iter().map(|row| row.key.clone()).collect();
+        assert_eq!(
+            keys,
+            [
+                Some(ReportKey::Priority(PriorityLevel::High)),
+                Some(ReportKey::Priority(PriorityLevel::Low))
+            ]
+        );
+        assert_eq!(report.groups[0].completed, 3);
+        assert_eq!(hours(report.groups[0].p90_cycle_time_hours), 40.0);
+        assert_eq!(hours(report.groups[1].mean_cycle_time_hours), 30.0);
+
+        let report = repository
+            .task_report(&from_json(json!({ "group_by": "week" })))
+            .await
+            .unwrap();
+        let weeks: Vec<_> = report
+            .groups
+            .iter()
+            .map(|row| (row.key.clone(), row.total))
+            .collect();
+        assert_eq!(
+            weeks,
+            [
+                (Some(ReportKey::Period("2026-03-02".to_string())), 5),
+                (Some(ReportKey::Period("2026-03-09".to_string())), 1)
+            ]
+        );
+
+        let report = repository
+            .task_report(&from_json(
+                json!({ "group_by": "store", "status": "InProgress" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(report.groups.len(), 1);
+        assert_eq!(report.groups[0].key, Some(ReportKey::Id(1)));
+    })
+    .await;
+}
+
+#[actix_web::test]
+async fn test_templates_materialize_once() {
+    for_each_backend(async |pool| {
+        let repository = TaskTemplateRepository::new(pool);
+        let request: CreateTaskTemplateRequest = from_json(json!({
+            "title": "Check fridges",
+            "store_ids": [1, 2],
+            "rrule": "FREQ=DAILY",
+            "starts_on": "2026-03-01",
+        }));
+        let template = repository.create(request).await.unwrap();
+        let dates = ["2026-03-01".parse().unwrap()];
+
+        let first = repository.materialize(&template, &dates).await.unwrap();
+        let second = repository.materialize(&template, &dates).await.unwrap();
+
+        assert_eq!(first.created_task_ids.len(), 2);
+        assert_eq!(second.existing, 2);
+    })
+    .await;
+}
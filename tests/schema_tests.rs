@@ -0,0 +1,165 @@
+mod common;
+
+use common::for_each_backend;
+use rust_api_framework::database::{self, Backend, DbPool, MIGRATIONS};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Files allowed to declare tables outside `migrations/`: the frozen schema
+/// that pre-versioning databases are upgraded from, and the `_migrations`
+/// bookkeeping table.
+const SCHEMA_OWNERS: &[&str] = &["src/database/legacy.rs", "src/database/migrations.rs"];
+
+fn rust_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            rust_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+}
+
+/// Table names created by the backend's migrations, minus those a later one
+/// drops, under their names after any renames.
+fn migrated_tables(backend: Backend) -> BTreeSet<String> {
+    let mut tables = BTreeSet::new();
+    for migration in MIGRATIONS {
+        let sql: String = migration
+            .scripts(backend)
+            .up
+            .lines()
+            .filter(|line| !line.trim_start().starts_with("--"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        for statement in sql.split(';') {
+            let words: Vec<String> = statement
+                .split_whitespace()
+                .map(|word| word.to_uppercase())
+                .collect();
+            let name = |start: usize| {
+                let name = match words.get(start..start + 3) {
+                    Some([a, b, c]) if a == "IF" && b == "NOT" && c == "EXISTS" => {
+                        words.get(start + 3)
+                    }
+                    Some([a, b, ..]) if a == "IF" && b == "EXISTS" => words.get(start + 2),
+                    _ => words.get(start),
+                };
+                name.unwrap().trim_end_matches('(').to_lowercase()
+            };
+            match words.as_slice() {
+                [create, table, ..] if create == "CREATE" && table == "TABLE" => {
+                    tables.insert(name(2));
+                }
+                [drop, table, ..] if drop == "DROP" && table == "TABLE" => {
+                    tables.remove(&name(2));
+                }
+                [alter, table, from, rename, to, new, ..]
+                    if alter == "ALTER" && table == "TABLE" && rename == "RENAME" && to == "TO" =>
+                {
+                    tables.remove(&from.to_lowercase());
+                    tables.insert(new.to_lowercase());
+                }
+                _ => {}
+            }
+        }
+    }
+    tables
+}
+
+#[test]
+fn test_tables_are_only_declared_in_migrations() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut files = Vec::new();
+    rust_files(&root.join("src"), &mut files);
+    rust_files(&root.join("tests"), &mut files);
+
+    let offenders: Vec<String> = files
+        .iter()
+        .filter(|path| !path.ends_with(file!()))
+        .map(|path| {
+            path.strip_prefix(root)
+                .unwrap()
+                .to_string_lossy()
+                .replace('\\', "/")
+        })
+        .filter(|path| !SCHEMA_OWNERS.contains(&path.as_str()))
+        .filter(|path| {
+            fs::read_to_string(root.join(path))
+                .unwrap()
+                .to_uppercase()
+                .contains("CREATE TABLE")
+        })
+        .collect();
+
+    assert!(
+        offenders.is_empty(),
+        "Tables must be created by a migration in migrations/, not in {:?}",
+        offenders
+    );
+}
+
+#[test]
+fn test_migration_files_are_all_registered() {
+    for (backend, dir) in [(Backend::Sqlite, "sqlite"), (Backend::Postgres, "postgres")] {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("migrations")
+            .join(dir);
+        let files: BTreeSet<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+
+        let mut registered = BTreeSet::new();
+        for migration in MIGRATIONS {
+            let stem = format!("{:04}_{}", migration.version, migration.name);
+            registered.insert(format!("{}.up.sql", stem));
+            if migration.scripts(backend).down.is_some() {
+                registered.insert(format!("{}.down.sql", stem));
+            }
+        }
+
+        assert_eq!(files, registered, "{:?}", backend);
+    }
+    assert!(MIGRATIONS.windows(2).all(|w| w[0].version < w[1].version));
+}
+
+#[test]
+fn test_backends_migrate_to_the_same_tables() {
+    assert!(migrated_tables(Backend::Sqlite).contains("tasks"));
+    assert_eq!(
+        migrated_tables(Backend::Sqlite),
+        migrated_tables(Backend::Postgres)
+    );
+}
+
+async fn database_tables(pool: &DbPool) -> BTreeSet<String> {
+    let sql = match pool.backend() {
+        Backend::Sqlite => {
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name != '_migrations'"
+        }
+        Backend::Postgres => {
+            "SELECT CAST(table_name AS TEXT) FROM information_schema.tables WHERE table_schema = current_schema() AND table_name != '_migrations'"
+        }
+    };
+
+    database::query_scalar::<String>(sql)
+        .fetch_all(pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .collect()
+}
+
+#[actix_web::test]
+async fn test_migrated_database_has_only_migrated_tables() {
+    for_each_backend(async |pool| {
+        assert_eq!(
+            database_tables(&pool).await,
+            migrated_tables(pool.backend())
+        );
+    })
+    .await;
+}